@@ -37,7 +37,14 @@ impl Component for Isometric {
         _layout: &Layout,
     ) -> KludgineResult<()> {
         let map = self.map.as_ref().unwrap();
-        map.draw(context.scene(), Point::default()).await?;
+        // Center the top corner of the map horizontally
+        let scene_size = context.scene().size().await;
+        let top_corner = map.tile_bounds(Point::new(0, 0)).center().x;
+        map.draw(
+            context.scene(),
+            Point::new(scene_size.width / 2. - top_corner, 0.),
+        )
+        .await?;
 
         Ok(())
     }
@@ -46,13 +53,16 @@ impl Component for Isometric {
 impl Isometric {
     async fn load_assets(&mut self) -> KludgineResult<()> {
         let texture = Texture::load("examples/assets/isometric_tile.png")?;
+        let texture_height = texture.size().height;
         let sprite = Sprite::single_frame(texture).await;
 
-        let mut map = PersistentTileMap::persistent_with_size(
-            Size::new(126, 62),
-            Size::new(MAP_SIZE, MAP_SIZE),
-        );
-        map.set_stagger(Size::new(63, 31));
+        // The tile's image is taller than the diamond it sits on, so it
+        // overlaps the tiles behind it.
+        let tile_size = Size::new(126, 62);
+        let mut map =
+            PersistentTileMap::persistent_with_size(tile_size, Size::new(MAP_SIZE, MAP_SIZE));
+        map.set_projection(TileProjection::Isometric);
+        map.set_sprite_overhang(texture_height - tile_size.height);
         for x in 0..MAP_SIZE {
             for y in 0..MAP_SIZE {
                 map.set(Point::new(x, y), Some(sprite.new_instance().await));
@@ -67,6 +67,8 @@ impl Component for SpriteSheetExample {
                 .map(|source| SpriteFrame {
                     source,
                     duration: Some(Duration::from_millis(500)),
+                    pivot: None,
+                    events: Vec::new(),
                 })
                 .collect(),
             AnimationMode::Forward,
@@ -83,6 +85,8 @@ impl Component for SpriteSheetExample {
                 .map(|source| SpriteFrame {
                     source,
                     duration: Some(Duration::from_millis(200)),
+                    pivot: None,
+                    events: Vec::new(),
                 })
                 .collect(),
             AnimationMode::PingPong,
@@ -99,6 +103,8 @@ impl Component for SpriteSheetExample {
                 .map(|source| SpriteFrame {
                     source,
                     duration: Some(Duration::from_millis(200)),
+                    pivot: None,
+                    events: Vec::new(),
                 })
                 .collect(),
             AnimationMode::PingPong,
@@ -98,6 +98,161 @@ impl Color {
         self.0.a = alpha;
         self
     }
+
+    /// Looks up a color by its CSS name, ignoring case
+    pub fn named(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "aliceblue" => Some(Self::ALICEBLUE),
+            "antiquewhite" => Some(Self::ANTIQUEWHITE),
+            "aqua" => Some(Self::AQUA),
+            "aquamarine" => Some(Self::AQUAMARINE),
+            "azure" => Some(Self::AZURE),
+            "beige" => Some(Self::BEIGE),
+            "bisque" => Some(Self::BISQUE),
+            "black" => Some(Self::BLACK),
+            "blanchedalmond" => Some(Self::BLANCHEDALMOND),
+            "blue" => Some(Self::BLUE),
+            "blueviolet" => Some(Self::BLUEVIOLET),
+            "brown" => Some(Self::BROWN),
+            "burlywood" => Some(Self::BURLYWOOD),
+            "cadetblue" => Some(Self::CADETBLUE),
+            "chartreuse" => Some(Self::CHARTREUSE),
+            "chocolate" => Some(Self::CHOCOLATE),
+            "coral" => Some(Self::CORAL),
+            "cornflowerblue" => Some(Self::CORNFLOWERBLUE),
+            "cornsilk" => Some(Self::CORNSILK),
+            "crimson" => Some(Self::CRIMSON),
+            "cyan" => Some(Self::CYAN),
+            "darkblue" => Some(Self::DARKBLUE),
+            "darkcyan" => Some(Self::DARKCYAN),
+            "darkgoldenrod" => Some(Self::DARKGOLDENROD),
+            "darkgray" => Some(Self::DARKGRAY),
+            "darkgreen" => Some(Self::DARKGREEN),
+            "darkgrey" => Some(Self::DARKGREY),
+            "darkkhaki" => Some(Self::DARKKHAKI),
+            "darkmagenta" => Some(Self::DARKMAGENTA),
+            "darkolivegreen" => Some(Self::DARKOLIVEGREEN),
+            "darkorange" => Some(Self::DARKORANGE),
+            "darkorchid" => Some(Self::DARKORCHID),
+            "darkred" => Some(Self::DARKRED),
+            "darksalmon" => Some(Self::DARKSALMON),
+            "darkseagreen" => Some(Self::DARKSEAGREEN),
+            "darkslateblue" => Some(Self::DARKSLATEBLUE),
+            "darkslategray" => Some(Self::DARKSLATEGRAY),
+            "darkslategrey" => Some(Self::DARKSLATEGREY),
+            "darkturquoise" => Some(Self::DARKTURQUOISE),
+            "darkviolet" => Some(Self::DARKVIOLET),
+            "deeppink" => Some(Self::DEEPPINK),
+            "deepskyblue" => Some(Self::DEEPSKYBLUE),
+            "dimgray" => Some(Self::DIMGRAY),
+            "dimgrey" => Some(Self::DIMGREY),
+            "dodgerblue" => Some(Self::DODGERBLUE),
+            "firebrick" => Some(Self::FIREBRICK),
+            "floralwhite" => Some(Self::FLORALWHITE),
+            "forestgreen" => Some(Self::FORESTGREEN),
+            "fuchsia" => Some(Self::FUCHSIA),
+            "gainsboro" => Some(Self::GAINSBORO),
+            "ghostwhite" => Some(Self::GHOSTWHITE),
+            "gold" => Some(Self::GOLD),
+            "goldenrod" => Some(Self::GOLDENROD),
+            "gray" => Some(Self::GRAY),
+            "grey" => Some(Self::GREY),
+            "green" => Some(Self::GREEN),
+            "greenyellow" => Some(Self::GREENYELLOW),
+            "honeydew" => Some(Self::HONEYDEW),
+            "hotpink" => Some(Self::HOTPINK),
+            "indianred" => Some(Self::INDIANRED),
+            "indigo" => Some(Self::INDIGO),
+            "ivory" => Some(Self::IVORY),
+            "khaki" => Some(Self::KHAKI),
+            "lavender" => Some(Self::LAVENDER),
+            "lavenderblush" => Some(Self::LAVENDERBLUSH),
+            "lawngreen" => Some(Self::LAWNGREEN),
+            "lemonchiffon" => Some(Self::LEMONCHIFFON),
+            "lightblue" => Some(Self::LIGHTBLUE),
+            "lightcoral" => Some(Self::LIGHTCORAL),
+            "lightcyan" => Some(Self::LIGHTCYAN),
+            "lightgoldenrodyellow" => Some(Self::LIGHTGOLDENRODYELLOW),
+            "lightgray" => Some(Self::LIGHTGRAY),
+            "lightgreen" => Some(Self::LIGHTGREEN),
+            "lightgrey" => Some(Self::LIGHTGREY),
+            "lightpink" => Some(Self::LIGHTPINK),
+            "lightsalmon" => Some(Self::LIGHTSALMON),
+            "lightseagreen" => Some(Self::LIGHTSEAGREEN),
+            "lightskyblue" => Some(Self::LIGHTSKYBLUE),
+            "lightslategray" => Some(Self::LIGHTSLATEGRAY),
+            "lightslategrey" => Some(Self::LIGHTSLATEGREY),
+            "lightsteelblue" => Some(Self::LIGHTSTEELBLUE),
+            "lightyellow" => Some(Self::LIGHTYELLOW),
+            "lime" => Some(Self::LIME),
+            "limegreen" => Some(Self::LIMEGREEN),
+            "linen" => Some(Self::LINEN),
+            "magenta" => Some(Self::MAGENTA),
+            "maroon" => Some(Self::MAROON),
+            "mediumaquamarine" => Some(Self::MEDIUMAQUAMARINE),
+            "mediumblue" => Some(Self::MEDIUMBLUE),
+            "mediumorchid" => Some(Self::MEDIUMORCHID),
+            "mediumpurple" => Some(Self::MEDIUMPURPLE),
+            "mediumseagreen" => Some(Self::MEDIUMSEAGREEN),
+            "mediumslateblue" => Some(Self::MEDIUMSLATEBLUE),
+            "mediumspringgreen" => Some(Self::MEDIUMSPRINGGREEN),
+            "mediumturquoise" => Some(Self::MEDIUMTURQUOISE),
+            "mediumvioletred" => Some(Self::MEDIUMVIOLETRED),
+            "midnightblue" => Some(Self::MIDNIGHTBLUE),
+            "mintcream" => Some(Self::MINTCREAM),
+            "mistyrose" => Some(Self::MISTYROSE),
+            "moccasin" => Some(Self::MOCCASIN),
+            "navajowhite" => Some(Self::NAVAJOWHITE),
+            "navy" => Some(Self::NAVY),
+            "oldlace" => Some(Self::OLDLACE),
+            "olive" => Some(Self::OLIVE),
+            "olivedrab" => Some(Self::OLIVEDRAB),
+            "orange" => Some(Self::ORANGE),
+            "orangered" => Some(Self::ORANGERED),
+            "orchid" => Some(Self::ORCHID),
+            "palegoldenrod" => Some(Self::PALEGOLDENROD),
+            "palegreen" => Some(Self::PALEGREEN),
+            "paleturquoise" => Some(Self::PALETURQUOISE),
+            "palevioletred" => Some(Self::PALEVIOLETRED),
+            "papayawhip" => Some(Self::PAPAYAWHIP),
+            "peachpuff" => Some(Self::PEACHPUFF),
+            "peru" => Some(Self::PERU),
+            "pink" => Some(Self::PINK),
+            "plum" => Some(Self::PLUM),
+            "powderblue" => Some(Self::POWDERBLUE),
+            "purple" => Some(Self::PURPLE),
+            "rebeccapurple" => Some(Self::REBECCAPURPLE),
+            "red" => Some(Self::RED),
+            "rosybrown" => Some(Self::ROSYBROWN),
+            "royalblue" => Some(Self::ROYALBLUE),
+            "saddlebrown" => Some(Self::SADDLEBROWN),
+            "salmon" => Some(Self::SALMON),
+            "sandybrown" => Some(Self::SANDYBROWN),
+            "seagreen" => Some(Self::SEAGREEN),
+            "seashell" => Some(Self::SEASHELL),
+            "sienna" => Some(Self::SIENNA),
+            "silver" => Some(Self::SILVER),
+            "skyblue" => Some(Self::SKYBLUE),
+            "slateblue" => Some(Self::SLATEBLUE),
+            "slategray" => Some(Self::SLATEGRAY),
+            "slategrey" => Some(Self::SLATEGREY),
+            "snow" => Some(Self::SNOW),
+            "springgreen" => Some(Self::SPRINGGREEN),
+            "steelblue" => Some(Self::STEELBLUE),
+            "tan" => Some(Self::TAN),
+            "teal" => Some(Self::TEAL),
+            "thistle" => Some(Self::THISTLE),
+            "tomato" => Some(Self::TOMATO),
+            "turquoise" => Some(Self::TURQUOISE),
+            "violet" => Some(Self::VIOLET),
+            "wheat" => Some(Self::WHEAT),
+            "white" => Some(Self::WHITE),
+            "whitesmoke" => Some(Self::WHITESMOKE),
+            "yellow" => Some(Self::YELLOW),
+            "yellowgreen" => Some(Self::YELLOWGREEN),
+            _ => None,
+        }
+    }
 }
 
 impl Color {
@@ -35,18 +35,34 @@ pub enum KludgineError {
     TessellationError(lyon_tessellation::TessellationError),
     #[error("AtlasSpriteId belongs to an Atlas not registered in this collection")]
     InvalidAtlasSpriteId,
+    #[error("image is too large to fit in a texture atlas page")]
+    ImageTooLargeForAtlas,
     #[error("An index provided was not found")]
     InvalidIndex,
     #[error("error parsing sprite data: {0}")]
     SpriteParseError(String),
     #[error("no frames could be found for the current tag")]
     InvalidSpriteTag,
+    #[error("animation state not found: {0}")]
+    InvalidAnimationState(String),
     #[error("font family not found: {0}")]
     FontFamilyNotFound(String),
     #[error("argument is out of bounds")]
     OutOfBounds,
     #[error("clipboard error: {0}")]
     Clipboard(String),
+    #[error("error capturing frame: {0}")]
+    FrameCaptureError(String),
+    #[error("snapshot mismatch: {0}")]
+    SnapshotMismatch(String),
+    #[error("components were still processing messages after {0:?}")]
+    SettleTimeout(std::time::Duration),
+    #[error("error parsing tiled map: {0}")]
+    TiledParseError(String),
+    #[error("error parsing svg: {0}")]
+    SvgParseError(String),
+    #[error("shapes painted with patterns can't be prepared")]
+    PatternInPreparedShape,
 
     #[error("specify at most 2 of the dimensions top, bottom, and height. (e.g., top and bottom, but not height")]
     AbsoluteBoundsInvalidVertical,
@@ -115,6 +131,7 @@ pub mod scene;
 pub mod shape;
 pub mod sprite;
 pub mod style;
+pub mod testing;
 pub mod text;
 pub mod texture;
 pub mod tilemap;
@@ -132,12 +149,15 @@ pub mod prelude {
             ScreenScale, Size, SizeExt, Surround, Unknown, Vector,
         },
         runtime::Runtime,
-        scene::{Scene, Target},
+        scene::{OffscreenTarget, Scene, Target},
         shape::*,
         sprite::{
-            AnimationMode, Sprite, SpriteAnimation, SpriteAnimations, SpriteCollection,
-            SpriteFrame, SpriteMap, SpriteRotation, SpriteSheet, SpriteSource,
-            SpriteSourceSublocation,
+            AnimationMode, AnimationRepeat, AnimationState, AnimationStateMachine,
+            AnimationTransition, NineSlice, NineSliceFill, Sprite, SpriteAnimation,
+            SpriteAnimations, SpriteCollection, SpriteEvent, SpriteFrame, SpriteFrameBuilder,
+            SpriteLayer, SpriteMap, SpriteRotation, SpriteSheet, SpriteSlice, SpriteSource,
+            SpriteSourceSublocation, SpriteTint, SpriteTransform, SpriteTrim, SpriteUpdate,
+            TextureAtlas, TransitionCondition, TransitionTiming,
         },
         style::{
             theme::{
@@ -156,14 +176,17 @@ pub mod prelude {
         },
         texture::Texture,
         tilemap::{
-            PersistentMap, PersistentTileMap, PersistentTileProvider, Tile, TileMap, TileProvider,
+            AutotileMode, AutotileRules, Autotiler, ChunkLoader, ChunkedTileProvider,
+            LayeredTileMap, PersistentMap, PersistentTileMap, PersistentTileProvider, StaggerAxis,
+            StaggerIndex, TerrainMap, Tile, TileLayer, TileMap, TileMapData, TileNeighbors,
+            TilePalette, TileProjection, TileProvider, TiledMap,
         },
         ui::{
-            AbsoluteBounds, AbsoluteLayout, AnimatableComponent, AnimationManager, Border, Button,
-            Callback, ChainElementDynamicContents, ColumnLayout, Component, ComponentBorder,
-            ComponentOverflow, ContentOffset, Context, ControlEvent, Dialog, DialogButton,
-            DialogButtonSpacing, DialogButtons, Entity, EntityBuilder, Grid, GridCommand,
-            GridEvent, HierarchicalArena, Image, ImageAlphaAnimation, ImageCommand,
+            AbsoluteBounds, AbsoluteLayout, AnimatableComponent, AnimationManager, BackgroundImage,
+            Border, BorderImage, Button, Callback, ChainElementDynamicContents, ColumnLayout,
+            Component, ComponentBorder, ComponentOverflow, ContentOffset, Context, ControlEvent,
+            Dialog, DialogButton, DialogButtonSpacing, DialogButtons, Entity, EntityBuilder, Grid,
+            GridCommand, GridEvent, HierarchicalArena, Image, ImageAlphaAnimation, ImageCommand,
             ImageFrameAnimation, ImageOptions, ImageScaling, Index, Indexable,
             InteractiveComponent, InteractiveComponentExt, Label, LabelCommand, Layout,
             LayoutConstraints, LayoutContext, LayoutSolver, LayoutSolverExt, LinearTransition,
@@ -177,7 +200,8 @@ pub mod prelude {
                 DeviceId, ElementState, Event, EventStatus, InputEvent, MouseButton,
                 MouseScrollDelta, ScanCode, TouchPhase, VirtualKeyCode,
             },
-            OpenableWindow, Window, WindowBuilder, WindowCreator,
+            FrameCapture, FrameRecording, OpenableWindow, SoftwareRenderer, Window, WindowBuilder,
+            WindowCreator,
         },
         Handle, KludgineError, KludgineResult, RequiresInitialization,
     };
@@ -39,7 +39,9 @@ pub(crate) enum RuntimeEvent {
 }
 
 use lazy_static::lazy_static;
-use std::sync::Mutex;
+use std::sync::{Mutex, Once};
+
+static THREAD_POOL_LAUNCHED: Once = Once::new();
 
 pub trait EventProcessor: Send + Sync {
     fn process_event(
@@ -177,12 +179,27 @@ impl Runtime {
     where
         App: Application + 'static,
     {
-        {
+        // A headless interface may have already started the thread pool
+        Self::launch_thread_pool();
+        let app_runtime = ApplicationRuntime { app };
+        let (request_receiver, event_sender) = app_runtime.launch();
+
+        Self {
+            request_receiver,
+            event_sender,
+        }
+    }
+
+    /// Launches the thread pool used by [`Runtime::spawn`] if it isn't
+    /// already running.
+    fn launch_thread_pool() {
+        // The executor threads hold read locks on the pool for as long as they
+        // run, so the pool can't be checked for by acquiring a write lock.
+        THREAD_POOL_LAUNCHED.call_once(|| {
             {
                 let mut pool_guard = GLOBAL_THREAD_POOL
                     .write()
                     .expect("Error locking global thread pool");
-                assert!(pool_guard.is_none());
                 let executor = smol::Executor::new();
                 *pool_guard = Some(executor);
             }
@@ -205,14 +222,13 @@ impl Runtime {
 
                 signal.close();
             });
-        }
-        let app_runtime = ApplicationRuntime { app };
-        let (request_receiver, event_sender) = app_runtime.launch();
+        });
+    }
 
-        Self {
-            request_receiver,
-            event_sender,
-        }
+    /// Ensures [`Runtime::spawn`] can be used without a running application,
+    /// such as when rendering a headless user interface.
+    pub(crate) fn initialize_headless() {
+        Self::launch_thread_pool();
     }
 
     fn internal_open_window(
@@ -1,22 +1,28 @@
 use crate::{
     math::{Point, Raw, Scale, Scaled, ScreenScale, Size, Vector},
-    shape::Shape,
+    shape::{PreparedShape, Shape},
     sprite::RenderedSprite,
     style::{
         theme::{SystemTheme, Theme},
         FontStyle, Weight,
     },
     text::{font::Font, prepared::PreparedSpan},
+    window::frame::OffscreenFrame,
     Handle, KludgineError, KludgineResult,
 };
-use euclid::Rect;
+use crossbeam::queue::SegQueue;
+use euclid::{Rect, Transform2D};
 use platforms::target::{OS, TARGET_OS};
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
     time::{Duration, Instant},
 };
-use winit::event::VirtualKeyCode;
+use winit::event::{ElementState, VirtualKeyCode};
+
+mod offscreen;
+
+pub use offscreen::OffscreenTarget;
 
 #[derive(Debug)]
 pub(crate) enum Element {
@@ -29,6 +35,10 @@ pub(crate) enum Element {
         clip: Option<Rect<u32, Raw>>,
     },
     Shape(Shape<Raw>),
+    PreparedShape {
+        shape: PreparedShape,
+        transform: Transform2D<f32, Raw, Raw>,
+    },
 }
 
 #[derive(Clone, Debug)]
@@ -116,6 +126,10 @@ pub(crate) struct SceneData {
     elapsed: Option<Duration>,
     fonts: HashMap<String, Vec<Font>>,
     system_theme: SystemTheme,
+    /// Offscreen targets realized since the last frame was rendered
+    offscreen_frames: Vec<OffscreenFrame>,
+    /// The texture ids of offscreen targets that have been dropped
+    released_offscreen_textures: Arc<SegQueue<u64>>,
     #[derivative(Debug = "ignore")]
     theme: Arc<Theme>,
 }
@@ -156,10 +170,57 @@ impl Scene {
                 elements: Vec::new(),
                 fonts: HashMap::new(),
                 system_theme: SystemTheme::Light,
+                offscreen_frames: Vec::new(),
+                released_offscreen_textures: Default::default(),
             }),
         }
     }
 
+    /// Creates a scene that isn't attached to a window, for use with a
+    /// [`SoftwareRenderer`](crate::window::SoftwareRenderer).
+    pub async fn headless(theme: Theme, size: Size<f32, Raw>, scale_factor: ScreenScale) -> Self {
+        let mut scene = Self::new(theme);
+        scene.set_internal_size(size).await;
+        scene.set_scale_factor(scale_factor).await;
+        #[cfg(feature = "bundled-fonts-enabled")]
+        scene.register_bundled_fonts().await;
+        scene
+    }
+
+    /// Creates an offscreen target of `size` that shares this scene's theme,
+    /// fonts and scale factor.
+    pub async fn offscreen(&self, size: Size<f32, Scaled>) -> OffscreenTarget {
+        let scale_factor = self.scale_factor().await;
+        self.offscreen_with_scale_factor(size, scale_factor).await
+    }
+
+    /// Creates an offscreen target of `size` that shares this scene's theme
+    /// and fonts, and renders at `scale_factor` regardless of this scene's
+    /// scale factor.
+    pub async fn offscreen_with_scale_factor(
+        &self,
+        size: Size<f32, Scaled>,
+        scale_factor: ScreenScale,
+    ) -> OffscreenTarget {
+        let scene = self.data.read().await;
+        let offscreen = Scene {
+            data: Handle::new(SceneData {
+                theme: scene.theme.clone(),
+                scale_factor,
+                size: size * scale_factor,
+                pressed_keys: HashSet::new(),
+                now: None,
+                elapsed: None,
+                elements: Vec::new(),
+                fonts: scene.fonts.clone(),
+                system_theme: scene.system_theme,
+                offscreen_frames: Vec::new(),
+                released_offscreen_textures: Default::default(),
+            }),
+        };
+        OffscreenTarget::new(offscreen, self.clone()).await
+    }
+
     pub async fn system_theme(&self) -> SystemTheme {
         let scene = self.data.read().await;
         scene.system_theme
@@ -170,6 +231,33 @@ impl Scene {
         scene.system_theme = system_theme;
     }
 
+    /// Queues `frame` to be rendered before the next frame of this scene,
+    /// replacing an earlier realization of the same target.
+    pub(crate) async fn push_offscreen_frame(&self, frame: OffscreenFrame) {
+        let mut scene = self.data.write().await;
+        scene
+            .offscreen_frames
+            .retain(|queued| queued.texture_id != frame.texture_id);
+        scene.offscreen_frames.push(frame);
+    }
+
+    pub(crate) async fn take_offscreen_frames(&self) -> Vec<OffscreenFrame> {
+        let mut scene = self.data.write().await;
+        std::mem::take(&mut scene.offscreen_frames)
+    }
+
+    /// The queue offscreen targets created from this scene push their texture
+    /// ids onto when they're dropped.
+    pub(crate) async fn released_offscreen_textures(&self) -> Arc<SegQueue<u64>> {
+        let scene = self.data.read().await;
+        scene.released_offscreen_textures.clone()
+    }
+
+    pub(crate) async fn take_released_offscreen_textures(&self) -> Vec<u64> {
+        let scene = self.data.read().await;
+        std::iter::from_fn(|| scene.released_offscreen_textures.pop()).collect()
+    }
+
     pub(crate) async fn push_element(&self, element: Element) {
         let mut scene = self.data.write().await;
         scene.elements.push(element);
@@ -195,6 +283,18 @@ impl Scene {
         scene.scale_factor
     }
 
+    pub(crate) async fn set_key_state(&self, key: VirtualKeyCode, state: ElementState) {
+        let mut scene = self.data.write().await;
+        match state {
+            ElementState::Pressed => {
+                scene.pressed_keys.insert(key);
+            }
+            ElementState::Released => {
+                scene.pressed_keys.remove(&key);
+            }
+        }
+    }
+
     pub async fn keys_pressed(&self) -> HashSet<VirtualKeyCode> {
         let scene = self.data.read().await;
         scene.pressed_keys.clone()
@@ -230,10 +330,17 @@ impl Scene {
         }
     }
 
-    pub(crate) async fn start_frame(&mut self) {
+    /// Clears the scene's elements and advances its clock. Windows call this
+    /// automatically before each frame is rendered.
+    pub async fn start_frame(&mut self) {
+        self.start_frame_at(Instant::now()).await
+    }
+
+    /// Starts a frame using `now` as the current time instead of the system clock.
+    pub async fn start_frame_at(&mut self, now: Instant) {
         let mut scene = self.data.write().await;
         let last_start = scene.now;
-        scene.now = Some(Instant::now());
+        scene.now = Some(now);
         scene.elapsed = match last_start {
             Some(last_start) => scene.now.unwrap().checked_duration_since(last_start),
             None => None,
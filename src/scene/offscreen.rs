@@ -0,0 +1,175 @@
+use crate::{
+    math::{Scaled, ScreenScale, Size},
+    scene::{Scene, Target},
+    texture::Texture,
+    window::frame::{Frame, OffscreenFrame},
+};
+use async_lock::Mutex;
+use crossbeam::queue::SegQueue;
+use image::{DynamicImage, RgbaImage};
+use std::sync::Arc;
+
+/// A [`Target`] that isn't displayed in a window. Anything drawn into it can be
+/// realized into a [`Texture`], allowing an expensive set of elements to be
+/// drawn once and then rendered as a sprite.
+///
+/// Realizing queues the drawn elements to be rendered into the texture by the
+/// scene's renderer before its next frame. The texture keeps the same id each
+/// time the target is realized, so sprites made from it show the most recently
+/// realized contents. Its `image` only has the target's dimensions; the
+/// rendered pixels never leave the renderer.
+///
+/// Dropping the target releases the rendered texture and everything loaded to
+/// draw it. Sprites still drawing the texture afterwards draw nothing.
+#[derive(Debug)]
+pub struct OffscreenTarget {
+    target: Target,
+    parent: Scene,
+    texture: Texture,
+    /// Tracks what the renderer has loaded for this target across
+    /// realizations
+    frame: Arc<Mutex<Frame>>,
+    released: Arc<SegQueue<u64>>,
+}
+
+impl OffscreenTarget {
+    pub(crate) async fn new(mut scene: Scene, parent: Scene) -> Self {
+        scene.start_frame().await;
+        let size = scene.internal_size().await.round().cast::<u32>();
+        Self {
+            target: Target::from(scene),
+            released: parent.released_offscreen_textures().await,
+            parent,
+            texture: Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(
+                size.width,
+                size.height,
+            ))),
+            frame: Default::default(),
+        }
+    }
+
+    /// The target to draw into.
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    pub async fn size(&self) -> Size<f32, Scaled> {
+        self.target.size().await
+    }
+
+    pub async fn scale_factor(&self) -> ScreenScale {
+        self.target.scale_factor().await
+    }
+
+    /// Removes everything previously drawn, returning the target to draw into.
+    pub async fn clear(&mut self) -> &Target {
+        self.target.scene.start_frame().await;
+        &self.target
+    }
+
+    /// Renders everything drawn since the last clear into this target's
+    /// texture.
+    pub async fn realize(&mut self) -> Texture {
+        self.frame.lock().await.update(&self.target).await;
+        self.parent
+            .push_offscreen_frame(OffscreenFrame {
+                texture_id: self.texture.id,
+                frame: self.frame.clone(),
+            })
+            .await;
+        self.texture.clone()
+    }
+}
+
+impl Drop for OffscreenTarget {
+    fn drop(&mut self) {
+        self.released.push(self.texture.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        math::{Point, Rect, ScreenScale},
+        shape::{Fill, Shape},
+        sprite::{SpriteRotation, SpriteSource},
+        style::theme::Minimal,
+        window::SoftwareRenderer,
+        KludgineResult,
+    };
+    use image::Rgba;
+
+    #[async_test]
+    async fn realize_test() -> KludgineResult<()> {
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(8., 8.),
+            ScreenScale::new(2.),
+        )
+        .await;
+        scene.start_frame().await;
+        let mut offscreen = scene.offscreen(Size::new(2., 2.)).await;
+        assert_eq!(offscreen.size().await, Size::new(2., 2.));
+
+        Shape::rect(Rect::new(Point::new(0., 0.), Size::new(1., 2.)))
+            .fill(Fill::new(Color::RED))
+            .render_at(Point::default(), offscreen.clear().await)
+            .await;
+        let texture = offscreen.realize().await;
+        // The offscreen target renders at the scene's scale factor.
+        assert_eq!(texture.size(), Size::new(4, 4));
+
+        let source = SpriteSource::entire_texture(texture.clone()).await;
+        let target = Target::from(scene.clone());
+        let mut renderer = SoftwareRenderer::default();
+        source
+            .render_within(
+                &target,
+                Rect::new(Point::new(2., 2.), Size::new(2., 2.)),
+                SpriteRotation::default(),
+            )
+            .await;
+        let image = renderer.render(&target).await?;
+        assert_eq!(image.get_pixel(5, 5), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(6, 5), &Rgba([0, 0, 0, 0]));
+
+        // Realizing again keeps the texture's id, so the existing sprite source
+        // draws the new contents.
+        Shape::rect(Rect::new(Point::new(0., 0.), Size::new(2., 2.)))
+            .fill(Fill::new(Color::BLUE))
+            .render_at(Point::default(), offscreen.clear().await)
+            .await;
+        assert_eq!(offscreen.realize().await.id, texture.id);
+
+        scene.start_frame().await;
+        source
+            .render_within(
+                &target,
+                Rect::new(Point::new(2., 2.), Size::new(2., 2.)),
+                SpriteRotation::default(),
+            )
+            .await;
+        let image = renderer.render(&target).await?;
+        assert_eq!(image.get_pixel(6, 5), &Rgba([0, 0, 255, 255]));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn scale_factor_test() -> KludgineResult<()> {
+        let scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(8., 8.),
+            ScreenScale::new(2.),
+        )
+        .await;
+        let offscreen = scene
+            .offscreen_with_scale_factor(Size::new(2., 2.), ScreenScale::new(1.))
+            .await;
+        assert_eq!(offscreen.scale_factor().await, ScreenScale::new(1.));
+        assert_eq!(offscreen.size().await, Size::new(2., 2.));
+        assert_eq!(offscreen.texture.size(), Size::new(2, 2));
+        Ok(())
+    }
+}
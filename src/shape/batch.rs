@@ -1,6 +1,9 @@
-use crate::{math::Raw, shape::Shape, KludgineResult};
+use crate::{
+    math::Raw,
+    shape::{Mesh, Shape},
+    KludgineResult,
+};
 use easygpu::prelude::*;
-use easygpu_lyon::ShapeBuilder;
 
 #[derive(Debug, Default, Clone)]
 pub struct Batch {
@@ -12,13 +15,17 @@ impl Batch {
         self.shapes.push(shape)
     }
 
-    pub(crate) fn finish(self, renderer: &Renderer) -> KludgineResult<easygpu_lyon::Shape> {
-        let mut builder = ShapeBuilder::default();
+    pub(crate) fn tessellate(self) -> KludgineResult<Mesh> {
+        let mut mesh = Mesh::default();
 
         for shape in self.shapes {
-            shape.build(&mut builder)?;
+            shape.build(&mut mesh)?;
         }
 
-        Ok(builder.prepare(renderer))
+        Ok(mesh)
+    }
+
+    pub(crate) fn finish(self, renderer: &Renderer) -> KludgineResult<easygpu_lyon::Shape> {
+        Ok(self.tessellate()?.prepare(renderer))
     }
 }
@@ -1,15 +1,43 @@
 use crate::{
-    math::{Length, Point, Raw, Scale, Scaled},
+    math::{Angle, Length, Point, Raw, Scale, Scaled, Vector},
     scene::Target,
-    shape::{Fill, Stroke},
+    shape::{geometry::ShapeGeometry, Fill, Mesh, Path, PathBuilder, Stroke},
     KludgineError, KludgineResult,
 };
+use approx::relative_eq;
+use euclid::Transform2D;
 #[derive(Clone, Debug)]
 pub(crate) struct Circle<S> {
     pub center: Point<f32, S>,
     pub radius: Length<f32, S>,
 }
 
+impl<S> Circle<S>
+where
+    S: Copy,
+{
+    /// Circles remain circles when rotated, translated, reflected or scaled
+    /// uniformly. Other transforms turn circles into ellipses, which are
+    /// drawn as paths.
+    pub(crate) fn transformed(&self, transform: &Transform2D<f32, S, S>) -> ShapeGeometry<S> {
+        let preserves_circles = (relative_eq!(transform.m11, transform.m22)
+            && relative_eq!(transform.m12, -transform.m21))
+            || (relative_eq!(transform.m11, -transform.m22)
+                && relative_eq!(transform.m12, transform.m21));
+        if preserves_circles {
+            ShapeGeometry::Circle(Circle {
+                center: transform.transform_point(self.center),
+                radius: self.radius * transform.determinant().abs().sqrt(),
+            })
+        } else {
+            let radius = self.radius.get();
+            ShapeGeometry::Path(
+                Path::ellipse(self.center, Vector::new(radius, radius)).transformed(transform),
+            )
+        }
+    }
+}
+
 impl Circle<Scaled> {
     pub(crate) async fn translate_and_convert_to_device(
         &self,
@@ -24,14 +52,14 @@ impl Circle<Scaled> {
 }
 
 impl Circle<Raw> {
-    pub fn build(
+    pub(crate) fn build(
         &self,
-        builder: &mut easygpu_lyon::ShapeBuilder,
+        builder: &mut Mesh,
         stroke: &Option<Stroke>,
         fill: &Option<Fill>,
     ) -> KludgineResult<()> {
         if let Some(fill) = fill {
-            builder.default_color = fill.color.rgba();
+            builder.set_paint(&fill.paint);
             lyon_tessellation::basic_shapes::fill_circle(
                 self.center.cast_unit(),
                 self.radius.get(),
@@ -42,7 +70,18 @@ impl Circle<Raw> {
         }
 
         if let Some(stroke) = stroke {
-            builder.default_color = stroke.color.rgba();
+            builder.set_paint(&stroke.paint);
+            if stroke.dashes.is_some() {
+                let radii = Vector::new(self.radius.get(), self.radius.get());
+                let path = PathBuilder::new(self.center + Vector::new(self.radius.get(), 0.))
+                    .elliptical_arc(self.center, radii, Angle::zero(), Angle::degrees(360.))
+                    .close()
+                    .build();
+                return builder
+                    .stroke(&stroke.outline(path.as_lyon()), &stroke.options)
+                    .map_err(KludgineError::TessellationError);
+            }
+
             lyon_tessellation::basic_shapes::stroke_circle(
                 self.center.cast_unit(),
                 self.radius.get(),
@@ -65,3 +104,30 @@ impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for Circle<Src> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Unknown;
+
+    #[test]
+    fn transformed_circle_test() {
+        let circle = Circle::<Unknown> {
+            center: Point::new(1., 0.),
+            radius: Length::new(2.),
+        };
+        let rotated_and_scaled = Transform2D::rotation(Angle::degrees(90.)).then_scale(3., 3.);
+        match circle.transformed(&rotated_and_scaled) {
+            ShapeGeometry::Circle(circle) => {
+                assert!((circle.center - Point::new(0., 3.)).length() < 0.001);
+                assert!((circle.radius.get() - 6.).abs() < 0.001);
+            }
+            other => panic!("expected a circle, got {:?}", other),
+        }
+
+        assert!(matches!(
+            circle.transformed(&Transform2D::scale(1., 2.)),
+            ShapeGeometry::Path(_)
+        ));
+    }
+}
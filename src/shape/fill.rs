@@ -1,16 +1,28 @@
-use crate::color::Color;
+use crate::shape::Paint;
 
 #[derive(Default, Clone, Debug)]
 pub struct Fill {
-    pub color: Color,
+    pub paint: Paint,
     pub options: lyon_tessellation::FillOptions,
 }
 
 impl Fill {
-    pub fn new(color: Color) -> Self {
+    pub fn new(paint: impl Into<Paint>) -> Self {
         Self {
-            color,
+            paint: paint.into(),
             options: Default::default(),
         }
     }
+
+    pub fn with_options(mut self, options: lyon_tessellation::FillOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub(crate) fn scaled(&self, scale: f32) -> Self {
+        Self {
+            paint: self.paint.scaled(scale),
+            options: self.options,
+        }
+    }
 }
@@ -1,9 +1,10 @@
 use crate::{
     math::{Point, Raw, Scale, Scaled},
     scene::Target,
-    shape::{circle::Circle, Fill, Path, Stroke},
+    shape::{circle::Circle, Fill, Mesh, Path, Stroke},
     KludgineResult,
 };
+use euclid::Transform2D;
 
 #[derive(Clone, Debug)]
 pub(crate) enum ShapeGeometry<S> {
@@ -15,7 +16,7 @@ pub(crate) enum ShapeGeometry<S> {
 impl ShapeGeometry<Raw> {
     pub fn build(
         &self,
-        builder: &mut easygpu_lyon::ShapeBuilder,
+        builder: &mut Mesh,
         stroke: &Option<Stroke>,
         fill: &Option<Fill>,
     ) -> KludgineResult<()> {
@@ -27,6 +28,19 @@ impl ShapeGeometry<Raw> {
     }
 }
 
+impl<S> ShapeGeometry<S>
+where
+    S: Copy,
+{
+    pub(crate) fn transformed(&self, transform: &Transform2D<f32, S, S>) -> Self {
+        match self {
+            Self::Empty => Self::Empty,
+            Self::Path(path) => Self::Path(path.transformed(transform)),
+            Self::Circle(circle) => circle.transformed(transform),
+        }
+    }
+}
+
 impl ShapeGeometry<Scaled> {
     pub(crate) async fn translate_and_convert_to_device(
         &self,
@@ -0,0 +1,255 @@
+use crate::{
+    color::Color,
+    math::{Point, Raw, Rect},
+    shape::{
+        slice::{self, Polygon},
+        Gradient, Paint,
+    },
+};
+use easygpu::prelude::*;
+use euclid::Vector3D;
+use lyon_tessellation::{
+    math::Point as LyonPoint, path::Path as LyonPath, BasicGeometryBuilder, Count, FillAttributes,
+    FillGeometryBuilder, FillOptions, FillTessellator, GeometryBuilder, GeometryBuilderError,
+    StrokeAttributes, StrokeGeometryBuilder, StrokeOptions, StrokeTessellator, TessellationError,
+    VertexId,
+};
+use std::sync::Arc;
+
+/// The largest size of the cells that radial and conic gradients are divided
+/// into. Colors are only calculated at the corners of each cell and
+/// interpolated between them.
+const GRADIENT_CELL_SIZE: f32 = 4.;
+/// The number of cells small geometry is divided into along each axis
+const GRADIENT_MINIMUM_CELLS: f32 = 16.;
+/// The most cells a single piece of geometry is divided into
+const GRADIENT_MAXIMUM_CELLS: f32 = 4096.;
+
+/// A vertex laid out to match the vertex format of `easygpu_lyon::LyonPipeline`
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Vertex {
+    pub position: Vector3D<f32, ScreenSpace>,
+    pub color: Rgba8,
+}
+
+impl Vertex {
+    pub fn location(&self) -> Point<f32, Raw> {
+        Point::new(self.position.x, self.position.y)
+    }
+}
+
+/// Tessellated shape geometry. The vertices and indices can either be uploaded
+/// to the GPU or rasterized directly.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+    paint: Paint,
+    geometry_start: (usize, usize),
+}
+
+impl Mesh {
+    pub fn set_paint(&mut self, paint: &Paint) {
+        self.paint = paint.clone();
+    }
+
+    pub fn fill(
+        &mut self,
+        path: &LyonPath,
+        options: &FillOptions,
+    ) -> Result<(), TessellationError> {
+        let mut tessellator = FillTessellator::new();
+        tessellator.tessellate_path(path, options, self)?;
+        Ok(())
+    }
+
+    pub fn stroke(
+        &mut self,
+        path: &LyonPath,
+        options: &StrokeOptions,
+    ) -> Result<(), TessellationError> {
+        let mut tessellator = StrokeTessellator::new();
+        tessellator.tessellate_path(path, options, self)?;
+        Ok(())
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [Vertex; 3]> + '_ {
+        self.indices.chunks_exact(3).map(move |triangle| {
+            [
+                self.vertices[triangle[0] as usize],
+                self.vertices[triangle[1] as usize],
+                self.vertices[triangle[2] as usize],
+            ]
+        })
+    }
+
+    /// Returns each triangle added since `first_index` as a polygon
+    pub fn polygons(&self, first_index: usize) -> Vec<Polygon> {
+        self.indices[first_index..]
+            .chunks_exact(3)
+            .map(|triangle| {
+                triangle
+                    .iter()
+                    .map(|&index| self.vertices[index as usize].location())
+                    .collect()
+            })
+            .collect()
+    }
+
+    pub fn prepare(&self, renderer: &Renderer) -> easygpu_lyon::Shape {
+        let vertices = renderer.device.create_buffer(&self.vertices);
+        let indices = renderer.device.create_index(&self.indices);
+
+        easygpu_lyon::Shape {
+            index_count: self.indices.len() as u32,
+            vertices: Arc::new(vertices),
+            indices: Arc::new(indices),
+        }
+    }
+
+    fn push_vertex(&mut self, position: LyonPoint) -> Result<VertexId, GeometryBuilderError> {
+        let id = VertexId(self.vertices.len() as u32);
+        if self.vertices.len() >= u16::MAX as usize {
+            return Err(GeometryBuilderError::TooManyVertices);
+        }
+
+        let color = match &self.paint {
+            Paint::Color(color) => *color,
+            // Gradients are colored once the geometry is complete, and patterns
+            // are drawn using the pattern's texture.
+            _ => Color::WHITE,
+        };
+        self.vertices.push(Vertex {
+            position: Vector3D::new(position.x, position.y, 0.),
+            color: color.into(),
+        });
+
+        Ok(id)
+    }
+
+    /// Splits the geometry added since `begin_geometry` so that the gradient's
+    /// colors can be interpolated between vertices, then colors each vertex
+    fn apply_gradient(&mut self, gradient: &Gradient) {
+        let (first_vertex, first_index) = self.geometry_start;
+        if self.vertices.len() == first_vertex {
+            return;
+        }
+
+        let bounds = Rect::from_points(self.vertices[first_vertex..].iter().map(Vertex::location));
+        let polygons = self.polygons(first_index);
+        let polygons = if gradient.is_linear() {
+            // Colors change linearly between the stops of a linear gradient, so
+            // splitting at each stop is exact.
+            let stops = gradient
+                .stops
+                .iter()
+                .map(|stop| stop.offset)
+                .collect::<Vec<_>>();
+            slice::slice(polygons, |point| gradient.offset_at(point, &bounds), &stops)
+        } else {
+            let mut cell_width = gradient_cell_size(bounds.size.width);
+            let mut cell_height = gradient_cell_size(bounds.size.height);
+            let cells = bounds.size.width / cell_width * bounds.size.height / cell_height;
+            if cells > GRADIENT_MAXIMUM_CELLS {
+                // Use larger cells rather than running out of vertices
+                let growth = (cells / GRADIENT_MAXIMUM_CELLS).sqrt();
+                cell_width *= growth;
+                cell_height *= growth;
+            }
+            slice::slice_grid(polygons, &bounds, bounds.origin, cell_width, cell_height)
+        };
+        let color_at = |point: Point<f32, Raw>| -> Rgba8 {
+            gradient.color_at(gradient.offset_at(point, &bounds)).into()
+        };
+
+        let vertex_count = polygons.iter().map(Vec::len).sum::<usize>();
+        if first_vertex + vertex_count >= u16::MAX as usize {
+            // Too large to split within this mesh, color the original vertices
+            for vertex in &mut self.vertices[first_vertex..] {
+                vertex.color = color_at(vertex.location());
+            }
+            return;
+        }
+
+        self.vertices.truncate(first_vertex);
+        self.indices.truncate(first_index);
+        for polygon in polygons {
+            let first_polygon_vertex = self.vertices.len() as u16;
+            for &point in &polygon {
+                self.vertices.push(Vertex {
+                    position: Vector3D::new(point.x, point.y, 0.),
+                    color: color_at(point),
+                });
+            }
+            for triangle in slice::triangulate(&polygon) {
+                self.indices.extend(
+                    triangle
+                        .iter()
+                        .map(|&index| first_polygon_vertex + index as u16),
+                );
+            }
+        }
+    }
+}
+
+fn gradient_cell_size(length: f32) -> f32 {
+    (length / GRADIENT_MINIMUM_CELLS).clamp(1., GRADIENT_CELL_SIZE)
+}
+
+impl GeometryBuilder for Mesh {
+    fn begin_geometry(&mut self) {
+        self.geometry_start = (self.vertices.len(), self.indices.len());
+    }
+
+    fn end_geometry(&mut self) -> Count {
+        if let Paint::Gradient(gradient) = &self.paint {
+            let gradient = gradient.clone();
+            self.apply_gradient(&gradient);
+        }
+
+        Count {
+            vertices: self.vertices.len() as u32,
+            indices: self.indices.len() as u32,
+        }
+    }
+
+    fn add_triangle(&mut self, a: VertexId, b: VertexId, c: VertexId) {
+        self.indices.push(a.0 as u16);
+        self.indices.push(b.0 as u16);
+        self.indices.push(c.0 as u16);
+    }
+
+    fn abort_geometry(&mut self) {
+        // Only discard the geometry from the shape that failed, not the entire batch
+        let (vertices, indices) = self.geometry_start;
+        self.vertices.truncate(vertices);
+        self.indices.truncate(indices);
+    }
+}
+
+impl FillGeometryBuilder for Mesh {
+    fn add_fill_vertex(
+        &mut self,
+        position: LyonPoint,
+        _attributes: FillAttributes,
+    ) -> Result<VertexId, GeometryBuilderError> {
+        self.push_vertex(position)
+    }
+}
+
+impl StrokeGeometryBuilder for Mesh {
+    fn add_stroke_vertex(
+        &mut self,
+        position: LyonPoint,
+        _attributes: StrokeAttributes,
+    ) -> Result<VertexId, GeometryBuilderError> {
+        self.push_vertex(position)
+    }
+}
+
+impl BasicGeometryBuilder for Mesh {
+    fn add_vertex(&mut self, position: LyonPoint) -> Result<VertexId, GeometryBuilderError> {
+        self.push_vertex(position)
+    }
+}
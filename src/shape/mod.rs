@@ -2,18 +2,27 @@ mod batch;
 mod circle;
 mod fill;
 mod geometry;
+mod mesh;
+mod paint;
 mod path;
+mod prepared;
+mod rounded_rect;
+mod slice;
 mod stroke;
+mod svg;
 
-pub use self::{batch::*, fill::*, path::*, stroke::*};
+pub use self::{
+    batch::*, fill::*, paint::*, path::*, prepared::*, rounded_rect::*, stroke::*, svg::*,
+};
 use crate::{
-    math::{Point, Raw, Rect, Scaled},
+    math::{Angle, Point, Raw, Rect, Scaled, Vector},
     scene::{Element, Target},
     KludgineResult,
 };
 use circle::Circle;
-use euclid::{Length, Scale};
+use euclid::{Length, Scale, Transform2D};
 use geometry::ShapeGeometry;
+pub(crate) use mesh::Mesh;
 
 #[derive(Default, Clone, Debug)]
 pub struct Shape<S> {
@@ -42,6 +51,10 @@ where
         }
     }
 
+    pub fn rounded_rect(rect: impl Into<Rect<f32, S>>, radii: CornerRadii<S>) -> Self {
+        Self::path(Path::rounded_rect(rect.into(), radii))
+    }
+
     pub fn circle(center: Point<f32, S>, radius: Length<f32, S>) -> Self {
         Self {
             geometry: ShapeGeometry::Circle(Circle { center, radius }),
@@ -50,6 +63,55 @@ where
         }
     }
 
+    pub fn ellipse(center: Point<f32, S>, radii: Vector<f32, S>) -> Self {
+        Self::path(Path::ellipse(center, radii))
+    }
+
+    /// An open arc of a circle, beginning at `start_angle` and sweeping
+    /// clockwise by `sweep`. Arcs are usually stroked rather than filled.
+    pub fn arc(
+        center: Point<f32, S>,
+        radius: Length<f32, S>,
+        start_angle: Angle,
+        sweep: Angle,
+    ) -> Self {
+        let radii = Vector::new(radius.get(), radius.get());
+        let start = center
+            + Vector::new(
+                radius.get() * start_angle.radians.cos(),
+                radius.get() * start_angle.radians.sin(),
+            );
+        Self::path(
+            PathBuilder::new(start)
+                .elliptical_arc(center, radii, start_angle, sweep)
+                .build(),
+        )
+    }
+
+    /// A slice of a circle, enclosed by an arc and two lines to the center
+    pub fn pie(
+        center: Point<f32, S>,
+        radius: Length<f32, S>,
+        start_angle: Angle,
+        sweep: Angle,
+    ) -> Self {
+        let radii = Vector::new(radius.get(), radius.get());
+        Self::path(
+            PathBuilder::new(center)
+                .elliptical_arc(center, radii, start_angle, sweep)
+                .close()
+                .build(),
+        )
+    }
+
+    pub fn path(path: Path<S>) -> Self {
+        Self {
+            geometry: ShapeGeometry::Path(path),
+            stroke: None,
+            fill: None,
+        }
+    }
+
     pub fn polygon(points: impl IntoIterator<Item = Point<f32, S>>) -> Self {
         let mut points = points.into_iter();
         if let Some(start) = points.next() {
@@ -77,6 +139,38 @@ where
         self.stroke = Some(stroke);
         self
     }
+
+    /// Applies `transform` to this shape's geometry. Stroke widths and
+    /// pattern tiles aren't affected by the transform.
+    pub fn transformed(mut self, transform: &Transform2D<f32, S, S>) -> Self {
+        self.geometry = self.geometry.transformed(transform);
+        self
+    }
+
+    /// Rotates this shape clockwise by `angle` around `pivot`
+    pub fn rotated_around(self, angle: Angle, pivot: Point<f32, S>) -> Self {
+        self.transformed(&around(pivot, Transform2D::rotation(angle)))
+    }
+
+    /// Scales this shape by `x` horizontally and `y` vertically, away from
+    /// `pivot`
+    pub fn scaled_around(self, x: f32, y: f32, pivot: Point<f32, S>) -> Self {
+        self.transformed(&around(pivot, Transform2D::scale(x, y)))
+    }
+
+    /// Skews this shape by `x` along the x axis and `y` along the y axis,
+    /// relative to `pivot`
+    pub fn skewed_around(self, x: Angle, y: Angle, pivot: Point<f32, S>) -> Self {
+        let skew = Transform2D::new(1., y.radians.tan(), x.radians.tan(), 1., 0., 0.);
+        self.transformed(&around(pivot, skew))
+    }
+}
+
+/// Returns `transform` applied relative to `pivot` instead of the origin
+fn around<S>(pivot: Point<f32, S>, transform: Transform2D<f32, S, S>) -> Transform2D<f32, S, S> {
+    Transform2D::translation(-pivot.x, -pivot.y)
+        .then(&transform)
+        .then_translate(pivot.to_vector())
 }
 
 impl Shape<Scaled> {
@@ -90,21 +184,69 @@ impl Shape<Scaled> {
         location: Point<f32, Scaled>,
         scene: &Target,
     ) -> Shape<Raw> {
+        let effective_scale = scene.scale_factor().await;
         Shape {
             geometry: self
                 .geometry
                 .translate_and_convert_to_device(location, scene)
                 .await,
-            fill: self.fill.clone(),
-            stroke: self.stroke.clone(),
+            fill: self
+                .fill
+                .as_ref()
+                .map(|fill| fill.scaled(effective_scale.get())),
+            stroke: self
+                .stroke
+                .as_ref()
+                .map(|stroke| stroke.scaled(effective_scale.get())),
         }
     }
 }
 
 impl Shape<Raw> {
-    pub(crate) fn build(&self, builder: &mut easygpu_lyon::ShapeBuilder) -> KludgineResult<()> {
+    pub(crate) fn build(&self, builder: &mut Mesh) -> KludgineResult<()> {
         self.geometry.build(builder, &self.stroke, &self.fill)
     }
+
+    /// Returns the pattern used to paint this shape, if any
+    pub(crate) fn pattern(&self) -> Option<&Pattern> {
+        self.fill
+            .as_ref()
+            .and_then(|fill| fill.paint.pattern())
+            .or_else(|| {
+                self.stroke
+                    .as_ref()
+                    .and_then(|stroke| stroke.paint.pattern())
+            })
+    }
+
+    /// Splits this shape into the layers that need to be drawn separately.
+    /// Patterns are drawn with their textures, so a shape whose fill or
+    /// stroke is a pattern is split into one shape for each.
+    pub(crate) fn paint_layers(&self) -> Vec<Shape<Raw>> {
+        if self.pattern().is_none() || self.fill.is_none() || self.stroke.is_none() {
+            return vec![self.clone()];
+        }
+
+        vec![
+            Shape {
+                geometry: self.geometry.clone(),
+                fill: self.fill.clone(),
+                stroke: None,
+            },
+            Shape {
+                geometry: self.geometry.clone(),
+                fill: None,
+                stroke: self.stroke.clone(),
+            },
+        ]
+    }
+
+    /// Tessellates this shape, returning each triangle as a polygon
+    pub(crate) fn polygons(&self) -> KludgineResult<Vec<slice::Polygon>> {
+        let mut mesh = Mesh::default();
+        self.build(&mut mesh)?;
+        Ok(mesh.polygons(0))
+    }
 }
 
 impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for Shape<Src> {
@@ -112,8 +254,8 @@ impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for Shape<Src> {
     fn mul(self, scale: Scale<f32, Src, Dst>) -> Self::Output {
         Self::Output {
             geometry: self.geometry * scale,
-            fill: self.fill,
-            stroke: self.stroke,
+            fill: self.fill.map(|fill| fill.scaled(scale.get())),
+            stroke: self.stroke.map(|stroke| stroke.scaled(scale.get())),
         }
     }
 }
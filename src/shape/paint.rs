@@ -0,0 +1,275 @@
+use crate::{
+    color::Color,
+    math::{Angle, Point, Raw, Rect, Size, Unknown, Vector},
+    shape::slice::{self, Polygon},
+    sprite::SpriteSource,
+};
+
+/// How a shape's fill or stroke is colored
+#[derive(Clone, Debug)]
+pub enum Paint {
+    Color(Color),
+    Gradient(Gradient),
+    Pattern(Pattern),
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Self::Color(Color::default())
+    }
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Self::Color(color)
+    }
+}
+
+impl From<Gradient> for Paint {
+    fn from(gradient: Gradient) -> Self {
+        Self::Gradient(gradient)
+    }
+}
+
+impl From<Pattern> for Paint {
+    fn from(pattern: Pattern) -> Self {
+        Self::Pattern(pattern)
+    }
+}
+
+impl Paint {
+    pub fn pattern(&self) -> Option<&Pattern> {
+        match self {
+            Self::Pattern(pattern) => Some(pattern),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn scaled(&self, scale: f32) -> Self {
+        match self {
+            Self::Pattern(pattern) => Self::Pattern(Pattern {
+                source: pattern.source.clone(),
+                scale: pattern.scale * scale,
+            }),
+            other => other.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientStop {
+    /// The position of the stop along the gradient, from 0 to 1
+    pub offset: f32,
+    pub color: Color,
+}
+
+/// The geometry of a [`Gradient`]. Points and lengths are relative to the
+/// bounding box of the geometry being painted, where (0, 0) is the top-left
+/// corner and (1, 1) is the bottom-right corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientKind {
+    Linear {
+        start: Point<f32>,
+        end: Point<f32>,
+    },
+    Radial {
+        center: Point<f32>,
+        radius: f32,
+    },
+    /// Sweeps clockwise around `center`, beginning at `start_angle`
+    Conic {
+        center: Point<f32>,
+        start_angle: Angle,
+    },
+}
+
+/// A color gradient made up of multiple color stops. Colors before the first
+/// stop and after the last stop use the color of the nearest stop.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<GradientStop>,
+}
+
+impl Gradient {
+    pub fn new(kind: GradientKind) -> Self {
+        Self {
+            kind,
+            stops: Vec::new(),
+        }
+    }
+
+    pub fn linear(start: Point<f32>, end: Point<f32>) -> Self {
+        Self::new(GradientKind::Linear { start, end })
+    }
+
+    pub fn radial(center: Point<f32>, radius: f32) -> Self {
+        Self::new(GradientKind::Radial { center, radius })
+    }
+
+    pub fn conic(center: Point<f32>, start_angle: Angle) -> Self {
+        Self::new(GradientKind::Conic {
+            center,
+            start_angle,
+        })
+    }
+
+    pub fn with_stop(mut self, offset: f32, color: Color) -> Self {
+        let index = self
+            .stops
+            .iter()
+            .position(|stop| stop.offset > offset)
+            .unwrap_or(self.stops.len());
+        self.stops.insert(index, GradientStop { offset, color });
+        self
+    }
+
+    /// Returns the interpolated color at `offset` along the gradient
+    pub fn color_at(&self, offset: f32) -> Color {
+        let after = match self.stops.iter().position(|stop| stop.offset > offset) {
+            Some(0) => return self.stops[0].color,
+            Some(index) => index,
+            None => return self.stops.last().map(|stop| stop.color).unwrap_or_default(),
+        };
+        let start = &self.stops[after - 1];
+        let end = &self.stops[after];
+        let amount = (offset - start.offset) / (end.offset - start.offset);
+        let start = start.color.rgba();
+        let end = end.color.rgba();
+        let channel = |index: usize| start[index] + (end[index] - start[index]) * amount;
+        Color::new(channel(0), channel(1), channel(2), channel(3))
+    }
+
+    /// Returns the offset along the gradient of `location`, a point on screen
+    /// within geometry whose bounding box is `bounds`
+    pub(crate) fn offset_at(&self, location: Point<f32, Raw>, bounds: &Rect<f32, Raw>) -> f32 {
+        let point = Point::new(
+            (location.x - bounds.origin.x) / bounds.size.width.max(f32::EPSILON),
+            (location.y - bounds.origin.y) / bounds.size.height.max(f32::EPSILON),
+        );
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let direction = end - start;
+                let length = direction.square_length();
+                if length > 0. {
+                    (point - start).dot(direction) / length
+                } else {
+                    0.
+                }
+            }
+            GradientKind::Radial { center, radius } => {
+                if radius > 0. {
+                    (point - center).length() / radius
+                } else {
+                    1.
+                }
+            }
+            GradientKind::Conic {
+                center,
+                start_angle,
+            } => {
+                let angle = (point - center).angle_from_x_axis() - start_angle;
+                angle.positive().radians / std::f32::consts::TAU
+            }
+        }
+    }
+
+    pub(crate) fn is_linear(&self) -> bool {
+        matches!(self.kind, GradientKind::Linear { .. })
+    }
+}
+
+/// A polygon whose points are paired with locations within a texture
+pub(crate) type TexturedPolygon = Vec<(Point<f32, Raw>, Point<f32, Unknown>)>;
+
+/// Fills geometry by repeating a sprite. Tiles are drawn at the sprite's
+/// natural size multiplied by `scale`, beginning at the top-left of the
+/// geometry's bounding box.
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub source: SpriteSource,
+    pub scale: f32,
+}
+
+impl Pattern {
+    pub fn new(source: SpriteSource) -> Self {
+        Self { source, scale: 1. }
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    pub(crate) fn tile_size(&self) -> Size<f32, Raw> {
+        let size = self.source.location.bounds().size.to_f32();
+        Size::new(size.width * self.scale, size.height * self.scale)
+    }
+
+    /// Splits `polygons` along the edges of each tile, returning each point
+    /// of each piece along with its location in the pattern's texture
+    pub(crate) fn tile(&self, polygons: Vec<Polygon>) -> Vec<TexturedPolygon> {
+        let tile_size = self.tile_size();
+        if tile_size.is_empty() {
+            return Vec::new();
+        }
+
+        let bounds = Rect::from_points(polygons.iter().flatten());
+        let source = self.source.location.bounds().to_f32();
+        slice::slice_grid(
+            polygons,
+            &bounds,
+            bounds.origin,
+            tile_size.width,
+            tile_size.height,
+        )
+        .into_iter()
+        .map(|polygon| {
+            let center = polygon
+                .iter()
+                .fold(Vector::zero(), |sum, point| sum + point.to_vector())
+                / polygon.len() as f32;
+            let tile_origin = bounds.origin
+                + Vector::new(
+                    ((center.x - bounds.origin.x) / tile_size.width).floor() * tile_size.width,
+                    ((center.y - bounds.origin.y) / tile_size.height).floor() * tile_size.height,
+                );
+            polygon
+                .into_iter()
+                .map(|point| {
+                    let offset = (point - tile_origin) / self.scale;
+                    let texture_location = Point::new(
+                        (source.origin.x + offset.x).clamp(source.min_x(), source.max_x()),
+                        (source.origin.y + offset.y).clamp(source.min_y(), source.max_y()),
+                    );
+                    (point, texture_location)
+                })
+                .collect()
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_color_test() {
+        let gradient = Gradient::linear(Point::new(0., 0.), Point::new(1., 0.))
+            .with_stop(1., Color::new(0., 0., 1., 1.))
+            .with_stop(0.25, Color::new(1., 0., 0., 1.));
+        assert_eq!(gradient.stops[0].offset, 0.25);
+
+        // Colors outside of the stops use the nearest stop
+        assert_eq!(gradient.color_at(0.), Color::new(1., 0., 0., 1.));
+        assert_eq!(gradient.color_at(2.), Color::new(0., 0., 1., 1.));
+        assert_eq!(gradient.color_at(0.625), Color::new(0.5, 0., 0.5, 1.));
+
+        let bounds = Rect::new(Point::new(10., 10.), Size::new(20., 10.));
+        assert_eq!(gradient.offset_at(Point::new(15., 12.), &bounds), 0.25);
+
+        let conic = Gradient::conic(Point::new(0.5, 0.5), Angle::zero());
+        assert_eq!(conic.offset_at(Point::new(20., 20.), &bounds), 0.25);
+    }
+}
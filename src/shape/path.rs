@@ -1,9 +1,10 @@
 use crate::{
-    math::{Point, Raw, Scale, Scaled, ScreenScale},
+    math::{Angle, Point, Raw, Scale, Scaled, ScreenScale, Vector},
     scene::Target,
-    shape::{Fill, Stroke},
+    shape::{Fill, Mesh, Stroke},
     KludgineError, KludgineResult,
 };
+use euclid::Transform2D;
 use lyon_tessellation::path::{builder::PathBuilder as _, PathEvent as LyonPathEvent};
 
 pub type Endpoint<S> = Point<f32, S>;
@@ -132,24 +133,24 @@ impl Path<Scaled> {
 }
 
 impl Path<Raw> {
-    pub fn build(
+    pub(crate) fn build(
         &self,
-        builder: &mut easygpu_lyon::ShapeBuilder,
+        builder: &mut Mesh,
         stroke: &Option<Stroke>,
         fill: &Option<Fill>,
     ) -> KludgineResult<()> {
         let path = self.as_lyon();
         if let Some(fill) = fill {
-            builder.default_color = fill.color.rgba();
+            builder.set_paint(&fill.paint);
             builder
                 .fill(&path, &fill.options)
                 .map_err(KludgineError::TessellationError)?;
         }
 
         if let Some(stroke) = stroke {
-            builder.default_color = stroke.color.rgba();
+            builder.set_paint(&stroke.paint);
             builder
-                .stroke(&path, &stroke.options)
+                .stroke(&stroke.outline(path), &stroke.options)
                 .map_err(KludgineError::TessellationError)?;
         }
 
@@ -165,6 +166,40 @@ impl Path<Raw> {
     }
 }
 
+impl<S> Path<S> {
+    /// Appends the subpaths of `other` to this path. When filled, areas
+    /// enclosed by an even number of subpaths are left empty, which allows
+    /// cutting holes in shapes.
+    pub fn combined(mut self, other: Path<S>) -> Self {
+        self.events.extend(other.events);
+        self
+    }
+
+    /// Returns this path with each point transformed by `transform`
+    pub fn transformed<Dst>(&self, transform: &Transform2D<f32, S, Dst>) -> Path<Dst> {
+        Path {
+            events: self
+                .events
+                .iter()
+                .map(|event| event.transformed(transform))
+                .collect(),
+        }
+    }
+}
+
+impl<S> Path<S>
+where
+    S: Copy,
+{
+    /// Creates a closed path outlining the ellipse centered on `center`
+    pub fn ellipse(center: Point<f32, S>, radii: Vector<f32, S>) -> Self {
+        PathBuilder::new(center + Vector::new(radii.x, 0.))
+            .elliptical_arc(center, radii, Angle::zero(), Angle::degrees(360.))
+            .close()
+            .build()
+    }
+}
+
 impl<S, T> From<T> for Path<S>
 where
     T: IntoIterator<Item = PathEvent<S>>,
@@ -241,6 +276,45 @@ where
         self
     }
 
+    /// Draws an arc of the ellipse centered on `center`, beginning at
+    /// `start_angle` and sweeping clockwise by `sweep`. A line is drawn to the
+    /// start of the arc if the path isn't already there.
+    pub fn elliptical_arc(
+        mut self,
+        center: Point<f32, S>,
+        radii: Vector<f32, S>,
+        start_angle: Angle,
+        sweep: Angle,
+    ) -> Self {
+        let point_at =
+            |angle: f32| center + Vector::new(radii.x * angle.cos(), radii.y * angle.sin());
+        let tangent_at = |angle: f32| Vector::new(-radii.x * angle.sin(), radii.y * angle.cos());
+
+        let start = point_at(start_angle.radians);
+        if (start - self.current_location).square_length() > f32::EPSILON {
+            self = self.line_to(start);
+        }
+
+        // Each segment is approximated with a cubic curve, which is accurate
+        // for sweeps up to a quarter turn.
+        let segments = (sweep.radians.abs() / std::f32::consts::FRAC_PI_2)
+            .ceil()
+            .max(1.);
+        let segment_sweep = sweep.radians / segments;
+        let handle_length = 4. / 3. * (segment_sweep / 4.).tan();
+        let mut angle = start_angle.radians;
+        for _ in 0..segments as usize {
+            let end_angle = angle + segment_sweep;
+            self = self.cubic_curve_to(
+                point_at(angle) + tangent_at(angle) * handle_length,
+                point_at(end_angle) - tangent_at(end_angle) * handle_length,
+                point_at(end_angle),
+            );
+            angle = end_angle;
+        }
+        self
+    }
+
     pub fn close(mut self) -> Self {
         self.close = true;
         self
@@ -256,6 +330,41 @@ impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for Path<Src> {
     }
 }
 
+impl<S> PathEvent<S> {
+    fn transformed<Dst>(&self, transform: &Transform2D<f32, S, Dst>) -> PathEvent<Dst> {
+        match *self {
+            PathEvent::Begin { at } => PathEvent::Begin {
+                at: transform.transform_point(at),
+            },
+            PathEvent::Line { from, to } => PathEvent::Line {
+                from: transform.transform_point(from),
+                to: transform.transform_point(to),
+            },
+            PathEvent::Quadratic { from, ctrl, to } => PathEvent::Quadratic {
+                from: transform.transform_point(from),
+                ctrl: transform.transform_point(ctrl),
+                to: transform.transform_point(to),
+            },
+            PathEvent::Cubic {
+                from,
+                ctrl1,
+                ctrl2,
+                to,
+            } => PathEvent::Cubic {
+                from: transform.transform_point(from),
+                ctrl1: transform.transform_point(ctrl1),
+                ctrl2: transform.transform_point(ctrl2),
+                to: transform.transform_point(to),
+            },
+            PathEvent::End { last, first, close } => PathEvent::End {
+                last: transform.transform_point(last),
+                first: transform.transform_point(first),
+                close,
+            },
+        }
+    }
+}
+
 impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for PathEvent<Src> {
     type Output = PathEvent<Dst>;
     fn mul(self, scale: Scale<f32, Src, Dst>) -> Self::Output {
@@ -0,0 +1,93 @@
+use crate::{
+    math::{Point, Raw, Scaled},
+    scene::{Element, Target},
+    shape::{Mesh, Shape},
+    KludgineError, KludgineResult,
+};
+use crossbeam::atomic::AtomicCell;
+use euclid::Transform2D;
+use lazy_static::lazy_static;
+use std::sync::Arc;
+
+lazy_static! {
+    static ref GLOBAL_ID_CELL: AtomicCell<u64> = AtomicCell::new(0);
+}
+
+/// Shapes that have been tessellated ahead of time. Preparing geometry that
+/// rarely changes, such as backgrounds and icons, avoids tessellating it every
+/// frame. The mesh is uploaded the first time it's drawn and reused until it
+/// stops being drawn. To change the geometry, prepare the new shapes.
+#[derive(Clone, Debug)]
+pub struct PreparedShape {
+    pub(crate) id: u64,
+    pub(crate) mesh: Arc<Mesh>,
+    /// The scale factor of the scene the shapes were tessellated for
+    scale_factor: f32,
+}
+
+impl PreparedShape {
+    /// Tessellates `shapes` for `scene`. Shapes painted with patterns can't be
+    /// prepared.
+    pub async fn new(shapes: &[Shape<Scaled>], scene: &Target) -> KludgineResult<Self> {
+        let effective_scale = scene.scale_factor().await;
+        let mut mesh = Mesh::default();
+        for shape in shapes {
+            let shape = shape.clone() * effective_scale;
+            if shape.pattern().is_some() {
+                return Err(KludgineError::PatternInPreparedShape);
+            }
+            shape.build(&mut mesh)?;
+        }
+
+        Ok(Self {
+            id: GLOBAL_ID_CELL.fetch_add(1),
+            mesh: Arc::new(mesh),
+            scale_factor: effective_scale.get(),
+        })
+    }
+
+    pub async fn render_at(&self, location: Point<f32, Scaled>, scene: &Target) {
+        self.render_scaled_at(location, 1., scene).await
+    }
+
+    /// Draws the shapes at `location`, scaled by `scale`
+    pub async fn render_scaled_at(&self, location: Point<f32, Scaled>, scale: f32, scene: &Target) {
+        self.render_transformed_at(location, &Transform2D::scale(scale, scale), scene)
+            .await
+    }
+
+    /// Draws the shapes at `location` after applying `transform`, which
+    /// allows rotating, scaling and skewing the shapes without tessellating
+    /// them again
+    pub async fn render_transformed_at(
+        &self,
+        location: Point<f32, Scaled>,
+        transform: &Transform2D<f32, Scaled, Scaled>,
+        scene: &Target,
+    ) {
+        let effective_scale = scene.scale_factor().await;
+        let location = scene.offset_point(location).await * effective_scale;
+        // The mesh is converted back to the coordinates it was prepared in,
+        // which also accounts for the scene's scale factor changing since the
+        // shapes were prepared
+        let transform =
+            Transform2D::<f32, Raw, Scaled>::scale(1. / self.scale_factor, 1. / self.scale_factor)
+                .then(transform)
+                .then_scale(effective_scale.get(), effective_scale.get())
+                .with_destination::<Raw>()
+                .then_translate(location.to_vector());
+        scene
+            .push_element(Element::PreparedShape {
+                shape: self.clone(),
+                transform,
+            })
+            .await
+    }
+}
+
+impl Shape<Scaled> {
+    /// Tessellates this shape ahead of time. See [`PreparedShape`].
+    pub async fn prepare(&self, scene: &Target) -> KludgineResult<PreparedShape> {
+        PreparedShape::new(std::slice::from_ref(self), scene).await
+    }
+}
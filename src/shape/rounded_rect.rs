@@ -0,0 +1,154 @@
+use crate::{
+    math::{Angle, Length, Point, Rect, Scale, Vector},
+    shape::{Path, PathBuilder},
+};
+
+/// The radius of each corner of a rounded rectangle
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CornerRadii<S> {
+    pub top_left: Length<f32, S>,
+    pub top_right: Length<f32, S>,
+    pub bottom_right: Length<f32, S>,
+    pub bottom_left: Length<f32, S>,
+}
+
+impl<S> CornerRadii<S> {
+    pub fn uniform(radius: Length<f32, S>) -> Self {
+        Self {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    pub fn with_top_left(mut self, radius: Length<f32, S>) -> Self {
+        self.top_left = radius;
+        self
+    }
+
+    pub fn with_top_right(mut self, radius: Length<f32, S>) -> Self {
+        self.top_right = radius;
+        self
+    }
+
+    pub fn with_bottom_right(mut self, radius: Length<f32, S>) -> Self {
+        self.bottom_right = radius;
+        self
+    }
+
+    pub fn with_bottom_left(mut self, radius: Length<f32, S>) -> Self {
+        self.bottom_left = radius;
+        self
+    }
+
+    /// Returns the radii reduced proportionally so that neighboring corners
+    /// don't overlap within `rect`
+    pub fn fitted_to(&self, rect: &Rect<f32, S>) -> Self {
+        let ratio = |length: f32, a: Length<f32, S>, b: Length<f32, S>| {
+            let total = a.get() + b.get();
+            if total > length && total > 0. {
+                length / total
+            } else {
+                1.
+            }
+        };
+        let scale = ratio(rect.size.width, self.top_left, self.top_right)
+            .min(ratio(rect.size.width, self.bottom_left, self.bottom_right))
+            .min(ratio(rect.size.height, self.top_left, self.bottom_left))
+            .min(ratio(rect.size.height, self.top_right, self.bottom_right))
+            .max(0.);
+        Self {
+            top_left: Length::new(self.top_left.get() * scale),
+            top_right: Length::new(self.top_right.get() * scale),
+            bottom_right: Length::new(self.bottom_right.get() * scale),
+            bottom_left: Length::new(self.bottom_left.get() * scale),
+        }
+    }
+}
+
+impl<Src, Dst> std::ops::Mul<Scale<f32, Src, Dst>> for CornerRadii<Src> {
+    type Output = CornerRadii<Dst>;
+
+    fn mul(self, scale: Scale<f32, Src, Dst>) -> Self::Output {
+        Self::Output {
+            top_left: self.top_left * scale,
+            top_right: self.top_right * scale,
+            bottom_right: self.bottom_right * scale,
+            bottom_left: self.bottom_left * scale,
+        }
+    }
+}
+
+impl<S> Path<S>
+where
+    S: Copy,
+{
+    /// Creates a closed path outlining `rect` with rounded corners
+    pub fn rounded_rect(rect: Rect<f32, S>, radii: CornerRadii<S>) -> Self {
+        let radii = radii.fitted_to(&rect);
+        let corner = |builder: PathBuilder<S>, center: Point<f32, S>, radius: f32, start: f32| {
+            if radius > 0. {
+                builder.elliptical_arc(
+                    center,
+                    Vector::new(radius, radius),
+                    Angle::degrees(start),
+                    Angle::degrees(90.),
+                )
+            } else {
+                builder.line_to(center)
+            }
+        };
+
+        let (top_left, top_right, bottom_right, bottom_left) = (
+            radii.top_left.get(),
+            radii.top_right.get(),
+            radii.bottom_right.get(),
+            radii.bottom_left.get(),
+        );
+        let builder = PathBuilder::new(Point::new(rect.min_x() + top_left, rect.min_y()));
+        let builder = corner(
+            builder,
+            Point::new(rect.max_x() - top_right, rect.min_y() + top_right),
+            top_right,
+            -90.,
+        );
+        let builder = corner(
+            builder,
+            Point::new(rect.max_x() - bottom_right, rect.max_y() - bottom_right),
+            bottom_right,
+            0.,
+        );
+        let builder = corner(
+            builder,
+            Point::new(rect.min_x() + bottom_left, rect.max_y() - bottom_left),
+            bottom_left,
+            90.,
+        );
+        corner(
+            builder,
+            Point::new(rect.min_x() + top_left, rect.min_y() + top_left),
+            top_left,
+            180.,
+        )
+        .close()
+        .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{Size, Unknown};
+
+    #[test]
+    fn fitted_radii_test() {
+        let rect = Rect::<f32, Unknown>::new(Point::default(), Size::new(10., 4.));
+        let radii = CornerRadii::uniform(Length::new(4.)).with_bottom_left(Length::new(0.));
+        let fitted = radii.fitted_to(&rect);
+        // The right corners would overlap vertically, so every corner shrinks
+        assert_eq!(fitted.top_right, Length::new(2.));
+        assert_eq!(fitted.top_left, Length::new(2.));
+        assert_eq!(fitted.bottom_left, Length::new(0.));
+    }
+}
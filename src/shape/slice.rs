@@ -0,0 +1,126 @@
+use crate::math::{Point, Raw, Rect};
+
+/// A convex polygon
+pub(crate) type Polygon = Vec<Point<f32, Raw>>;
+
+/// Splits convex polygons along each line where `value` equals one of `cuts`.
+/// `value` must change linearly across the screen for the cuts to be exact.
+/// Because every polygon is split at the same lines, edges shared between
+/// neighboring polygons are split at the same points.
+pub(crate) fn slice<F: Fn(Point<f32, Raw>) -> f32>(
+    polygons: Vec<Polygon>,
+    value: F,
+    cuts: &[f32],
+) -> Vec<Polygon> {
+    let mut polygons = polygons;
+    for &cut in cuts {
+        let mut split_polygons = Vec::with_capacity(polygons.len());
+        for polygon in polygons {
+            let (below, above) = split(&polygon, &value, cut);
+            if below.len() >= 3 {
+                split_polygons.push(below);
+            }
+            if above.len() >= 3 {
+                split_polygons.push(above);
+            }
+        }
+        polygons = split_polygons;
+    }
+    polygons
+}
+
+/// Splits convex polygons into the cells of a grid whose lines pass through
+/// `origin`
+pub(crate) fn slice_grid(
+    polygons: Vec<Polygon>,
+    bounds: &Rect<f32, Raw>,
+    origin: Point<f32, Raw>,
+    cell_width: f32,
+    cell_height: f32,
+) -> Vec<Polygon> {
+    let columns = grid_lines(bounds.min_x(), bounds.max_x(), origin.x, cell_width);
+    let rows = grid_lines(bounds.min_y(), bounds.max_y(), origin.y, cell_height);
+    let polygons = slice(polygons, |point| point.x, &columns);
+    slice(polygons, |point| point.y, &rows)
+}
+
+/// Returns the locations of the grid lines strictly between `min` and `max`
+fn grid_lines(min: f32, max: f32, origin: f32, spacing: f32) -> Vec<f32> {
+    if spacing <= 0. {
+        return Vec::new();
+    }
+
+    let mut line = origin + ((min - origin) / spacing).floor() * spacing;
+    let mut lines = Vec::new();
+    while line < max {
+        if line > min {
+            lines.push(line);
+        }
+        line += spacing;
+    }
+    lines
+}
+
+fn split<F: Fn(Point<f32, Raw>) -> f32>(
+    polygon: &[Point<f32, Raw>],
+    value: &F,
+    cut: f32,
+) -> (Polygon, Polygon) {
+    let mut below = Vec::new();
+    let mut above = Vec::new();
+    for (index, &point) in polygon.iter().enumerate() {
+        let next = polygon[(index + 1) % polygon.len()];
+        let point_value = value(point) - cut;
+        let next_value = value(next) - cut;
+        if point_value <= 0. {
+            below.push(point);
+        }
+        if point_value >= 0. {
+            above.push(point);
+        }
+        if (point_value < 0. && next_value > 0.) || (point_value > 0. && next_value < 0.) {
+            let amount = point_value / (point_value - next_value);
+            let intersection = point.lerp(next, amount);
+            below.push(intersection);
+            above.push(intersection);
+        }
+    }
+    (below, above)
+}
+
+/// Splits a convex polygon into triangles
+pub(crate) fn triangulate(polygon: &[Point<f32, Raw>]) -> impl Iterator<Item = [usize; 3]> {
+    (1..polygon.len().saturating_sub(1)).map(|index| [0, index, index + 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slice_test() {
+        let square = vec![
+            Point::new(0., 0.),
+            Point::new(4., 0.),
+            Point::new(4., 4.),
+            Point::new(0., 4.),
+        ];
+        let sliced = slice_grid(
+            vec![square],
+            &Rect::new(Point::new(0., 0.), euclid::Size2D::new(4., 4.)),
+            Point::new(1., 1.),
+            2.,
+            2.,
+        );
+        // Lines at x = 1, x = 3, y = 1 and y = 3 cut the square into 9 cells
+        assert_eq!(sliced.len(), 9);
+        for cell in sliced {
+            let cell_bounds = Rect::from_points(&cell);
+            assert!(cell_bounds.size.width <= 2. && cell_bounds.size.height <= 2.);
+        }
+
+        // Cutting exactly along an edge leaves the polygon intact
+        let triangle = vec![Point::new(0., 0.), Point::new(2., 0.), Point::new(0., 2.)];
+        assert_eq!(slice(vec![triangle], |point| point.x, &[0.]).len(), 1);
+    }
+}
@@ -1,16 +1,241 @@
-use crate::color::Color;
+use crate::shape::Paint;
+use lyon_tessellation::path::{
+    builder::PathBuilder as _, iterator::PathIterator, math::Point as LyonPoint, Path as LyonPath,
+    PathEvent as LyonPathEvent,
+};
 
 #[derive(Default, Clone, Debug)]
 pub struct Stroke {
-    pub color: Color,
+    pub paint: Paint,
     pub options: lyon_tessellation::StrokeOptions,
+    /// Breaks the stroke into dashes, or None for a solid stroke
+    pub dashes: Option<StrokeDashes>,
 }
 
 impl Stroke {
-    pub fn new(color: Color) -> Self {
+    pub fn new(paint: impl Into<Paint>) -> Self {
         Self {
-            color,
+            paint: paint.into(),
             options: Default::default(),
+            dashes: None,
         }
     }
+
+    pub fn with_options(mut self, options: lyon_tessellation::StrokeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn with_dashes(mut self, dashes: StrokeDashes) -> Self {
+        self.dashes = Some(dashes);
+        self
+    }
+
+    pub(crate) fn scaled(&self, scale: f32) -> Self {
+        Self {
+            paint: self.paint.scaled(scale),
+            options: self.options,
+            dashes: self.dashes.clone(),
+        }
+    }
+
+    /// Returns the path to tessellate for `path`, which is broken into dashes
+    /// if this stroke is dashed
+    pub(crate) fn outline(&self, path: LyonPath) -> LyonPath {
+        match &self.dashes {
+            Some(dashes) => dashes.apply(&path, self.options.tolerance),
+            None => path,
+        }
+    }
+}
+
+/// A repeating pattern of dashes and gaps. Dotted strokes can be made using
+/// short dashes with round line caps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeDashes {
+    /// Alternating lengths of dashes and gaps, beginning with a dash
+    pub lengths: Vec<f32>,
+    /// How far into the pattern each subpath starts
+    pub offset: f32,
+}
+
+impl StrokeDashes {
+    /// Creates a pattern from alternating dash and gap lengths. An odd number
+    /// of lengths is repeated to make an even number of dashes and gaps.
+    pub fn new(mut lengths: Vec<f32>) -> Self {
+        if lengths.len() % 2 == 1 {
+            lengths.extend_from_within(..);
+        }
+        Self {
+            lengths,
+            offset: 0.,
+        }
+    }
+
+    /// Evenly sized dashes and gaps
+    pub fn dashed(length: f32, gap: f32) -> Self {
+        Self::new(vec![length, gap])
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    fn apply(&self, path: &LyonPath, tolerance: f32) -> LyonPath {
+        let pattern_length = self.lengths.iter().sum::<f32>();
+        if pattern_length <= 0. || self.lengths.iter().any(|length| *length < 0.) {
+            return path.clone();
+        }
+
+        let mut dasher = Dasher {
+            lengths: &self.lengths,
+            events: Vec::new(),
+            index: 0,
+            remaining: 0.,
+            dash_start: None,
+        };
+        for event in path.iter().flattened(tolerance) {
+            match event {
+                LyonPathEvent::Begin { .. } => dasher.restart(self.offset, pattern_length),
+                LyonPathEvent::Line { from, to } => dasher.line(from, to),
+                LyonPathEvent::End { last, first, close } => {
+                    if close {
+                        dasher.line(last, first);
+                    }
+                    dasher.finish_dash(if close { first } else { last });
+                }
+                _ => {}
+            }
+        }
+
+        let mut builder = LyonPath::builder();
+        for event in dasher.events {
+            builder.path_event(event);
+        }
+        builder.build()
+    }
+}
+
+/// Walks along a flattened path, emitting a subpath for each dash
+struct Dasher<'a> {
+    lengths: &'a [f32],
+    events: Vec<LyonPathEvent>,
+    index: usize,
+    remaining: f32,
+    /// The start of the dash being drawn, and the last point added to it
+    dash_start: Option<(LyonPoint, LyonPoint)>,
+}
+
+impl<'a> Dasher<'a> {
+    fn is_dash(&self) -> bool {
+        self.index & 1 == 0
+    }
+
+    fn restart(&mut self, offset: f32, pattern_length: f32) {
+        self.index = 0;
+        self.remaining = self.lengths[0];
+        let mut offset = offset.rem_euclid(pattern_length);
+        while offset >= self.remaining {
+            offset -= self.remaining;
+            self.index = (self.index + 1) % self.lengths.len();
+            self.remaining = self.lengths[self.index];
+        }
+        self.remaining -= offset;
+        self.dash_start = None;
+    }
+
+    fn line(&mut self, from: LyonPoint, to: LyonPoint) {
+        let length = (to - from).length();
+        let mut traveled = 0.;
+        loop {
+            if self.is_dash() && self.dash_start.is_none() {
+                let start = from.lerp(to, traveled / length.max(f32::EPSILON));
+                self.dash_start = Some((start, start));
+                self.events.push(LyonPathEvent::Begin { at: start });
+            }
+
+            if length - traveled < self.remaining {
+                self.remaining -= length - traveled;
+                self.line_to(to);
+                return;
+            }
+
+            traveled += self.remaining;
+            let point = from.lerp(to, traveled / length.max(f32::EPSILON));
+            self.line_to(point);
+            self.finish_dash(point);
+            self.index = (self.index + 1) % self.lengths.len();
+            self.remaining = self.lengths[self.index];
+        }
+    }
+
+    fn line_to(&mut self, point: LyonPoint) {
+        if let Some((_, last)) = &mut self.dash_start {
+            if *last != point {
+                self.events.push(LyonPathEvent::Line {
+                    from: *last,
+                    to: point,
+                });
+                *last = point;
+            }
+        }
+    }
+
+    fn finish_dash(&mut self, point: LyonPoint) {
+        if let Some((first, _)) = self.dash_start.take() {
+            self.events.push(LyonPathEvent::End {
+                last: point,
+                first,
+                close: false,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lyon_tessellation::math::point;
+
+    #[test]
+    fn dash_test() {
+        let mut builder = LyonPath::builder();
+        builder.path_event(LyonPathEvent::Begin { at: point(0., 0.) });
+        builder.path_event(LyonPathEvent::Line {
+            from: point(0., 0.),
+            to: point(10., 0.),
+        });
+        builder.path_event(LyonPathEvent::End {
+            last: point(10., 0.),
+            first: point(0., 0.),
+            close: false,
+        });
+        let path = builder.build();
+
+        let dashes = |dashes: StrokeDashes| {
+            dashes
+                .apply(&path, 0.1)
+                .iter()
+                .filter_map(|event| match event {
+                    LyonPathEvent::End { first, last, .. } => Some((first.x, last.x)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            dashes(StrokeDashes::dashed(3., 1.)),
+            vec![(0., 3.), (4., 7.), (8., 10.)]
+        );
+        assert_eq!(
+            dashes(StrokeDashes::new(vec![2., 3.]).with_offset(4.)),
+            vec![(1., 3.), (6., 8.)]
+        );
+        // An odd number of lengths alternates between dashes and gaps
+        assert_eq!(StrokeDashes::new(vec![1., 2., 3.]).lengths.len(), 6);
+        assert_eq!(
+            dashes(StrokeDashes::new(vec![1., 2., 3.])),
+            vec![(0., 1.), (3., 6.), (7., 9.)]
+        );
+    }
 }
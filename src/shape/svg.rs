@@ -0,0 +1,1010 @@
+use crate::{
+    color::Color,
+    math::{Angle, Point, Rect, Scaled, Size, Unknown, Vector},
+    scene::Target,
+    shape::{
+        geometry::ShapeGeometry, CornerRadii, Fill, Path, PathBuilder, PathEvent, PreparedShape,
+        Shape, Stroke, StrokeDashes,
+    },
+    KludgineError, KludgineResult,
+};
+use euclid::Transform2D;
+use lyon_tessellation::{
+    geom::{ArcFlags, SvgArc},
+    math as lyon_math, FillOptions, FillRule, LineCap, LineJoin, StrokeOptions,
+};
+use roxmltree::{Document, Node};
+
+type SvgTransform = Transform2D<f32, Unknown, Unknown>;
+
+/// Vector graphics loaded from an SVG document. Paths, rects, circles,
+/// ellipses, lines, polylines and polygons are supported, along with groups,
+/// transforms, and solid colored fills and strokes. Paints that reference
+/// another element, such as gradients, are drawn with their fallback color,
+/// or not drawn if they don't have one.
+#[derive(Clone, Debug)]
+pub struct Svg {
+    /// The area of the document that is drawn
+    pub view_box: Rect<f32, Unknown>,
+    shapes: Vec<SvgShape>,
+}
+
+#[derive(Clone, Debug)]
+struct SvgShape {
+    path: Path<Unknown>,
+    fill: Option<Fill>,
+    stroke: Option<Stroke>,
+}
+
+impl Svg {
+    pub fn parse(source: &str) -> KludgineResult<Self> {
+        let document = Document::parse(source).map_err(|err| svg_error(err.to_string()))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "svg" {
+            return Err(svg_error("the root element must be <svg>"));
+        }
+
+        let view_box = match root.attribute("viewBox") {
+            Some(view_box) => match *parse_numbers(view_box)?.as_slice() {
+                [x, y, width, height] => Rect::new(Point::new(x, y), Size::new(width, height)),
+                _ => return Err(svg_error("viewBox must contain 4 numbers")),
+            },
+            None => match (
+                optional_length(root, "width")?,
+                optional_length(root, "height")?,
+            ) {
+                (Some(width), Some(height)) => {
+                    Rect::new(Point::default(), Size::new(width, height))
+                }
+                _ => return Err(svg_error("a viewBox or a width and height is required")),
+            },
+        };
+
+        let mut shapes = Vec::new();
+        load_element(
+            root,
+            &Style::default(),
+            &SvgTransform::identity(),
+            &mut shapes,
+        )?;
+        Ok(Self { view_box, shapes })
+    }
+
+    /// Returns the shapes of this document, scaled so that the view box fits
+    /// within `size`. The aspect ratio is preserved, and the drawing is
+    /// centered within `size`.
+    pub fn shapes_sized<S: Copy>(&self, size: Size<f32, S>) -> Vec<Shape<S>> {
+        let scale =
+            (size.width / self.view_box.size.width).min(size.height / self.view_box.size.height);
+        if !scale.is_finite() || scale <= 0. {
+            return Vec::new();
+        }
+
+        let offset_x = (size.width - self.view_box.size.width * scale) / 2.;
+        let offset_y = (size.height - self.view_box.size.height * scale) / 2.;
+        let transform = Transform2D::new(
+            scale,
+            0.,
+            0.,
+            scale,
+            offset_x - self.view_box.origin.x * scale,
+            offset_y - self.view_box.origin.y * scale,
+        );
+        self.shapes
+            .iter()
+            .map(|shape| Shape {
+                geometry: ShapeGeometry::Path(shape.path.transformed(&transform)),
+                fill: shape.fill.clone(),
+                stroke: shape
+                    .stroke
+                    .as_ref()
+                    .map(|stroke| scale_stroke(stroke, scale)),
+            })
+            .collect()
+    }
+
+    /// Tessellates this document ahead of time, sized to fit within `size`.
+    /// See [`PreparedShape`].
+    pub async fn prepare(
+        &self,
+        size: Size<f32, Scaled>,
+        scene: &Target,
+    ) -> KludgineResult<PreparedShape> {
+        PreparedShape::new(&self.shapes_sized(size), scene).await
+    }
+
+    /// Draws this document at `location`, sized to fit within `size`
+    pub async fn render_at(
+        &self,
+        location: Point<f32, Scaled>,
+        size: Size<f32, Scaled>,
+        scene: &Target,
+    ) {
+        for shape in self.shapes_sized(size) {
+            shape.render_at(location, scene).await;
+        }
+    }
+}
+
+impl<S> Path<S>
+where
+    S: Copy,
+{
+    /// Parses SVG path data, the contents of a `<path>` element's `d`
+    /// attribute
+    pub fn from_svg_data(data: &str) -> KludgineResult<Self> {
+        let mut parser = PathDataParser {
+            tokens: Tokens::new(data),
+            events: Vec::new(),
+            start: Point::zero(),
+            current: Point::zero(),
+            in_subpath: false,
+            previous_cubic: None,
+            previous_quadratic: None,
+        };
+        parser.parse()?;
+        Ok(Path::from(parser.events))
+    }
+}
+
+struct PathDataParser<'a, S> {
+    tokens: Tokens<'a>,
+    events: Vec<PathEvent<S>>,
+    start: Point<f32, S>,
+    current: Point<f32, S>,
+    in_subpath: bool,
+    /// The last control point of the previous command, used to mirror the
+    /// control point of smooth curves
+    previous_cubic: Option<Point<f32, S>>,
+    previous_quadratic: Option<Point<f32, S>>,
+}
+
+impl<S> PathDataParser<'_, S>
+where
+    S: Copy,
+{
+    fn parse(&mut self) -> KludgineResult<()> {
+        let mut command = None;
+        while !self.tokens.is_done() {
+            if let Some(next_command) = self.tokens.command() {
+                command = Some(next_command);
+            }
+            let current_command = command.ok_or_else(|| svg_error("expected a path command"))?;
+            let origin = if current_command.is_ascii_lowercase() {
+                self.current.to_vector()
+            } else {
+                Vector::zero()
+            };
+            let previous_cubic = self.previous_cubic.take();
+            let previous_quadratic = self.previous_quadratic.take();
+
+            match current_command.to_ascii_uppercase() {
+                'M' => {
+                    let to = self.point(origin)?;
+                    self.end_subpath(false);
+                    self.start = to;
+                    self.current = to;
+                    // Additional coordinates are treated as lines
+                    command = Some(if current_command == 'm' { 'l' } else { 'L' });
+                }
+                'L' => {
+                    let to = self.point(origin)?;
+                    self.line_to(to);
+                }
+                'H' => {
+                    let x = self.tokens.number()? + origin.x;
+                    self.line_to(Point::new(x, self.current.y));
+                }
+                'V' => {
+                    let y = self.tokens.number()? + origin.y;
+                    self.line_to(Point::new(self.current.x, y));
+                }
+                'C' | 'S' => {
+                    let ctrl1 = if current_command.eq_ignore_ascii_case(&'C') {
+                        self.point(origin)?
+                    } else {
+                        self.mirrored(previous_cubic)
+                    };
+                    let ctrl2 = self.point(origin)?;
+                    let to = self.point(origin)?;
+                    self.begin_subpath();
+                    self.events.push(PathEvent::Cubic {
+                        from: self.current,
+                        ctrl1,
+                        ctrl2,
+                        to,
+                    });
+                    self.current = to;
+                    self.previous_cubic = Some(ctrl2);
+                }
+                'Q' | 'T' => {
+                    let ctrl = if current_command.eq_ignore_ascii_case(&'Q') {
+                        self.point(origin)?
+                    } else {
+                        self.mirrored(previous_quadratic)
+                    };
+                    let to = self.point(origin)?;
+                    self.begin_subpath();
+                    self.events.push(PathEvent::Quadratic {
+                        from: self.current,
+                        ctrl,
+                        to,
+                    });
+                    self.current = to;
+                    self.previous_quadratic = Some(ctrl);
+                }
+                'A' => {
+                    let radii =
+                        lyon_math::vector(self.tokens.number()?.abs(), self.tokens.number()?.abs());
+                    let x_rotation = Angle::degrees(self.tokens.number()?);
+                    let flags = ArcFlags {
+                        large_arc: self.tokens.flag()?,
+                        sweep: self.tokens.flag()?,
+                    };
+                    let to = self.point(origin)?;
+                    self.arc_to(radii, x_rotation, flags, to);
+                }
+                'Z' => {
+                    self.end_subpath(true);
+                    self.current = self.start;
+                    // Coordinates can't follow a close command
+                    command = None;
+                }
+                other => return Err(svg_error(format!("unknown path command {}", other))),
+            }
+        }
+
+        self.end_subpath(false);
+        Ok(())
+    }
+
+    fn point(&mut self, origin: Vector<f32, S>) -> KludgineResult<Point<f32, S>> {
+        let x = self.tokens.number()?;
+        let y = self.tokens.number()?;
+        Ok(Point::new(x, y) + origin)
+    }
+
+    /// Reflects the previous control point around the current point, or
+    /// returns the current point if the previous command wasn't a matching
+    /// curve
+    fn mirrored(&self, previous: Option<Point<f32, S>>) -> Point<f32, S> {
+        match previous {
+            Some(previous) => self.current + (self.current - previous),
+            None => self.current,
+        }
+    }
+
+    fn begin_subpath(&mut self) {
+        if !self.in_subpath {
+            self.events.push(PathEvent::Begin { at: self.current });
+            self.start = self.current;
+            self.in_subpath = true;
+        }
+    }
+
+    fn end_subpath(&mut self, close: bool) {
+        if self.in_subpath {
+            self.events.push(PathEvent::End {
+                last: self.current,
+                first: self.start,
+                close,
+            });
+            self.in_subpath = false;
+        }
+    }
+
+    fn line_to(&mut self, to: Point<f32, S>) {
+        self.begin_subpath();
+        self.events.push(PathEvent::Line {
+            from: self.current,
+            to,
+        });
+        self.current = to;
+    }
+
+    fn arc_to(
+        &mut self,
+        radii: lyon_math::Vector,
+        x_rotation: Angle,
+        flags: ArcFlags,
+        to: Point<f32, S>,
+    ) {
+        let arc = SvgArc {
+            from: lyon_math::point(self.current.x, self.current.y),
+            to: lyon_math::point(to.x, to.y),
+            radii,
+            x_rotation,
+            flags,
+        };
+        if arc.is_straight_line() {
+            self.line_to(to);
+            return;
+        }
+
+        let mut segments = Vec::new();
+        arc.for_each_cubic_bezier(&mut |segment| segments.push(*segment));
+        self.begin_subpath();
+        for segment in segments {
+            let segment_end = Point::new(segment.to.x, segment.to.y);
+            self.events.push(PathEvent::Cubic {
+                from: self.current,
+                ctrl1: Point::new(segment.ctrl1.x, segment.ctrl1.y),
+                ctrl2: Point::new(segment.ctrl2.x, segment.ctrl2.y),
+                to: segment_end,
+            });
+            self.current = segment_end;
+        }
+    }
+}
+
+/// Reads numbers, flags and commands from SVG attribute values, where values
+/// may be separated by whitespace, commas, or nothing at all
+struct Tokens<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source: source.as_bytes(),
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.position).copied()
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() || byte == b',' {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn is_done(&mut self) -> bool {
+        self.skip_separators();
+        self.position >= self.source.len()
+    }
+
+    fn command(&mut self) -> Option<char> {
+        self.skip_separators();
+        match self.peek() {
+            Some(byte) if byte.is_ascii_alphabetic() => {
+                self.position += 1;
+                Some(byte as char)
+            }
+            _ => None,
+        }
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.position;
+        while matches!(self.peek(), Some(byte) if byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn number(&mut self) -> KludgineResult<f32> {
+        self.skip_separators();
+        let start = self.position;
+        if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+            self.position += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.position = start;
+            return Err(svg_error("expected a number"));
+        }
+
+        // Only consume an exponent if it's complete, because `e` could be
+        // the start of a unit like `em`
+        if matches!(self.peek(), Some(b'e') | Some(b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+                self.position += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.position = mantissa_end;
+            }
+        }
+
+        std::str::from_utf8(&self.source[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .ok_or_else(|| svg_error("invalid number"))
+    }
+
+    /// Reads an arc flag, which may be followed immediately by another value
+    fn flag(&mut self) -> KludgineResult<bool> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(svg_error("expected an arc flag")),
+        };
+        self.position += 1;
+        Ok(flag)
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.source[self.position..]
+    }
+}
+
+/// The presentation attributes that are inherited by an element's children
+#[derive(Clone, Debug)]
+struct Style {
+    color: Color,
+    fill: Option<Color>,
+    fill_opacity: f32,
+    fill_rule: FillRule,
+    stroke: Option<Color>,
+    stroke_opacity: f32,
+    stroke_width: f32,
+    line_cap: LineCap,
+    line_join: LineJoin,
+    miter_limit: f32,
+    dashes: Option<Vec<f32>>,
+    dash_offset: f32,
+    opacity: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            color: Color::BLACK,
+            fill: Some(Color::BLACK),
+            fill_opacity: 1.,
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+            stroke_opacity: 1.,
+            stroke_width: 1.,
+            line_cap: LineCap::Butt,
+            line_join: LineJoin::Miter,
+            miter_limit: StrokeOptions::DEFAULT_MITER_LIMIT,
+            dashes: None,
+            dash_offset: 0.,
+            opacity: 1.,
+        }
+    }
+}
+
+impl Style {
+    /// Returns this style with the presentation attributes and `style`
+    /// declarations of `node` applied
+    fn inherited_by(&self, node: Node<'_, '_>) -> KludgineResult<Self> {
+        let mut style = self.clone();
+        // `color` is applied first because other properties can refer to it
+        if let Some(color) = node.attribute("color") {
+            style.apply("color", color)?;
+        }
+        for attribute in node.attributes() {
+            if attribute.name() != "color" {
+                style.apply(attribute.name(), attribute.value())?;
+            }
+        }
+        if let Some(declarations) = node.attribute("style") {
+            for declaration in declarations.split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    style.apply(name.trim(), value.trim())?;
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    fn apply(&mut self, name: &str, value: &str) -> KludgineResult<()> {
+        if value == "inherit" {
+            return Ok(());
+        }
+
+        match name {
+            "color" => self.color = parse_color(value, self.color)?,
+            "fill" => self.fill = parse_paint(value, self.color)?,
+            "fill-opacity" => self.fill_opacity = parse_number(value)?,
+            "fill-rule" => {
+                self.fill_rule = match value {
+                    "nonzero" => FillRule::NonZero,
+                    "evenodd" => FillRule::EvenOdd,
+                    _ => return Err(svg_error(format!("unknown fill-rule {}", value))),
+                }
+            }
+            "stroke" => self.stroke = parse_paint(value, self.color)?,
+            "stroke-opacity" => self.stroke_opacity = parse_number(value)?,
+            "stroke-width" => self.stroke_width = parse_length(value)?,
+            "stroke-linecap" => {
+                self.line_cap = match value {
+                    "butt" => LineCap::Butt,
+                    "round" => LineCap::Round,
+                    "square" => LineCap::Square,
+                    _ => return Err(svg_error(format!("unknown stroke-linecap {}", value))),
+                }
+            }
+            "stroke-linejoin" => {
+                self.line_join = match value {
+                    "miter" | "miter-clip" | "arcs" => LineJoin::Miter,
+                    "round" => LineJoin::Round,
+                    "bevel" => LineJoin::Bevel,
+                    _ => return Err(svg_error(format!("unknown stroke-linejoin {}", value))),
+                }
+            }
+            "stroke-miterlimit" => self.miter_limit = parse_number(value)?,
+            "stroke-dasharray" => {
+                self.dashes = if value == "none" {
+                    None
+                } else {
+                    Some(parse_numbers(value)?)
+                }
+            }
+            "stroke-dashoffset" => self.dash_offset = parse_length(value)?,
+            "opacity" => self.opacity *= parse_number(value)?,
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn fill(&self) -> Option<Fill> {
+        self.fill.map(|color| {
+            let alpha = color.alpha() * self.fill_opacity * self.opacity;
+            Fill::new(color.with_alpha(alpha))
+                .with_options(FillOptions::default().with_fill_rule(self.fill_rule))
+        })
+    }
+
+    /// Returns the stroke for a shape drawn with `transform`
+    fn stroke(&self, transform: &SvgTransform) -> Option<Stroke> {
+        let color = self.stroke?;
+        if self.stroke_width <= 0. {
+            return None;
+        }
+
+        let alpha = color.alpha() * self.stroke_opacity * self.opacity;
+        let options = StrokeOptions::default()
+            .with_line_width(self.stroke_width)
+            .with_line_cap(self.line_cap)
+            .with_line_join(self.line_join)
+            .with_miter_limit(self.miter_limit);
+        let mut stroke = Stroke::new(color.with_alpha(alpha)).with_options(options);
+        if let Some(lengths) = &self.dashes {
+            stroke = stroke
+                .with_dashes(StrokeDashes::new(lengths.clone()).with_offset(self.dash_offset));
+        }
+        // Strokes can't be skewed, so non-uniform transforms use the average
+        // scale
+        Some(scale_stroke(&stroke, transform.determinant().abs().sqrt()))
+    }
+}
+
+fn scale_stroke(stroke: &Stroke, scale: f32) -> Stroke {
+    let mut stroke = stroke.clone();
+    stroke.options.line_width *= scale;
+    if let Some(dashes) = &mut stroke.dashes {
+        for length in dashes.lengths.iter_mut() {
+            *length *= scale;
+        }
+        dashes.offset *= scale;
+    }
+    stroke
+}
+
+fn load_element(
+    node: Node<'_, '_>,
+    parent_style: &Style,
+    parent_transform: &SvgTransform,
+    shapes: &mut Vec<SvgShape>,
+) -> KludgineResult<()> {
+    if node.attribute("display") == Some("none") {
+        return Ok(());
+    }
+
+    let style = parent_style.inherited_by(node)?;
+    let transform = match node.attribute("transform") {
+        Some(transform) => parse_transform(transform)?.then(parent_transform),
+        None => *parent_transform,
+    };
+
+    let path = match node.tag_name().name() {
+        "svg" | "g" => {
+            for child in node.children().filter(|child| child.is_element()) {
+                load_element(child, &style, &transform, shapes)?;
+            }
+            return Ok(());
+        }
+        "path" => match node.attribute("d") {
+            Some(data) => Some(Path::from_svg_data(data)?),
+            None => None,
+        },
+        "rect" => rect_path(node)?,
+        "circle" => {
+            let radius = length_attribute(node, "r")?;
+            if radius > 0. {
+                Some(Path::ellipse(
+                    center_attributes(node)?,
+                    Vector::new(radius, radius),
+                ))
+            } else {
+                None
+            }
+        }
+        "ellipse" => {
+            let radii = Vector::new(length_attribute(node, "rx")?, length_attribute(node, "ry")?);
+            if radii.x > 0. && radii.y > 0. {
+                Some(Path::ellipse(center_attributes(node)?, radii))
+            } else {
+                None
+            }
+        }
+        "line" => Some(
+            PathBuilder::new(Point::new(
+                length_attribute(node, "x1")?,
+                length_attribute(node, "y1")?,
+            ))
+            .line_to(Point::new(
+                length_attribute(node, "x2")?,
+                length_attribute(node, "y2")?,
+            ))
+            .build(),
+        ),
+        "polyline" => points_path(node, false)?,
+        "polygon" => points_path(node, true)?,
+        // Definitions, metadata and unsupported elements aren't drawn
+        _ => None,
+    };
+
+    if let Some(path) = path {
+        // Lines have no area to fill
+        let fill = if node.tag_name().name() == "line" {
+            None
+        } else {
+            style.fill()
+        };
+        shapes.push(SvgShape {
+            path: path.transformed(&transform),
+            fill,
+            stroke: style.stroke(&transform),
+        });
+    }
+    Ok(())
+}
+
+fn rect_path(node: Node<'_, '_>) -> KludgineResult<Option<Path<Unknown>>> {
+    let rect = Rect::new(
+        Point::new(length_attribute(node, "x")?, length_attribute(node, "y")?),
+        Size::new(
+            length_attribute(node, "width")?,
+            length_attribute(node, "height")?,
+        ),
+    );
+    if rect.is_empty() {
+        return Ok(None);
+    }
+
+    // A missing radius uses the other radius
+    let (rx, ry) = match (optional_length(node, "rx")?, optional_length(node, "ry")?) {
+        (Some(rx), Some(ry)) => (rx, ry),
+        (Some(radius), None) | (None, Some(radius)) => (radius, radius),
+        (None, None) => (0., 0.),
+    };
+    let radii = Vector::new(
+        rx.clamp(0., rect.size.width / 2.),
+        ry.clamp(0., rect.size.height / 2.),
+    );
+    if radii.x <= 0. || radii.y <= 0. {
+        return Ok(Some(Path::rounded_rect(rect, CornerRadii::default())));
+    }
+
+    let corner = |x: f32, y: f32| Point::new(x, y);
+    Ok(Some(
+        PathBuilder::new(corner(rect.min_x() + radii.x, rect.min_y()))
+            .elliptical_arc(
+                corner(rect.max_x() - radii.x, rect.min_y() + radii.y),
+                radii,
+                Angle::degrees(-90.),
+                Angle::degrees(90.),
+            )
+            .elliptical_arc(
+                corner(rect.max_x() - radii.x, rect.max_y() - radii.y),
+                radii,
+                Angle::zero(),
+                Angle::degrees(90.),
+            )
+            .elliptical_arc(
+                corner(rect.min_x() + radii.x, rect.max_y() - radii.y),
+                radii,
+                Angle::degrees(90.),
+                Angle::degrees(90.),
+            )
+            .elliptical_arc(
+                corner(rect.min_x() + radii.x, rect.min_y() + radii.y),
+                radii,
+                Angle::degrees(180.),
+                Angle::degrees(90.),
+            )
+            .close()
+            .build(),
+    ))
+}
+
+fn points_path(node: Node<'_, '_>, close: bool) -> KludgineResult<Option<Path<Unknown>>> {
+    let numbers = parse_numbers(node.attribute("points").unwrap_or_default())?;
+    let mut points = numbers
+        .chunks_exact(2)
+        .map(|point| Point::new(point[0], point[1]));
+    let mut builder = match points.next() {
+        Some(start) => PathBuilder::new(start),
+        None => return Ok(None),
+    };
+    for point in points {
+        builder = builder.line_to(point);
+    }
+    if close {
+        builder = builder.close();
+    }
+    Ok(Some(builder.build()))
+}
+
+fn center_attributes(node: Node<'_, '_>) -> KludgineResult<Point<f32, Unknown>> {
+    Ok(Point::new(
+        length_attribute(node, "cx")?,
+        length_attribute(node, "cy")?,
+    ))
+}
+
+/// Parses a transform list, such as `translate(10 10) rotate(45)`
+fn parse_transform(value: &str) -> KludgineResult<SvgTransform> {
+    let mut transform = SvgTransform::identity();
+    let mut remaining = value.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    while !remaining.is_empty() {
+        let (name, rest) = remaining
+            .split_once('(')
+            .ok_or_else(|| svg_error(format!("invalid transform {}", value)))?;
+        let (arguments, rest) = rest
+            .split_once(')')
+            .ok_or_else(|| svg_error(format!("invalid transform {}", value)))?;
+        let arguments = parse_numbers(arguments)?;
+        let item = match (name.trim(), arguments.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => SvgTransform::new(a, b, c, d, e, f),
+            ("translate", &[x]) => SvgTransform::translation(x, 0.),
+            ("translate", &[x, y]) => SvgTransform::translation(x, y),
+            ("scale", &[scale]) => SvgTransform::scale(scale, scale),
+            ("scale", &[x, y]) => SvgTransform::scale(x, y),
+            ("rotate", &[angle]) => SvgTransform::rotation(Angle::degrees(angle)),
+            ("rotate", &[angle, x, y]) => SvgTransform::translation(-x, -y)
+                .then_rotate(Angle::degrees(angle))
+                .then_translate(Vector::new(x, y)),
+            ("skewX", &[angle]) => SvgTransform::new(1., 0., angle.to_radians().tan(), 1., 0., 0.),
+            ("skewY", &[angle]) => SvgTransform::new(1., angle.to_radians().tan(), 0., 1., 0., 0.),
+            _ => return Err(svg_error(format!("invalid transform {}", value))),
+        };
+        // Each transform in the list applies within the coordinates of the
+        // transforms following it
+        transform = item.then(&transform);
+        remaining = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+    Ok(transform)
+}
+
+fn parse_paint(value: &str, current_color: Color) -> KludgineResult<Option<Color>> {
+    if value == "none" {
+        Ok(None)
+    } else if let Some(reference) = value.strip_prefix("url(") {
+        let end = reference
+            .find(')')
+            .ok_or_else(|| svg_error(format!("invalid paint {}", value)))?;
+        let fallback = reference[end + 1..].trim();
+        if fallback.is_empty() {
+            Ok(None)
+        } else {
+            parse_paint(fallback, current_color)
+        }
+    } else {
+        parse_color(value, current_color).map(Some)
+    }
+}
+
+fn parse_color(value: &str, current_color: Color) -> KludgineResult<Color> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|digit| digit.to_digit(16).map(|digit| digit as f32))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| svg_error(format!("invalid color {}", value)))?;
+        return match *digits.as_slice() {
+            [r, g, b] => Ok(Color::new(r / 15., g / 15., b / 15., 1.)),
+            [r1, r2, g1, g2, b1, b2] => Ok(Color::new(
+                (r1 * 16. + r2) / 255.,
+                (g1 * 16. + g2) / 255.,
+                (b1 * 16. + b2) / 255.,
+                1.,
+            )),
+            _ => Err(svg_error(format!("invalid color {}", value))),
+        };
+    }
+
+    if let Some(arguments) = value
+        .strip_prefix("rgba(")
+        .or_else(|| value.strip_prefix("rgb("))
+        .and_then(|arguments| arguments.strip_suffix(')'))
+    {
+        let channels = arguments
+            .split(',')
+            .enumerate()
+            .map(|(index, channel)| {
+                let channel = channel.trim();
+                match channel.strip_suffix('%') {
+                    Some(percent) => parse_number(percent).map(|percent| percent / 100.),
+                    None if index == 3 => parse_number(channel),
+                    None => parse_number(channel).map(|channel| channel / 255.),
+                }
+            })
+            .collect::<KludgineResult<Vec<_>>>()?;
+        return match *channels.as_slice() {
+            [r, g, b] => Ok(Color::new(r, g, b, 1.)),
+            [r, g, b, a] => Ok(Color::new(r, g, b, a)),
+            _ => Err(svg_error(format!("invalid color {}", value))),
+        };
+    }
+
+    match value {
+        "currentColor" => Ok(current_color),
+        "transparent" => Ok(Color::CLEAR_BLACK),
+        name => Color::named(name).ok_or_else(|| svg_error(format!("unknown color {}", value))),
+    }
+}
+
+fn parse_number(value: &str) -> KludgineResult<f32> {
+    let mut tokens = Tokens::new(value);
+    let number = tokens.number()?;
+    if tokens.is_done() {
+        Ok(number)
+    } else {
+        Err(svg_error(format!("invalid number {}", value)))
+    }
+}
+
+fn parse_numbers(value: &str) -> KludgineResult<Vec<f32>> {
+    let mut tokens = Tokens::new(value);
+    let mut numbers = Vec::new();
+    while !tokens.is_done() {
+        numbers.push(tokens.number()?);
+    }
+    Ok(numbers)
+}
+
+/// Parses a length in user units. Pixels are the only supported unit.
+fn parse_length(value: &str) -> KludgineResult<f32> {
+    let mut tokens = Tokens::new(value);
+    let length = tokens.number()?;
+    match tokens.remaining() {
+        b"" | b"px" => Ok(length),
+        _ => Err(svg_error(format!("unsupported length {}", value))),
+    }
+}
+
+fn optional_length(node: Node<'_, '_>, name: &str) -> KludgineResult<Option<f32>> {
+    node.attribute(name).map(parse_length).transpose()
+}
+
+fn length_attribute(node: Node<'_, '_>, name: &str) -> KludgineResult<f32> {
+    Ok(optional_length(node, name)?.unwrap_or_default())
+}
+
+fn svg_error<S: ToString>(message: S) -> KludgineError {
+    KludgineError::SvgParseError(message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::{Raw, Scale},
+        shape::Paint,
+    };
+    use lyon_tessellation::path::PathEvent as LyonPathEvent;
+
+    fn endpoints(path: &Path<Unknown>) -> Vec<(f32, f32)> {
+        let path = path.clone() * Scale::<f32, Unknown, Raw>::new(1.);
+        path.as_lyon()
+            .iter()
+            .filter_map(|event| match event {
+                LyonPathEvent::Line { to, .. }
+                | LyonPathEvent::Quadratic { to, .. }
+                | LyonPathEvent::Cubic { to, .. } => Some((to.x, to.y)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn path_data_test() -> KludgineResult<()> {
+        let path = Path::<Unknown>::from_svg_data("M10 10h5v5H10z m1,1 l.5.5-1e1 0")?;
+        assert_eq!(
+            endpoints(&path),
+            vec![
+                (15., 10.),
+                (15., 15.),
+                (10., 15.),
+                (11.5, 11.5),
+                (1.5, 11.5)
+            ]
+        );
+
+        // Arc flags don't need to be separated from the following values
+        let arc = Path::<Unknown>::from_svg_data("M0 0a5 5 0 0010 0")?;
+        let (x, y) = *endpoints(&arc).last().unwrap();
+        assert!((x - 10.).abs() < 0.001 && y.abs() < 0.001);
+
+        assert!(Path::<Unknown>::from_svg_data("10 10").is_err());
+        assert!(Path::<Unknown>::from_svg_data("M0 0 Z 10 10").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn document_test() -> KludgineResult<()> {
+        let svg = Svg::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 20">
+                <g transform="translate(2 0)" fill="red">
+                    <rect width="2" height="2"/>
+                </g>
+                <circle cx="5" cy="5" r="1" style="fill: none; stroke: #00f; stroke-width: 2px"/>
+                <defs><rect width="10" height="10"/></defs>
+            </svg>"##,
+        )?;
+
+        // The view box is twice as tall as it is wide, so it's centered
+        // horizontally and scaled by 2
+        let shapes = svg.shapes_sized(Size::<f32, Raw>::new(40., 40.));
+        assert_eq!(shapes.len(), 2);
+        let bounds = Rect::from_points(shapes[0].polygons()?.iter().flatten());
+        assert_eq!(bounds, Rect::new(Point::new(14., 0.), Size::new(4., 4.)));
+        assert!(matches!(
+            shapes[0].fill.as_ref().map(|fill| &fill.paint),
+            Some(Paint::Color(color)) if *color == Color::RED
+        ));
+
+        let stroke = shapes[1].stroke.as_ref().unwrap();
+        assert!(shapes[1].fill.is_none());
+        assert_eq!(stroke.options.line_width, 4.);
+        assert!(matches!(stroke.paint, Paint::Color(color) if color == Color::BLUE));
+        Ok(())
+    }
+
+    #[test]
+    fn url_paint_test() -> KludgineResult<()> {
+        let svg = Svg::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+                <defs><linearGradient id="g"/></defs>
+                <rect width="2" height="2" fill="url(#g) red" stroke="url(#g)"/>
+                <rect width="2" height="2" fill="url(#g)" stroke="blue"/>
+            </svg>"##,
+        )?;
+
+        let shapes = svg.shapes_sized(Size::<f32, Raw>::new(10., 10.));
+        assert_eq!(shapes.len(), 2);
+        assert!(matches!(
+            shapes[0].fill.as_ref().map(|fill| &fill.paint),
+            Some(Paint::Color(color)) if *color == Color::RED
+        ));
+        assert!(shapes[0].stroke.is_none());
+        assert!(shapes[1].fill.is_none());
+        assert!(shapes[1].stroke.is_some());
+
+        assert!(Svg::parse(
+            r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="2" height="2" fill="url(#g"/></svg>"#
+        )
+        .is_err());
+        Ok(())
+    }
+}
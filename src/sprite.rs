@@ -1,11 +1,16 @@
 use crate::{
-    math::{Angle, Box2D, Point, Raw, Rect, Scale, Size},
+    color::Color,
+    math::{Angle, Box2D, Point, Raw, Rect, Scale, Size, Vector},
     texture::Texture,
     Handle, KludgineError, KludgineResult,
 };
+mod atlas;
 mod batch;
 mod collection;
 mod gpu_batch;
+mod metadata;
+mod nine_slice;
+mod packed;
 mod pipeline;
 mod sheet;
 pub(crate) use self::{
@@ -14,7 +19,18 @@ pub(crate) use self::{
     pipeline::Pipeline,
 };
 mod source;
-pub use self::{collection::*, sheet::*, source::*};
+mod state_machine;
+pub use self::{
+    atlas::*,
+    collection::*,
+    metadata::{SpriteLayer, SpriteSlice, SpriteSliceKey},
+    nine_slice::*,
+    sheet::*,
+    source::*,
+    state_machine::*,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, iter::IntoIterator, sync::Arc, time::Duration};
 
 #[macro_export]
@@ -37,17 +53,60 @@ pub enum AnimationMode {
     Forward,
     Reverse,
     PingPong,
+    /// Alternates directions like `PingPong`, starting in reverse
+    PingPongReverse,
 }
 
 impl AnimationMode {
     fn default_direction(&self) -> AnimationDirection {
         match self {
             AnimationMode::Forward | AnimationMode::PingPong => AnimationDirection::Forward,
-            AnimationMode::Reverse => AnimationDirection::Reverse,
+            AnimationMode::Reverse | AnimationMode::PingPongReverse => AnimationDirection::Reverse,
+        }
+    }
+
+    fn first_frame(&self, frame_count: usize) -> usize {
+        match self.default_direction() {
+            AnimationDirection::Forward => 0,
+            AnimationDirection::Reverse => frame_count.saturating_sub(1),
         }
     }
 }
 
+/// Controls how many times an animation plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationRepeat {
+    /// Repeats forever
+    #[default]
+    Loop,
+    /// Plays once, then returns to the first frame
+    Once,
+    /// Plays the given number of times, then holds the last frame shown
+    Times(u32),
+    /// Plays once, then holds the last frame shown
+    HoldLastFrame,
+}
+
+/// Something that happened while advancing a sprite's animation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpriteEvent {
+    /// A named event on a frame that was shown
+    Frame { frame: usize, name: String },
+    /// The animation reached its end and started another repetition
+    Looped,
+    /// The animation finished playing
+    Finished,
+}
+
+/// The result of advancing a sprite's animation
+#[derive(Debug, Clone)]
+pub struct SpriteUpdate {
+    /// The frame to display
+    pub source: SpriteSource,
+    /// The events crossed, in the order they happened
+    pub events: Vec<SpriteEvent>,
+}
+
 #[derive(Debug, Clone)]
 enum AnimationDirection {
     Forward,
@@ -67,6 +126,11 @@ pub(crate) struct SpriteData {
     current_frame: usize,
     current_animation_direction: AnimationDirection,
     animations: SpriteAnimations,
+    slices: Vec<SpriteSlice>,
+    layers: Vec<SpriteLayer>,
+    completed_loops: u32,
+    finished: bool,
+    current_frame_reported: bool,
 }
 
 impl From<SpriteAnimations> for Sprite {
@@ -85,6 +149,11 @@ impl Sprite {
                 current_tag: None,
                 elapsed_since_frame_change: Duration::from_millis(0),
                 current_animation_direction: AnimationDirection::Forward,
+                slices: Vec::new(),
+                layers: Vec::new(),
+                completed_loops: 0,
+                finished: false,
+                current_frame_reported: false,
             }),
         }
     }
@@ -119,6 +188,8 @@ impl Sprite {
                 vec![SpriteFrame {
                     source,
                     duration: None,
+                    pivot: None,
+                    events: Vec::new(),
                 }],
                 AnimationMode::Forward,
             ),
@@ -130,9 +201,13 @@ impl Sprite {
 
     /// Loads [Aseprite](https://www.aseprite.org/) JSON export format, when using the correct settings
     ///
-    /// For the JSON data, use the Hash export option (default), and use either spaces or underscores (_)
-    /// inbetween the fields in the name. Ensure `{frame}` is the last field in the name before the extension.
-    /// E.g., `{tag}_{frame}.{extension}`
+    /// For the JSON data, use either the Array or Hash export option. With the Hash option (default), use
+    /// either spaces or underscores (_) inbetween the fields in the name. Ensure `{frame}` is the last field
+    /// in the name before the extension. E.g., `{tag}_{frame}.{extension}`
+    ///
+    /// Tags are loaded from `meta.frameTags`, including their repeat counts.
+    /// Frame events are loaded from the user data of each layer's cels,
+    /// separated by commas.
     pub async fn load_aseprite_json(raw_json: &str, texture: Texture) -> KludgineResult<Self> {
         let json = json::parse(raw_json)?;
 
@@ -156,24 +231,41 @@ impl Sprite {
             None => None,
         };
 
-        let mut frames = HashMap::new();
-        for (name, frame) in json["frames"].entries() {
-            // Remove the extension, if present
-            let name = name.split('.').next().unwrap();
-            // Split by _ or ' 'as per the documentation of this method.
-            let name_parts = name.split(|c| c == '_' || c == ' ').collect::<Vec<_>>();
-            let frame_number = name_parts[name_parts.len() - 1]
-                .parse::<usize>()
-                .or_else(|_| {
-                    if json["frames"].len() == 1 {
-                        Ok(0)
-                    } else {
-                        Err(KludgineError::SpriteParseError(
-                            "invalid aseprite json: frame was not numeric.".to_owned(),
-                        ))
-                    }
-                })?;
+        // The array export lists frames in order, while the hash export names
+        // each frame.
+        let numbered_frames = if json["frames"].is_array() {
+            json["frames"].members().enumerate().collect::<Vec<_>>()
+        } else {
+            json["frames"]
+                .entries()
+                .map(|(name, frame)| {
+                    // Remove the extension, if present
+                    let name = name.split('.').next().unwrap();
+                    // Split by _ or ' 'as per the documentation of this method.
+                    let name_parts = name.split(|c| c == '_' || c == ' ').collect::<Vec<_>>();
+                    let frame_number =
+                        name_parts[name_parts.len() - 1]
+                            .parse::<usize>()
+                            .or_else(|_| {
+                                if json["frames"].len() == 1 {
+                                    Ok(0)
+                                } else {
+                                    Err(KludgineError::SpriteParseError(
+                                        "invalid aseprite json: frame was not numeric.".to_owned(),
+                                    ))
+                                }
+                            })?;
+                    Ok((frame_number, frame))
+                })
+                .collect::<KludgineResult<Vec<_>>>()?
+        };
+
+        let slices = metadata::parse_aseprite_slices(meta)?;
+        let layers = metadata::parse_aseprite_layers(meta)?;
+        let mut frame_events = metadata::parse_aseprite_frame_events(meta)?;
 
+        let mut frames = HashMap::new();
+        for (frame_number, frame) in numbered_frames {
             let duration = match frame["duration"].as_u64() {
                 Some(millis) => Duration::from_millis(millis),
                 None => {
@@ -183,6 +275,7 @@ impl Sprite {
                 }
             };
 
+            let json_frame = frame;
             let frame = Rect::new(
                 Point::new(
                     frame["frame"]["x"].as_u32().ok_or_else(|| {
@@ -210,13 +303,24 @@ impl Sprite {
                 ),
             );
 
-            let source = SpriteSource::new(frame, texture.clone());
+            let mut source = SpriteSource::new(frame, texture.clone());
+            if json_frame["trimmed"].as_bool().unwrap_or_default() {
+                if let Some(trim) = packed::json_trim(json_frame).ok_or_else(|| {
+                    KludgineError::SpriteParseError(
+                        "invalid aseprite json: trim information was not valid".to_owned(),
+                    )
+                })? {
+                    source = source.with_trim(trim);
+                }
+            }
 
             frames.insert(
                 frame_number,
                 SpriteFrame {
                     duration: Some(duration),
                     source,
+                    pivot: metadata::frame_pivot(&slices, frame_number),
+                    events: frame_events.remove(&frame_number).unwrap_or_default(),
                 },
             );
         }
@@ -229,6 +333,8 @@ impl Sprite {
                 AnimationMode::Reverse
             } else if tag["direction"] == "pingpong" {
                 AnimationMode::PingPong
+            } else if tag["direction"] == "pingpong_reverse" {
+                AnimationMode::PingPongReverse
             } else {
                 return Err(KludgineError::SpriteParseError(
                     "invalid aseprite json: frameTags direction is an unknown value".to_owned(),
@@ -250,6 +356,25 @@ impl Sprite {
                     "invalid aseprite json: frameTags from was not numeric".to_owned(),
                 )
             })?;
+            // Aseprite exports the repeat count as a string, omitting it when
+            // the tag loops forever
+            let repeat = if tag["repeat"].is_null() {
+                AnimationRepeat::Loop
+            } else {
+                let times = tag["repeat"]
+                    .as_u32()
+                    .or_else(|| tag["repeat"].as_str().and_then(|s| s.parse().ok()))
+                    .ok_or_else(|| {
+                        KludgineError::SpriteParseError(
+                            "invalid aseprite json: frameTags repeat was not numeric".to_owned(),
+                        )
+                    })?;
+                match times {
+                    0 => AnimationRepeat::Loop,
+                    times => AnimationRepeat::Times(times),
+                }
+            };
+
             let mut animation_frames = Vec::new();
             for i in start_frame..(end_frame + 1) {
                 let frame = frames.get(&i).ok_or_else(|| {
@@ -260,7 +385,10 @@ impl Sprite {
                 animation_frames.push(frame.clone());
             }
 
-            animations.insert(name, SpriteAnimation::new(animation_frames, direction));
+            animations.insert(
+                name,
+                SpriteAnimation::new(animation_frames, direction).with_repeat(repeat),
+            );
         }
 
         let mut frames: Vec<_> = frames.into_iter().collect();
@@ -274,52 +402,147 @@ impl Sprite {
             ),
         );
 
-        Ok(Sprite::new(title, SpriteAnimations::new(animations)))
+        let sprite = Sprite::new(title, SpriteAnimations::new(animations));
+        {
+            let mut data = sprite.handle.write().await;
+            data.slices = slices;
+            data.layers = layers;
+        }
+        Ok(sprite)
     }
 
     pub async fn set_current_tag<S: Into<String>>(&self, tag: Option<S>) -> KludgineResult<()> {
         let new_tag = tag.map(|t| t.into());
         let mut sprite = self.handle.write().await;
         if sprite.current_tag != new_tag {
-            sprite.current_animation_direction = {
-                let animations = sprite.animations.handle.read().await;
-                let animation = animations
-                    .get(&new_tag)
-                    .ok_or(KludgineError::InvalidSpriteTag)?;
-                animation.mode.default_direction()
-            };
-            sprite.current_frame = 0;
+            if !sprite.animations.handle.read().await.contains_key(&new_tag) {
+                return Err(KludgineError::InvalidSpriteTag);
+            }
             sprite.current_tag = new_tag;
+            sprite.restart().await?;
         }
 
         Ok(())
     }
 
+    /// Starts the current animation over from its first frame
+    pub async fn restart(&self) -> KludgineResult<()> {
+        let mut sprite = self.handle.write().await;
+        sprite.restart().await
+    }
+
+    /// Returns how far through its current cycle the animation is, from 0 to
+    /// 1, in the direction it is playing. Animations without frames are always
+    /// at 0.
+    pub async fn phase(&self) -> KludgineResult<f32> {
+        let sprite = self.handle.read().await;
+        let (frame_count, frame_duration) = sprite
+            .with_current_animation(|animation| {
+                (
+                    animation.frames.len(),
+                    animation
+                        .frames
+                        .get(sprite.current_frame)
+                        .and_then(|frame| frame.duration),
+                )
+            })
+            .await?;
+        if frame_count == 0 {
+            return Ok(0.);
+        }
+        let frame = match sprite.current_animation_direction {
+            AnimationDirection::Forward => sprite.current_frame,
+            AnimationDirection::Reverse => frame_count - 1 - sprite.current_frame,
+        };
+        let progress = match frame_duration {
+            Some(duration) if duration > Duration::default() => {
+                (sprite.elapsed_since_frame_change.as_secs_f32() / duration.as_secs_f32()).min(1.)
+            }
+            _ => 0.,
+        };
+        Ok((frame as f32 + progress) / frame_count as f32)
+    }
+
+    /// Moves the animation to `phase` through its current cycle, as returned
+    /// by `phase`. Useful for keeping related animations in step.
+    pub async fn set_phase(&self, phase: f32) -> KludgineResult<()> {
+        let mut sprite = self.handle.write().await;
+        let frame_count = sprite
+            .with_current_animation(|animation| animation.frames.len())
+            .await?;
+        if frame_count == 0 {
+            return Ok(());
+        }
+        let position = phase.clamp(0., 1.) * frame_count as f32;
+        let frame = (position as usize).min(frame_count - 1);
+        sprite.current_frame = match sprite.current_animation_direction {
+            AnimationDirection::Forward => frame,
+            AnimationDirection::Reverse => frame_count - 1 - frame,
+        };
+        let frame_duration = sprite.with_current_frame(|frame| frame.duration).await?;
+        sprite.elapsed_since_frame_change = frame_duration
+            .map(|duration| duration.mul_f32((position - frame as f32).min(1.)))
+            .unwrap_or_default();
+        Ok(())
+    }
+
+    /// Returns true once an animation that doesn't loop forever has finished
+    pub async fn is_finished(&self) -> bool {
+        let sprite = self.handle.read().await;
+        sprite.finished
+    }
+
     pub async fn current_tag(&self) -> Option<String> {
         let sprite = self.handle.read().await;
         sprite.current_tag.clone()
     }
 
     pub async fn get_frame(&self, elapsed: Option<Duration>) -> KludgineResult<SpriteSource> {
+        Ok(self.get_frame_with_events(elapsed).await?.source)
+    }
+
+    /// Advances the animation by `elapsed` like `get_frame`, also returning
+    /// the events crossed. The first call after the animation starts reports
+    /// the events of its first frame.
+    pub async fn get_frame_with_events(
+        &self,
+        elapsed: Option<Duration>,
+    ) -> KludgineResult<SpriteUpdate> {
         let mut sprite = self.handle.write().await;
+        let mut events = Vec::new();
+        if !sprite.current_frame_reported {
+            sprite.current_frame_reported = true;
+            sprite.report_frame_events(&mut events).await?;
+        }
+
         if let Some(elapsed) = elapsed {
-            sprite.elapsed_since_frame_change += elapsed;
-
-            let current_frame_duration = sprite.with_current_frame(|frame| frame.duration).await?;
-            if let Some(frame_duration) = current_frame_duration {
-                if sprite.elapsed_since_frame_change > frame_duration {
-                    sprite.elapsed_since_frame_change = Duration::from_nanos(
-                        (sprite.elapsed_since_frame_change.as_nanos() % frame_duration.as_nanos())
-                            as u64,
-                    );
-                    sprite.advance_frame().await?;
+            if !sprite.finished {
+                sprite.elapsed_since_frame_change += elapsed;
+
+                while let Some(frame_duration) =
+                    sprite.with_current_frame(|frame| frame.duration).await?
+                {
+                    if sprite.elapsed_since_frame_change <= frame_duration {
+                        break;
+                    }
+
+                    sprite.elapsed_since_frame_change -= frame_duration;
+                    sprite.advance_frame(&mut events).await?;
+                    if sprite.finished {
+                        sprite.elapsed_since_frame_change = Duration::default();
+                        break;
+                    } else if frame_duration == Duration::default() {
+                        // Avoid spinning forever on frames without a duration
+                        break;
+                    }
                 }
             }
         }
 
-        Ok(sprite
+        let source = sprite
             .with_current_frame(|frame| frame.source.clone())
-            .await?)
+            .await?;
+        Ok(SpriteUpdate { source, events })
     }
 
     pub async fn remaining_frame_duration(&self) -> KludgineResult<Option<Duration>> {
@@ -337,6 +560,31 @@ impl Sprite {
         Ok(duration)
     }
 
+    /// Returns the pivot of the current frame
+    pub async fn pivot(&self) -> KludgineResult<Option<Point<u32>>> {
+        let sprite = self.handle.read().await;
+        sprite.with_current_frame(|frame| frame.pivot).await
+    }
+
+    pub async fn slices(&self) -> Vec<SpriteSlice> {
+        let sprite = self.handle.read().await;
+        sprite.slices.clone()
+    }
+
+    pub async fn slice(&self, name: &str) -> Option<SpriteSlice> {
+        let sprite = self.handle.read().await;
+        sprite
+            .slices
+            .iter()
+            .find(|slice| slice.name == name)
+            .cloned()
+    }
+
+    pub async fn layers(&self) -> Vec<SpriteLayer> {
+        let sprite = self.handle.read().await;
+        sprite.layers.clone()
+    }
+
     pub async fn animations(&self) -> SpriteAnimations {
         let handle = self.handle.read().await;
         handle.animations.clone()
@@ -358,7 +606,7 @@ impl Sprite {
         let animations = handle.animations.handle.read().await;
         if let Some(animation) = animations.values().next() {
             if let Some(frame) = animation.frames.first() {
-                return Some(frame.source.location.size());
+                return Some(frame.source.size());
             }
         }
         None
@@ -366,11 +614,64 @@ impl Sprite {
 }
 
 impl SpriteData {
-    async fn advance_frame(&mut self) -> KludgineResult<()> {
-        self.current_frame = self.next_frame().await?;
+    async fn restart(&mut self) -> KludgineResult<()> {
+        let (mode, frame_count) = self
+            .with_current_animation(|animation| (animation.mode.clone(), animation.frames.len()))
+            .await?;
+        self.current_animation_direction = mode.default_direction();
+        self.current_frame = mode.first_frame(frame_count);
+        self.elapsed_since_frame_change = Duration::default();
+        self.completed_loops = 0;
+        self.finished = false;
+        self.current_frame_reported = false;
         Ok(())
     }
-    async fn next_frame(&mut self) -> KludgineResult<usize> {
+
+    async fn advance_frame(&mut self, events: &mut Vec<SpriteEvent>) -> KludgineResult<()> {
+        let (next_frame, looped) = self.next_frame().await?;
+        if looped {
+            self.completed_loops += 1;
+            let repeat = self
+                .with_current_animation(|animation| animation.repeat)
+                .await?;
+            let finished = match repeat {
+                AnimationRepeat::Loop => false,
+                AnimationRepeat::Once | AnimationRepeat::HoldLastFrame => true,
+                AnimationRepeat::Times(times) => self.completed_loops >= times,
+            };
+
+            if finished {
+                self.finished = true;
+                if repeat == AnimationRepeat::Once {
+                    self.current_frame = next_frame;
+                }
+                events.push(SpriteEvent::Finished);
+                return Ok(());
+            }
+
+            events.push(SpriteEvent::Looped);
+        }
+
+        self.current_frame = next_frame;
+        self.report_frame_events(events).await
+    }
+
+    async fn report_frame_events(&self, events: &mut Vec<SpriteEvent>) -> KludgineResult<()> {
+        let frame = self.current_frame;
+        let names = self
+            .with_current_frame(|frame| frame.events.clone())
+            .await?;
+        events.extend(
+            names
+                .into_iter()
+                .map(|name| SpriteEvent::Frame { frame, name }),
+        );
+        Ok(())
+    }
+
+    /// Returns the next frame, and whether moving to it starts a new
+    /// repetition of the animation
+    async fn next_frame(&mut self) -> KludgineResult<(usize, bool)> {
         let starting_frame = self.current_frame as i32;
         let frames = self.animations.handle.read().await;
         let animation = frames
@@ -382,32 +683,51 @@ impl SpriteData {
             AnimationDirection::Reverse => starting_frame - 1,
         };
 
+        let last_frame = animation.frames.len() - 1;
         Ok(if next_frame < 0 {
             match animation.mode {
                 AnimationMode::Forward => unreachable!(),
                 AnimationMode::Reverse => {
                     // Cycle back to the last frame
-                    animation.frames.len() - 1
+                    (last_frame, true)
                 }
-                AnimationMode::PingPong => {
+                AnimationMode::PingPong | AnimationMode::PingPongReverse => {
                     self.current_animation_direction = AnimationDirection::Forward;
-                    1
+                    (
+                        1.min(last_frame),
+                        matches!(animation.mode, AnimationMode::PingPong),
+                    )
                 }
             }
-        } else if next_frame as usize >= animation.frames.len() {
+        } else if next_frame as usize > last_frame {
             match animation.mode {
                 AnimationMode::Reverse => unreachable!(),
-                AnimationMode::Forward => 0,
-                AnimationMode::PingPong => {
+                AnimationMode::Forward => (0, true),
+                AnimationMode::PingPong | AnimationMode::PingPongReverse => {
                     self.current_animation_direction = AnimationDirection::Reverse;
-                    (animation.frames.len() - 2).max(0)
+                    (
+                        last_frame.saturating_sub(1),
+                        matches!(animation.mode, AnimationMode::PingPongReverse),
+                    )
                 }
             }
         } else {
-            next_frame as usize
+            (next_frame as usize, false)
         })
     }
 
+    async fn with_current_animation<F, R>(&self, f: F) -> KludgineResult<R>
+    where
+        F: Fn(&SpriteAnimation) -> R,
+    {
+        let animations = self.animations.handle.read().await;
+        let animation = animations
+            .get(&self.current_tag)
+            .ok_or(KludgineError::InvalidSpriteTag)?;
+
+        Ok(f(animation))
+    }
+
     async fn with_current_frame<F, R>(&self, f: F) -> KludgineResult<R>
     where
         F: Fn(&SpriteFrame) -> R,
@@ -433,6 +753,12 @@ impl SpriteAnimations {
         }
     }
 
+    /// Returns true if both refer to the same animations, such as the
+    /// animations of two instances of a sprite
+    pub(crate) fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Handle::ptr_eq(&this.handle, &other.handle)
+    }
+
     pub async fn frames_for(&self, tag: &Option<impl ToString>) -> Option<SpriteAnimation> {
         let handle = self.handle.read().await;
         handle.get(&tag.as_ref().map(|s| s.to_string())).cloned()
@@ -443,11 +769,21 @@ impl SpriteAnimations {
 pub struct SpriteAnimation {
     pub frames: Vec<SpriteFrame>,
     pub mode: AnimationMode,
+    pub repeat: AnimationRepeat,
 }
 
 impl SpriteAnimation {
     pub fn new(frames: Vec<SpriteFrame>, mode: AnimationMode) -> Self {
-        Self { frames, mode }
+        Self {
+            frames,
+            mode,
+            repeat: AnimationRepeat::default(),
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: AnimationRepeat) -> Self {
+        self.repeat = repeat;
+        self
     }
 }
 
@@ -455,6 +791,10 @@ impl SpriteAnimation {
 pub struct SpriteFrame {
     pub source: SpriteSource,
     pub duration: Option<Duration>,
+    /// The frame's hotspot, relative to the top-left of the frame
+    pub pivot: Option<Point<u32>>,
+    /// Named events reported when the frame is shown
+    pub events: Vec<String>,
 }
 
 pub struct SpriteFrameBuilder {
@@ -462,6 +802,8 @@ pub struct SpriteFrameBuilder {
     tag: Option<String>,
     tag_frame: Option<usize>,
     duration: Option<Duration>,
+    pivot: Option<Point<u32>>,
+    events: Vec<String>,
 }
 
 impl SpriteFrameBuilder {
@@ -471,6 +813,8 @@ impl SpriteFrameBuilder {
             tag: None,
             tag_frame: None,
             duration: None,
+            pivot: None,
+            events: Vec::new(),
         }
     }
 
@@ -489,10 +833,22 @@ impl SpriteFrameBuilder {
         self
     }
 
+    pub fn with_pivot(mut self, pivot: Point<u32>) -> Self {
+        self.pivot = Some(pivot);
+        self
+    }
+
+    pub fn with_event<S: Into<String>>(mut self, name: S) -> Self {
+        self.events.push(name.into());
+        self
+    }
+
     pub fn build(self) -> SpriteFrame {
         SpriteFrame {
             source: self.source,
             duration: self.duration,
+            pivot: self.pivot,
+            events: self.events,
         }
     }
 }
@@ -506,14 +862,32 @@ impl RenderedSprite {
     pub fn new(
         render_at: Box2D<f32, Raw>,
         rotation: SpriteRotation<Raw>,
-        alpha: f32,
+        tint: SpriteTint,
         source: SpriteSource,
     ) -> Self {
         Self {
             data: Arc::new(RenderedSpriteData {
                 render_at,
                 rotation,
-                alpha,
+                transform: None,
+                tint,
+                source,
+            }),
+        }
+    }
+
+    pub fn transformed(
+        render_at: Box2D<f32, Raw>,
+        transform: euclid::Transform2D<f32, Raw, Raw>,
+        tint: SpriteTint,
+        source: SpriteSource,
+    ) -> Self {
+        Self {
+            data: Arc::new(RenderedSpriteData {
+                render_at,
+                rotation: SpriteRotation::default(),
+                transform: Some(transform),
+                tint,
                 source,
             }),
         }
@@ -524,10 +898,55 @@ impl RenderedSprite {
 pub(crate) struct RenderedSpriteData {
     pub render_at: Box2D<f32, Raw>,
     pub rotation: SpriteRotation<Raw>,
-    pub alpha: f32,
+    /// Applied to each vertex after `rotation`
+    pub transform: Option<euclid::Transform2D<f32, Raw, Raw>>,
+    pub tint: SpriteTint,
     pub source: SpriteSource,
 }
 
+/// Colors applied to a sprite as it is drawn, such as for team colors, fades
+/// or damage flashes
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpriteTint {
+    /// Multiplied with each pixel of the sprite, including its alpha
+    pub color: Color,
+    /// Added to each pixel of the sprite, scaled by the flash's alpha
+    pub flash: Color,
+}
+
+impl Default for SpriteTint {
+    fn default() -> Self {
+        Self {
+            color: Color::WHITE,
+            flash: Color::CLEAR_BLACK,
+        }
+    }
+}
+
+impl SpriteTint {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+
+    pub fn flash(flash: Color) -> Self {
+        Self::default().with_flash(flash)
+    }
+
+    pub fn with_flash(mut self, flash: Color) -> Self {
+        self.flash = flash;
+        self
+    }
+
+    /// Multiplies the tint's alpha by `alpha`
+    pub fn with_alpha(mut self, alpha: f32) -> Self {
+        self.color = self.color.with_alpha(self.color.alpha() * alpha);
+        self
+    }
+}
+
 #[derive(Copy, Clone, Debug, Default)]
 pub struct SpriteRotation<Unit> {
     pub angle: Option<Angle>,
@@ -551,6 +970,108 @@ impl<Unit> SpriteRotation<Unit> {
     }
 }
 
+/// A 2D transformation applied to a sprite as it is drawn. Flips, scaling,
+/// skewing and rotation are applied in that order around `pivot`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpriteTransform {
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub scale: Vector<f32>,
+    /// The angles to skew along the x and y axes
+    pub skew: (Angle, Angle),
+    pub rotation: Angle,
+    /// The point to transform around, in pixels relative to the top-left of
+    /// the sprite. If not specified, the center of the sprite is used.
+    pub pivot: Option<Point<f32>>,
+}
+
+impl Default for SpriteTransform {
+    fn default() -> Self {
+        Self {
+            flip_x: false,
+            flip_y: false,
+            scale: Vector::new(1., 1.),
+            skew: (Angle::zero(), Angle::zero()),
+            rotation: Angle::zero(),
+            pivot: None,
+        }
+    }
+}
+
+impl SpriteTransform {
+    pub fn flipped_x(mut self) -> Self {
+        self.flip_x = !self.flip_x;
+        self
+    }
+
+    pub fn flipped_y(mut self) -> Self {
+        self.flip_y = !self.flip_y;
+        self
+    }
+
+    pub fn with_scale(mut self, x: f32, y: f32) -> Self {
+        self.scale = Vector::new(x, y);
+        self
+    }
+
+    pub fn with_skew(mut self, x: Angle, y: Angle) -> Self {
+        self.skew = (x, y);
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Angle) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_pivot(mut self, pivot: Point<f32>) -> Self {
+        self.pivot = Some(pivot);
+        self
+    }
+
+    /// Uses a frame's hotspot as the pivot, if it has one
+    pub fn with_frame_pivot(mut self, frame: &SpriteFrame) -> Self {
+        if let Some(pivot) = frame.pivot {
+            self.pivot = Some(pivot.to_f32());
+        }
+        self
+    }
+
+    /// Returns the transformation of points on screen for a sprite of
+    /// `sprite_size` pixels drawn within `bounds`
+    pub fn to_matrix<Unit>(
+        &self,
+        bounds: Box2D<f32, Unit>,
+        sprite_size: Size<f32>,
+    ) -> euclid::Transform2D<f32, Unit, Unit> {
+        let scale = Vector::<f32, Unit>::new(
+            bounds.width() / sprite_size.width,
+            bounds.height() / sprite_size.height,
+        );
+        let pivot = match self.pivot {
+            Some(pivot) => bounds.min + Vector::new(pivot.x * scale.x, pivot.y * scale.y),
+            None => bounds.center(),
+        };
+
+        let flip_x = if self.flip_x { -1. } else { 1. };
+        let flip_y = if self.flip_y { -1. } else { 1. };
+        let skew = euclid::Transform2D::<f32, Unit, Unit>::new(
+            1.,
+            self.skew.1.radians.tan(),
+            self.skew.0.radians.tan(),
+            1.,
+            0.,
+            0.,
+        );
+        euclid::Transform2D::translation(-pivot.x, -pivot.y)
+            .then_scale(self.scale.x * flip_x, self.scale.y * flip_y)
+            .then(&skew)
+            .then_rotate(self.rotation)
+            .then_translate(pivot.to_vector())
+    }
+}
+
 impl<A, B> std::ops::Mul<Scale<f32, A, B>> for SpriteRotation<A> {
     type Output = SpriteRotation<B>;
 
@@ -561,3 +1082,185 @@ impl<A, B> std::ops::Mul<Scale<f32, A, B>> for SpriteRotation<A> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbaImage};
+
+    #[async_test]
+    async fn aseprite_array_test() -> KludgineResult<()> {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(8, 4)));
+        let sprite = Sprite::load_aseprite_json(
+            r#"{
+                "frames": [
+                    { "filename": "hero 0.ase", "frame": { "x": 0, "y": 0, "w": 4, "h": 4 }, "duration": 100 },
+                    { "filename": "hero 1.ase", "frame": { "x": 4, "y": 0, "w": 4, "h": 4 }, "duration": 200 }
+                ],
+                "meta": {
+                    "image": "hero.png",
+                    "size": { "w": 8, "h": 4 },
+                    "frameTags": [
+                        { "name": "blink", "from": 1, "to": 1, "direction": "reverse", "repeat": "3" },
+                        { "name": "sway", "from": 0, "to": 1, "direction": "pingpong_reverse" }
+                    ],
+                    "layers": [
+                        { "name": "Layer 1", "opacity": 255, "blendMode": "normal", "cels": [
+                            { "frame": 1, "data": "footstep, dust" }
+                        ] }
+                    ],
+                    "slices": [
+                        { "name": "hotspot", "keys": [
+                            { "frame": 0, "bounds": { "x": 1, "y": 1, "w": 2, "h": 2 }, "pivot": { "x": 1, "y": 2 } },
+                            { "frame": 1, "bounds": { "x": 0, "y": 1, "w": 2, "h": 2 }, "pivot": { "x": 1, "y": 2 } }
+                        ] }
+                    ]
+                }
+            }"#,
+            texture,
+        )
+        .await?;
+
+        let animations = sprite.animations().await;
+        let all = animations
+            .frames_for(&Option::<String>::None)
+            .await
+            .unwrap();
+        assert_eq!(all.frames.len(), 2);
+        assert_eq!(all.frames[1].duration, Some(Duration::from_millis(200)));
+        let blink = animations.frames_for(&Some("blink")).await.unwrap();
+        assert!(matches!(blink.mode, AnimationMode::Reverse));
+        assert_eq!(blink.repeat, AnimationRepeat::Times(3));
+        assert_eq!(blink.frames[0].events, vec!["footstep", "dust"]);
+        assert_eq!(
+            blink.frames[0].source.location.bounds(),
+            Rect::new(Point::new(4, 0), Size::new(4, 4))
+        );
+        let sway = animations.frames_for(&Some("sway")).await.unwrap();
+        assert!(matches!(sway.mode, AnimationMode::PingPongReverse));
+        assert_eq!(sway.repeat, AnimationRepeat::Loop);
+
+        // Pivots come from the slice key for each frame
+        assert_eq!(sprite.pivot().await?, Some(Point::new(2, 3)));
+        assert_eq!(all.frames[1].pivot, Some(Point::new(1, 3)));
+        assert_eq!(sprite.slice("hotspot").await.unwrap().keys.len(), 2);
+        assert_eq!(sprite.layers().await[0].name, "Layer 1");
+        Ok(())
+    }
+
+    #[async_test]
+    async fn empty_animation_phase_test() -> KludgineResult<()> {
+        let sprite = Sprite::from(SpriteAnimations::new(crate::hash_map!(
+            None => SpriteAnimation::new(Vec::new(), AnimationMode::Reverse),
+        )));
+        assert_eq!(sprite.phase().await?, 0.);
+        sprite.set_phase(0.5).await?;
+        assert_eq!(sprite.phase().await?, 0.);
+        Ok(())
+    }
+
+    fn frame_names(events: &[SpriteEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                SpriteEvent::Frame { name, .. } => name.clone(),
+                SpriteEvent::Looped => "looped".to_owned(),
+                SpriteEvent::Finished => "finished".to_owned(),
+            })
+            .collect()
+    }
+
+    #[async_test]
+    async fn animation_events_test() -> KludgineResult<()> {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(3, 1)));
+        let frames = (0..3)
+            .map(|x| {
+                let frame = SpriteFrameBuilder::new(SpriteSource::new(
+                    Rect::new(Point::new(x, 0), Size::new(1, 1)),
+                    texture.clone(),
+                ))
+                .with_duration(Duration::from_millis(10));
+                match x {
+                    0 => frame.with_event("start"),
+                    2 => frame.with_event("hit"),
+                    _ => frame,
+                }
+                .build()
+            })
+            .collect::<Vec<_>>();
+        let sprite = Sprite::from(SpriteAnimations::new(crate::hash_map!(
+            None => SpriteAnimation::new(frames.clone(), AnimationMode::Forward)
+                .with_repeat(AnimationRepeat::Times(2)),
+            Some("once".to_owned()) => SpriteAnimation::new(frames, AnimationMode::Reverse)
+                .with_repeat(AnimationRepeat::Once),
+        )));
+
+        // The first frame's events are reported before any time passes
+        let update = sprite.get_frame_with_events(None).await?;
+        assert_eq!(frame_names(&update.events), vec!["start"]);
+
+        // Crossing multiple frames in one update reports every event
+        let update = sprite
+            .get_frame_with_events(Some(Duration::from_millis(35)))
+            .await?;
+        assert_eq!(frame_names(&update.events), vec!["hit", "looped", "start"]);
+        assert_eq!(update.source.location.bounds().origin, Point::new(0, 0));
+
+        // The second repetition holds the last frame
+        let update = sprite
+            .get_frame_with_events(Some(Duration::from_millis(100)))
+            .await?;
+        assert_eq!(frame_names(&update.events), vec!["hit", "finished"]);
+        assert_eq!(update.source.location.bounds().origin, Point::new(2, 0));
+        assert!(sprite.is_finished().await);
+        assert!(sprite
+            .get_frame_with_events(Some(Duration::from_millis(100)))
+            .await?
+            .events
+            .is_empty());
+
+        // Reverse animations start on their last frame, and playing once
+        // returns to it
+        sprite.set_current_tag(Some("once")).await?;
+        assert!(!sprite.is_finished().await);
+        let update = sprite.get_frame_with_events(None).await?;
+        assert_eq!(frame_names(&update.events), vec!["hit"]);
+        let update = sprite
+            .get_frame_with_events(Some(Duration::from_millis(100)))
+            .await?;
+        assert_eq!(frame_names(&update.events), vec!["start", "finished"]);
+        assert_eq!(update.source.location.bounds().origin, Point::new(2, 0));
+
+        sprite.restart().await?;
+        assert!(!sprite.is_finished().await);
+        Ok(())
+    }
+
+    #[test]
+    fn transform_matrix_test() {
+        // A 4x4 sprite drawn at twice its size
+        let bounds = Box2D::<f32, Raw>::new(Point::new(10., 10.), Point::new(18., 18.));
+        let size = Size::new(4., 4.);
+        let corner = Point::new(10., 10.);
+
+        let flipped = SpriteTransform::default()
+            .flipped_y()
+            .to_matrix(bounds, size);
+        assert_eq!(flipped.transform_point(corner), Point::new(10., 18.));
+
+        // The pivot is in sprite pixels, so (1, 1) is at (12, 12) on screen
+        let rotated = SpriteTransform::default()
+            .with_rotation(Angle::degrees(90.))
+            .with_pivot(Point::new(1., 1.))
+            .to_matrix(bounds, size);
+        let rotated_corner = rotated.transform_point(Point::new(14., 12.));
+        assert!((rotated_corner - Point::new(12., 14.)).length() < 0.001);
+
+        let skewed = SpriteTransform::default()
+            .with_skew(Angle::degrees(45.), Angle::zero())
+            .with_pivot(Point::new(0., 0.))
+            .to_matrix(bounds, size);
+        let skewed_corner = skewed.transform_point(Point::new(10., 18.));
+        assert!((skewed_corner - Point::new(18., 18.)).length() < 0.001);
+    }
+}
@@ -0,0 +1,616 @@
+use crate::{
+    math::{Point, Rect, Size},
+    sprite::{
+        Sprite, SpriteAnimation, SpriteAnimations, SpriteFrame, SpriteSource, SpriteSourceLocation,
+        SpriteSourceSublocation,
+    },
+    texture::Texture,
+    Handle, KludgineError, KludgineResult,
+};
+use image::{DynamicImage, RgbaImage};
+use std::collections::HashMap;
+
+/// Packs images from many textures into a few shared textures, allowing
+/// sprites from different images to be drawn in the same batch.
+///
+/// Adding images returns [`SpriteSource`]s that point into the atlas. Each
+/// page keeps the same texture id as images are added and the page grows, so
+/// sources returned earlier are still drawn in the same batch as newer ones.
+/// Repacking the atlas replaces its pages. Use [`TextureAtlas::remap`] to
+/// retrieve the current location of sources returned before repacking.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    data: Handle<AtlasData>,
+}
+
+type SourceKey = (u64, Rect<u32>);
+
+#[derive(Debug)]
+struct AtlasData {
+    initial_page_size: Size<u32>,
+    max_page_size: Size<u32>,
+    padding: u32,
+    pages: Vec<AtlasPage>,
+    /// The location of each packed image, keyed by the texture and location
+    /// it was added from
+    entries: HashMap<SourceKey, AtlasEntry>,
+    /// Maps every location the atlas has returned to the key it was added as
+    aliases: HashMap<SourceKey, SourceKey>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AtlasEntry {
+    page: usize,
+    location: Rect<u32>,
+}
+
+#[derive(Debug)]
+struct AtlasPage {
+    image: RgbaImage,
+    packer: ShelfPacker,
+    texture: Texture,
+    dirty: bool,
+}
+
+impl TextureAtlas {
+    /// Creates an atlas whose pages start at `initial_page_size` and grow up
+    /// to 4096x4096 pixels
+    pub fn new(initial_page_size: Size<u32>) -> Self {
+        Self::with_max_page_size(initial_page_size, Size::new(4096, 4096))
+    }
+
+    pub fn with_max_page_size(initial_page_size: Size<u32>, max_page_size: Size<u32>) -> Self {
+        Self {
+            data: Handle::new(AtlasData {
+                initial_page_size: initial_page_size.min(max_page_size),
+                max_page_size,
+                padding: 1,
+                pages: Vec::new(),
+                entries: HashMap::new(),
+                aliases: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Sets the number of transparent pixels between packed images, which
+    /// prevents neighboring images from bleeding into each other when
+    /// sprites are scaled. Defaults to 1. Only affects images added
+    /// afterwards, or all images after [`TextureAtlas::repack`].
+    pub async fn set_padding(&self, padding: u32) {
+        let mut data = self.data.write().await;
+        data.padding = padding;
+    }
+
+    /// Returns the texture of each page
+    pub async fn pages(&self) -> Vec<Texture> {
+        let data = self.data.read().await;
+        data.pages.iter().map(|page| page.texture.clone()).collect()
+    }
+
+    pub async fn add_texture(&self, texture: &Texture) -> KludgineResult<SpriteSource> {
+        self.add_source(&SpriteSource::new(
+            Rect::new(Point::default(), texture.size()),
+            texture.clone(),
+        ))
+        .await
+    }
+
+    /// Copies the area of `source` into the atlas, returning its new location.
+    /// Sources that have already been added aren't copied again.
+    pub async fn add_source(&self, source: &SpriteSource) -> KludgineResult<SpriteSource> {
+        let mut sources = self.add_sources(std::slice::from_ref(source)).await?;
+        Ok(sources.remove(0))
+    }
+
+    /// Adds several sources at once, which avoids recreating the atlas's
+    /// textures after each one.
+    pub async fn add_sources(&self, sources: &[SpriteSource]) -> KludgineResult<Vec<SpriteSource>> {
+        let mut data = self.data.write().await;
+        for source in sources {
+            for (key, image) in source_areas(source) {
+                if !data.entries.contains_key(&key) && !data.aliases.contains_key(&key) {
+                    data.insert(key, &image)?;
+                }
+            }
+        }
+        data.update_textures();
+        Ok(sources.iter().map(|source| data.remap(source)).collect())
+    }
+
+    /// Returns a copy of `sprite` whose frames are drawn from the atlas
+    pub async fn add_sprite(&self, sprite: &Sprite) -> KludgineResult<Sprite> {
+        let (title, animations, slices, layers) = {
+            let data = sprite.handle.read().await;
+            let animations = data.animations.handle.read().await;
+            (
+                data.title.clone(),
+                animations.clone(),
+                data.slices.clone(),
+                data.layers.clone(),
+            )
+        };
+        let sources = animations
+            .values()
+            .flat_map(|animation| animation.frames.iter().map(|frame| frame.source.clone()))
+            .collect::<Vec<_>>();
+        let mut sources = self.add_sources(&sources).await?.into_iter();
+
+        let animations = animations
+            .into_iter()
+            .map(|(tag, animation)| {
+                let frames = animation
+                    .frames
+                    .into_iter()
+                    .map(|frame| SpriteFrame {
+                        source: sources.next().unwrap(),
+                        ..frame
+                    })
+                    .collect();
+                (
+                    tag,
+                    SpriteAnimation::new(frames, animation.mode).with_repeat(animation.repeat),
+                )
+            })
+            .collect();
+        let sprite = Sprite::new(title, SpriteAnimations::new(animations));
+        {
+            let mut data = sprite.handle.write().await;
+            data.slices = slices;
+            data.layers = layers;
+        }
+        Ok(sprite)
+    }
+
+    /// Returns the current location of `source` within the atlas. `source` can
+    /// be either the source the image was added from, or a location returned
+    /// by the atlas before the atlas changed. Sources that aren't in the atlas
+    /// are returned unchanged.
+    pub async fn remap(&self, source: &SpriteSource) -> SpriteSource {
+        let data = self.data.read().await;
+        data.remap(source)
+    }
+
+    pub async fn contains(&self, source: &SpriteSource) -> bool {
+        let data = self.data.read().await;
+        source_keys(source)
+            .iter()
+            .all(|key| data.entries.contains_key(key) || data.aliases.contains_key(key))
+    }
+
+    /// Packs every image into new pages, largest first. Repacking after many
+    /// images have been added incrementally usually reduces the number of
+    /// pages.
+    pub async fn repack(&self) -> KludgineResult<()> {
+        let mut data = self.data.write().await;
+        let mut images = data
+            .entries
+            .iter()
+            .map(|(key, entry)| {
+                let page = &data.pages[entry.page].image;
+                (*key, copy_area(page, entry.location))
+            })
+            .collect::<Vec<_>>();
+        images.sort_by(|(_, a), (_, b)| {
+            b.height()
+                .cmp(&a.height())
+                .then_with(|| b.width().cmp(&a.width()))
+        });
+
+        data.pages.clear();
+        data.entries.clear();
+        for (key, image) in images {
+            data.insert(key, &image)?;
+        }
+        data.update_textures();
+        Ok(())
+    }
+}
+
+impl AtlasData {
+    fn insert(&mut self, key: SourceKey, image: &RgbaImage) -> KludgineResult<()> {
+        let padded = Size::new(image.width() + self.padding, image.height() + self.padding);
+        if padded.width > self.max_page_size.width || padded.height > self.max_page_size.height {
+            return Err(KludgineError::ImageTooLargeForAtlas);
+        }
+
+        let (page, origin) = match self.allocate(padded) {
+            Some(allocation) => allocation,
+            None => {
+                self.pages.push(AtlasPage::new(self.initial_page_size));
+                let page = self.pages.len() - 1;
+                (page, self.allocate_in(page, padded).unwrap())
+            }
+        };
+
+        let page_data = &mut self.pages[page];
+        for y in 0..image.height() {
+            for x in 0..image.width() {
+                page_data
+                    .image
+                    .put_pixel(origin.x + x, origin.y + y, *image.get_pixel(x, y));
+            }
+        }
+        page_data.dirty = true;
+        self.entries.insert(
+            key,
+            AtlasEntry {
+                page,
+                location: Rect::new(origin, Size::new(image.width(), image.height())),
+            },
+        );
+        Ok(())
+    }
+
+    /// Finds space in an existing page, growing pages if needed
+    fn allocate(&mut self, size: Size<u32>) -> Option<(usize, Point<u32>)> {
+        (0..self.pages.len())
+            .find_map(|page| self.pages[page].packer.allocate(size).map(|o| (page, o)))
+            .or_else(|| {
+                (0..self.pages.len())
+                    .find_map(|page| self.allocate_in(page, size).map(|o| (page, o)))
+            })
+    }
+
+    /// Allocates space in `page`, growing it until it fits or reaches the
+    /// maximum page size
+    fn allocate_in(&mut self, page: usize, size: Size<u32>) -> Option<Point<u32>> {
+        let page = &mut self.pages[page];
+        loop {
+            if let Some(origin) = page.packer.allocate(size) {
+                return Some(origin);
+            }
+
+            let current = page.packer.size;
+            let grown =
+                if current.width <= current.height && current.width < self.max_page_size.width {
+                    Size::new(current.width * 2, current.height)
+                } else {
+                    Size::new(current.width, current.height * 2)
+                }
+                .min(self.max_page_size);
+            if grown == current {
+                return None;
+            }
+            page.grow(grown);
+        }
+    }
+
+    fn update_textures(&mut self) {
+        for page in &mut self.pages {
+            if page.dirty {
+                page.texture = page.texture.with_image(page.image.clone());
+                page.dirty = false;
+            }
+        }
+
+        for (key, entry) in &self.entries {
+            let texture_id = self.pages[entry.page].texture.id;
+            self.aliases.insert((texture_id, entry.location), *key);
+        }
+    }
+
+    fn remap(&self, source: &SpriteSource) -> SpriteSource {
+        let lookup = |rect: Rect<u32>| {
+            let key = (source.texture.id, rect);
+            let key = self.aliases.get(&key).unwrap_or(&key);
+            self.entries.get(key).copied()
+        };
+
+        let mut remapped = match &source.location {
+            SpriteSourceLocation::Rect(rect) => match lookup(*rect) {
+                Some(entry) => {
+                    SpriteSource::new(entry.location, self.pages[entry.page].texture.clone())
+                }
+                None => source.clone(),
+            },
+            SpriteSourceLocation::Rotated(rect) => match lookup(*rect) {
+                Some(entry) => SpriteSource {
+                    location: SpriteSourceLocation::Rotated(entry.location),
+                    texture: self.pages[entry.page].texture.clone(),
+                    trim: None,
+                },
+                None => source.clone(),
+            },
+            SpriteSourceLocation::Joined(locations) => {
+                let entries = locations
+                    .iter()
+                    .map(|location| lookup(location.source))
+                    .collect::<Option<Vec<_>>>();
+                match entries {
+                    // Joined sources must come from a single texture
+                    Some(entries) if entries.iter().all(|entry| entry.page == entries[0].page) => {
+                        SpriteSource::joined(
+                            locations
+                                .iter()
+                                .zip(entries.iter())
+                                .map(|(location, entry)| SpriteSourceSublocation {
+                                    source: entry.location,
+                                    destination: location.destination,
+                                }),
+                            self.pages[entries[0].page].texture.clone(),
+                        )
+                    }
+                    _ => source.clone(),
+                }
+            }
+        };
+        remapped.trim = source.trim;
+        remapped
+    }
+}
+
+impl AtlasPage {
+    fn new(size: Size<u32>) -> Self {
+        let image = RgbaImage::new(size.width, size.height);
+        Self {
+            texture: Texture::new(DynamicImage::ImageRgba8(image.clone())),
+            image,
+            packer: ShelfPacker::new(size),
+            dirty: false,
+        }
+    }
+
+    fn grow(&mut self, size: Size<u32>) {
+        let mut image = RgbaImage::new(size.width, size.height);
+        for (x, y, pixel) in self.image.enumerate_pixels() {
+            image.put_pixel(x, y, *pixel);
+        }
+        self.image = image;
+        self.packer.size = size;
+        self.dirty = true;
+    }
+}
+
+fn source_keys(source: &SpriteSource) -> Vec<SourceKey> {
+    match &source.location {
+        SpriteSourceLocation::Rect(rect) | SpriteSourceLocation::Rotated(rect) => {
+            vec![(source.texture.id, *rect)]
+        }
+        SpriteSourceLocation::Joined(locations) => locations
+            .iter()
+            .map(|location| (source.texture.id, location.source))
+            .collect(),
+    }
+}
+
+fn source_areas(source: &SpriteSource) -> Vec<(SourceKey, RgbaImage)> {
+    source_keys(source)
+        .into_iter()
+        .map(|key| (key, copy_area(&source.texture.image, key.1)))
+        .collect()
+}
+
+fn copy_area(image: &RgbaImage, area: Rect<u32>) -> RgbaImage {
+    RgbaImage::from_fn(area.size.width, area.size.height, |x, y| {
+        *image.get_pixel(area.origin.x + x, area.origin.y + y)
+    })
+}
+
+/// Packs rectangles into rows ("shelves"), each as tall as the tallest
+/// rectangle placed on it
+#[derive(Debug)]
+struct ShelfPacker {
+    size: Size<u32>,
+    shelves: Vec<Shelf>,
+}
+
+#[derive(Debug)]
+struct Shelf {
+    y: u32,
+    height: u32,
+    used_width: u32,
+}
+
+impl ShelfPacker {
+    fn new(size: Size<u32>) -> Self {
+        Self {
+            size,
+            shelves: Vec::new(),
+        }
+    }
+
+    fn allocate(&mut self, size: Size<u32>) -> Option<Point<u32>> {
+        let width = self.size.width;
+        // Use the shelf that wastes the least height
+        let best = self
+            .shelves
+            .iter_mut()
+            .filter(|shelf| shelf.height >= size.height && width - shelf.used_width >= size.width)
+            .min_by_key(|shelf| shelf.height - size.height);
+        if let Some(shelf) = best {
+            let origin = Point::new(shelf.used_width, shelf.y);
+            shelf.used_width += size.width;
+            return Some(origin);
+        }
+
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height)
+            .unwrap_or_default();
+        if y + size.height > self.size.height || size.width > width {
+            return None;
+        }
+
+        self.shelves.push(Shelf {
+            y,
+            height: size.height,
+            used_width: size.width,
+        });
+        Some(Point::new(0, y))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::ScreenScale,
+        scene::{Scene, Target},
+        sprite::{AnimationMode, SpriteRotation},
+        style::theme::Minimal,
+        window::SoftwareRenderer,
+    };
+    use image::Rgba;
+
+    fn solid_texture(size: Size<u32>, color: [u8; 4]) -> Texture {
+        Texture::new(DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            size.width,
+            size.height,
+            Rgba(color),
+        )))
+    }
+
+    fn pixel(source: &SpriteSource, x: u32, y: u32) -> Rgba<u8> {
+        let origin = source.location.bounds().origin;
+        *source.texture.image.get_pixel(origin.x + x, origin.y + y)
+    }
+
+    #[async_test]
+    async fn packing_test() -> KludgineResult<()> {
+        let atlas = TextureAtlas::with_max_page_size(Size::new(8, 8), Size::new(16, 16));
+        let red = solid_texture(Size::new(7, 7), [255, 0, 0, 255]);
+        let green = solid_texture(Size::new(3, 3), [0, 255, 0, 255]);
+
+        let red_source = atlas.add_texture(&red).await?;
+        assert_eq!(
+            red_source.location.bounds(),
+            Rect::new(Point::new(0, 0), Size::new(7, 7))
+        );
+        assert_eq!(atlas.pages().await.len(), 1);
+
+        // The page grows to fit the next image, keeping its texture id
+        let green_source = atlas.add_texture(&green).await?;
+        assert_eq!(atlas.pages().await.len(), 1);
+        assert_eq!(green_source.texture.image.dimensions(), (16, 8));
+        assert_eq!(red_source.texture.id, green_source.texture.id);
+        let aliases = atlas.data.read().await.aliases.len();
+        assert_eq!(pixel(&green_source, 2, 2), Rgba([0, 255, 0, 255]));
+
+        // Both the original and the stale source remap to the current page
+        let remapped = atlas.remap(&red_source).await;
+        assert_eq!(remapped.texture.id, green_source.texture.id);
+        assert_eq!(pixel(&remapped, 6, 6), Rgba([255, 0, 0, 255]));
+        let remapped = atlas
+            .remap(&SpriteSource::entire_texture(red.clone()).await)
+            .await;
+        assert_eq!(remapped.texture.id, green_source.texture.id);
+
+        // Adding an image again reuses its existing location
+        let again = atlas.add_texture(&green).await?;
+        assert_eq!(again.location.bounds(), green_source.location.bounds());
+        assert_eq!(atlas.data.read().await.aliases.len(), aliases);
+
+        // Images that don't fit in a full-sized page start a new page
+        let blue = solid_texture(Size::new(15, 15), [0, 0, 255, 255]);
+        let blue_source = atlas.add_texture(&blue).await?;
+        assert_eq!(atlas.pages().await.len(), 2);
+        assert_eq!(pixel(&blue_source, 14, 14), Rgba([0, 0, 255, 255]));
+
+        let huge = solid_texture(Size::new(16, 16), [0, 0, 0, 255]);
+        assert!(matches!(
+            atlas.add_texture(&huge).await,
+            Err(KludgineError::ImageTooLargeForAtlas)
+        ));
+
+        atlas.repack().await?;
+        assert_eq!(atlas.pages().await.len(), 2);
+        for (source, color) in &[
+            (&red_source, [255, 0, 0, 255]),
+            (&green_source, [0, 255, 0, 255]),
+            (&blue_source, [0, 0, 255, 255]),
+        ] {
+            let remapped = atlas.remap(source).await;
+            assert_eq!(pixel(&remapped, 0, 0), Rgba(*color));
+        }
+        Ok(())
+    }
+
+    #[async_test]
+    async fn sprite_test() -> KludgineResult<()> {
+        let atlas = TextureAtlas::new(Size::new(64, 64));
+        let first = solid_texture(Size::new(4, 4), [255, 0, 0, 255]);
+        let second = solid_texture(Size::new(4, 4), [0, 255, 0, 255]);
+        let mut animations = HashMap::new();
+        animations.insert(
+            None,
+            SpriteAnimation::new(
+                vec![
+                    SpriteFrame {
+                        source: SpriteSource::entire_texture(first).await,
+                        duration: None,
+                        pivot: None,
+                        events: Vec::new(),
+                    },
+                    SpriteFrame {
+                        source: SpriteSource::entire_texture(second).await,
+                        duration: None,
+                        pivot: None,
+                        events: Vec::new(),
+                    },
+                ],
+                AnimationMode::Forward,
+            ),
+        );
+        let sprite = Sprite::new(None, SpriteAnimations::new(animations));
+
+        let atlas_sprite = atlas.add_sprite(&sprite).await?;
+        let animation = atlas_sprite
+            .handle
+            .read()
+            .await
+            .animations
+            .frames_for(&Option::<String>::None)
+            .await
+            .unwrap();
+        let page = &atlas.pages().await[0];
+        assert!(animation
+            .frames
+            .iter()
+            .all(|frame| frame.source.texture.id == page.id));
+        assert_eq!(
+            pixel(&animation.frames[1].source, 0, 0),
+            Rgba([0, 255, 0, 255])
+        );
+        Ok(())
+    }
+
+    #[async_test]
+    async fn grown_page_render_test() -> KludgineResult<()> {
+        let atlas = TextureAtlas::with_max_page_size(Size::new(2, 2), Size::new(8, 8));
+        let red = atlas
+            .add_texture(&solid_texture(Size::new(1, 1), [255, 0, 0, 255]))
+            .await?;
+        let green = atlas
+            .add_texture(&solid_texture(Size::new(3, 3), [0, 255, 0, 255]))
+            .await?;
+        assert_eq!(red.texture.image.dimensions(), (2, 2));
+
+        // The source from before the page grew is drawn in the same batch as
+        // the new one, using the page's current contents
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(4., 1.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+        for (x, source) in [&red, &green, &red].iter().enumerate() {
+            source
+                .render_within(
+                    &target,
+                    Rect::new(Point::new(x as f32, 0.), Size::new(1., 1.)),
+                    SpriteRotation::default(),
+                )
+                .await;
+        }
+        let mut renderer = SoftwareRenderer::default();
+        let rendered = renderer.render(&target).await?;
+        assert_eq!(rendered.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(rendered.get_pixel(1, 0), &Rgba([0, 255, 0, 255]));
+        assert_eq!(rendered.get_pixel(2, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(rendered.get_pixel(3, 0), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+}
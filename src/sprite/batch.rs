@@ -1,5 +1,6 @@
 use crate::{
     math::{Raw, Rect, Size},
+    shape::Shape,
     sprite::RenderedSprite,
 };
 
@@ -9,6 +10,9 @@ pub(crate) struct Batch {
     pub clipping_rect: Option<Rect<u32, Raw>>,
     pub loaded_texture_id: u64,
     pub sprites: Vec<RenderedSprite>,
+    /// Shapes painted with a pattern using this batch's texture, drawn after
+    /// `sprites`
+    pub shapes: Vec<Shape<Raw>>,
 }
 
 impl Batch {
@@ -22,6 +26,7 @@ impl Batch {
             size,
             clipping_rect,
             sprites: Vec::new(),
+            shapes: Vec::new(),
         }
     }
 }
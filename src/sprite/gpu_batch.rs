@@ -1,9 +1,11 @@
 use crate::{
     math::{Box2D, Point, PointExt, Raw, Rect, Size, Unknown},
-    sprite::{pipeline::Vertex, RenderedSprite, SpriteRotation, SpriteSourceLocation},
+    shape::Shape,
+    sprite::{pipeline::Vertex, RenderedSprite, SpriteRotation, SpriteSourceLocation, SpriteTint},
+    KludgineError, KludgineResult,
 };
 use easygpu::prelude::*;
-use euclid::{Vector2D, Vector3D};
+use euclid::{Transform2D, Vector2D, Vector3D};
 
 pub(crate) struct GpuBatch {
     pub size: Size<u32, ScreenSpace>,
@@ -25,19 +27,14 @@ impl GpuBatch {
 
     pub fn add_sprite(&mut self, sprite: RenderedSprite) {
         let sprite = sprite.data;
-        let white_transparent = Rgba8 {
-            r: 255,
-            g: 255,
-            b: 255,
-            a: 0,
-        };
 
         match &sprite.source.location {
             SpriteSourceLocation::Rect(location) => self.add_box(
                 location.to_box2d(),
                 sprite.render_at,
                 sprite.rotation,
-                white_transparent,
+                sprite.transform.as_ref(),
+                sprite.tint,
             ),
             SpriteSourceLocation::Joined(locations) => {
                 let source_bounds = sprite.source.location.bounds();
@@ -53,49 +50,127 @@ impl GpuBatch {
                         location.source.to_box2d(),
                         destination.to_box2d(),
                         sprite.rotation,
-                        white_transparent,
+                        sprite.transform.as_ref(),
+                        sprite.tint,
                     );
                 }
             }
+            SpriteSourceLocation::Rotated(location) => self.add_rotated_box(
+                location.to_box2d(),
+                sprite.render_at,
+                sprite.rotation,
+                sprite.transform.as_ref(),
+                sprite.tint,
+            ),
         }
     }
 
-    pub fn vertex(&self, src: Point<f32, Unknown>, dest: Point<f32, Raw>, color: Rgba8) -> Vertex {
+    pub fn vertex(
+        &self,
+        src: Point<f32, Unknown>,
+        dest: Point<f32, Raw>,
+        tint: SpriteTint,
+    ) -> Vertex {
         Vertex {
             position: Vector3D::new(dest.x, dest.y, 0.),
             uv: Vector2D::new(
                 src.x / self.size.width as f32,
                 src.y / self.size.height as f32,
             ),
-            color,
+            color: tint.color.into(),
+            flash: tint.flash.into(),
         }
     }
 
     pub fn add_box(
         &mut self,
         src: Box2D<u32, Unknown>,
+        dest: Box2D<f32, Raw>,
+        rotation: SpriteRotation<Raw>,
+        transform: Option<&Transform2D<f32, Raw, Raw>>,
+        tint: SpriteTint,
+    ) {
+        let src = src.to_f32();
+        self.add_oriented_box(src, |point| point, dest, rotation, transform, tint);
+    }
+
+    /// Adds a box whose source area contains the sprite rotated 90 degrees
+    /// clockwise
+    pub fn add_rotated_box(
+        &mut self,
+        src: Box2D<u32, Unknown>,
+        dest: Box2D<f32, Raw>,
+        rotation: SpriteRotation<Raw>,
+        transform: Option<&Transform2D<f32, Raw, Raw>>,
+        tint: SpriteTint,
+    ) {
+        let src = src.to_f32();
+        // Clip in the sprite's unrotated coordinates, then map each corner back
+        // into the rotated area of the texture.
+        let unrotated = Box2D::new(Point::zero(), Point::new(src.height(), src.width()));
+        self.add_oriented_box(
+            unrotated,
+            |point| Point::new(src.max.x - point.y, src.min.y + point.x),
+            dest,
+            rotation,
+            transform,
+            tint,
+        );
+    }
+
+    fn add_oriented_box<F: Fn(Point<f32, Unknown>) -> Point<f32, Unknown>>(
+        &mut self,
+        mut src: Box2D<f32, Unknown>,
+        texture_location: F,
         mut dest: Box2D<f32, Raw>,
         rotation: SpriteRotation<Raw>,
-        color: Rgba8,
+        transform: Option<&Transform2D<f32, Raw, Raw>>,
+        tint: SpriteTint,
     ) {
-        let mut src = src.to_f32();
+        let origin = rotation.screen_location.unwrap_or_else(|| dest.center());
         if let Some(clip) = &self.clip {
+            // The rotation and transform are applied to the corners of `dest`
+            // after clipping, so the clip is tested against where the corners
+            // end up.
+            let mut quad_transform = Transform2D::identity();
+            if let Some(angle) = rotation.angle {
+                quad_transform = Transform2D::translation(-origin.x, -origin.y)
+                    .then_rotate(angle)
+                    .then_translate(origin.to_vector());
+            }
+            if let Some(transform) = transform {
+                quad_transform = quad_transform.then(transform);
+            }
+            let bounds = quad_transform.outer_transformed_box(&dest);
+
             // Convert to i32 because the destination could have negative coordinates.
             let clip_signed = clip.to_i32();
-            let dest_rounded = dest.round().to_i32();
+            let bounds_rounded = bounds.round().to_i32();
 
-            if !(clip_signed.min.x as i32 <= dest_rounded.min.x
-                && clip_signed.min.y as i32 <= dest_rounded.min.y
-                && clip_signed.max.x as i32 >= dest_rounded.max.x
-                && clip_signed.max.y as i32 >= dest_rounded.max.y)
+            if !(clip_signed.min.x as i32 <= bounds_rounded.min.x
+                && clip_signed.min.y as i32 <= bounds_rounded.min.y
+                && clip_signed.max.x as i32 >= bounds_rounded.max.x
+                && clip_signed.max.y as i32 >= bounds_rounded.max.y)
             {
-                if let Some(clipped_destination) = dest.intersection(&clip.to_f32()) {
-                    if rotation.angle.is_some() {
-                        // To properly apply clipping on a rotated quad requires tessellating the remaining polygon, and
-                        // the easygpu-lyon layer doesn't support uv coordinate extrapolation at this moment. We could use
-                        // lyon directly to generate these vertexes.
-                        eprintln!("Kludgine Error: Need to implement partial occlusion for sprites. Not clipping.");
-                    } else {
+                if bounds.intersection(&clip.to_f32()).is_none() {
+                    // Full clipping, just skip the drawing entirely
+                    return;
+                }
+
+                let axis_aligned = quad_transform.m12.abs() < f32::EPSILON
+                    && quad_transform.m21.abs() < f32::EPSILON;
+                // Transforms that only flip, scale and translate keep the box
+                // axis-aligned, so the clip can be mapped back onto `dest`
+                let local_clip = quad_transform
+                    .inverse()
+                    .filter(|_| axis_aligned)
+                    .map(|inverse| inverse.outer_transformed_box(&clip.to_f32()));
+                match local_clip {
+                    Some(local_clip) => {
+                        let clipped_destination = match dest.intersection(&local_clip) {
+                            Some(clipped_destination) => clipped_destination,
+                            None => return,
+                        };
                         // Adjust the src box based on how much was clipped
                         let source_size = src.size();
                         let dest_size = dest.size();
@@ -113,38 +188,44 @@ impl GpuBatch {
                         );
                         dest = clipped_destination;
                     }
-                } else {
-                    // Full clipping, just skip the drawing entirely
-                    return;
+                    None => {
+                        // To properly apply clipping on a rotated quad requires tessellating the remaining polygon, and
+                        // the easygpu-lyon layer doesn't support uv coordinate extrapolation at this moment. We could use
+                        // lyon directly to generate these vertexes.
+                        eprintln!("Kludgine Error: Need to implement partial occlusion for sprites. Not clipping.");
+                    }
                 }
             }
         }
 
-        let origin = rotation.screen_location.unwrap_or_else(|| dest.center());
         let top_left = self
-            .vertex(src.min, dest.min, color)
-            .rotate_by(rotation.angle, origin);
+            .vertex(texture_location(src.min), dest.min, tint)
+            .rotate_by(rotation.angle, origin)
+            .transform_by(transform);
         let top_right = self
             .vertex(
-                Point::from_lengths(src.max.x(), src.min.y()),
+                texture_location(Point::from_lengths(src.max.x(), src.min.y())),
                 Point::from_lengths(dest.max.x(), dest.min.y()),
-                color,
+                tint,
             )
-            .rotate_by(rotation.angle, origin);
+            .rotate_by(rotation.angle, origin)
+            .transform_by(transform);
         let bottom_left = self
             .vertex(
-                Point::from_lengths(src.min.x(), src.max.y()),
+                texture_location(Point::from_lengths(src.min.x(), src.max.y())),
                 Point::from_lengths(dest.min.x(), dest.max.y()),
-                color,
+                tint,
             )
-            .rotate_by(rotation.angle, origin);
+            .rotate_by(rotation.angle, origin)
+            .transform_by(transform);
         let bottom_right = self
             .vertex(
-                Point::from_lengths(src.max.x(), src.max.y()),
+                texture_location(Point::from_lengths(src.max.x(), src.max.y())),
                 Point::from_lengths(dest.max.x(), dest.max.y()),
-                color,
+                tint,
             )
-            .rotate_by(rotation.angle, origin);
+            .rotate_by(rotation.angle, origin)
+            .transform_by(transform);
 
         self.add_quad(top_left, top_right, bottom_left, bottom_right);
     }
@@ -168,14 +249,45 @@ impl GpuBatch {
         self.indicies.push(bl_index);
     }
 
-    // pub fn add_triangle(&mut self, a: Vertex, b: Vertex, c: Vertex) {
-    //     self.indicies.push(self.indicies.len() as u16);
-    //     self.items.push(a);
-    //     self.indicies.push(self.indicies.len() as u16);
-    //     self.items.push(b);
-    //     self.indicies.push(self.indicies.len() as u16);
-    //     self.items.push(c);
-    // }
+    pub fn triangles(&self) -> impl Iterator<Item = [Vertex; 3]> + '_ {
+        self.indicies.chunks_exact(3).map(move |triangle| {
+            [
+                self.items[triangle[0] as usize],
+                self.items[triangle[1] as usize],
+                self.items[triangle[2] as usize],
+            ]
+        })
+    }
+
+    /// Adds a shape painted with a pattern whose texture is this batch's
+    /// texture. Returns an error if the tiled shape needs more vertices than a
+    /// batch can index.
+    pub fn add_pattern_shape(&mut self, shape: &Shape<Raw>) -> KludgineResult<()> {
+        let pattern = match shape.pattern() {
+            Some(pattern) => pattern,
+            None => return Ok(()),
+        };
+
+        for polygon in pattern.tile(shape.polygons()?) {
+            if self.items.len() + polygon.len() >= u16::MAX as usize {
+                return Err(KludgineError::TessellationError(
+                    lyon_tessellation::TessellationError::TooManyVertices,
+                ));
+            }
+
+            let first_vertex = self.items.len() as u16;
+            for &(location, texture_location) in &polygon {
+                let vertex = self.vertex(texture_location, location, SpriteTint::default());
+                self.items.push(vertex);
+            }
+            for index in 1..polygon.len().saturating_sub(1) as u16 {
+                self.indicies.push(first_vertex);
+                self.indicies.push(first_vertex + index);
+                self.indicies.push(first_vertex + index + 1);
+            }
+        }
+        Ok(())
+    }
 
     pub fn finish(&self, renderer: &Renderer) -> BatchBuffers {
         let vertices = renderer.device.create_buffer(&self.items);
@@ -202,3 +314,87 @@ impl Draw for BatchBuffers {
         pass.draw_indexed(0..self.index_count as u32, 0, 0..1);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        shape::{Fill, Pattern},
+        sprite::SpriteSource,
+        texture::Texture,
+    };
+    use image::{DynamicImage, RgbaImage};
+
+    fn add_transformed(
+        batch: &mut GpuBatch,
+        dest: Box2D<f32, Raw>,
+        transform: Transform2D<f32, Raw, Raw>,
+    ) {
+        batch.add_box(
+            Box2D::new(Point::new(0, 0), Point::new(4, 4)),
+            dest,
+            SpriteRotation::default(),
+            Some(&transform),
+            SpriteTint::default(),
+        );
+    }
+
+    #[test]
+    fn transformed_clip_test() {
+        let clip = Box2D::new(Point::new(0, 0), Point::new(2, 4));
+        let dest = Box2D::new(Point::new(0., 0.), Point::new(4., 4.));
+
+        // Flipping horizontally shows the right half of the texture on the left
+        let mut batch = GpuBatch::new(Size::new(4, 4), Some(clip));
+        add_transformed(
+            &mut batch,
+            dest,
+            Transform2D::scale(-1., 1.).then_translate(Vector2D::new(4., 0.)),
+        );
+        let vertices = batch.triangles().flatten().collect::<Vec<_>>();
+        assert_eq!(vertices.len(), 6);
+        for vertex in vertices {
+            assert!(vertex.position.x <= 2.);
+            assert!((vertex.uv.x - (1. - vertex.position.x / 4.)).abs() < f32::EPSILON);
+        }
+
+        // Clipping applies to where the sprite is drawn
+        let mut batch = GpuBatch::new(Size::new(4, 4), Some(clip));
+        add_transformed(
+            &mut batch,
+            dest.translate(Vector2D::new(10., 0.)),
+            Transform2D::translation(-10., 0.),
+        );
+        add_transformed(&mut batch, dest, Transform2D::translation(10., 0.));
+        let vertices = batch.triangles().flatten().collect::<Vec<_>>();
+        assert_eq!(vertices.len(), 6);
+        assert!(vertices.iter().all(|vertex| vertex.position.x <= 2.));
+    }
+
+    #[async_test]
+    async fn pattern_vertex_limit_test() -> KludgineResult<()> {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(2, 2)));
+        let pattern = Pattern::new(SpriteSource::entire_texture(texture).await);
+        let shape = |size: f32| {
+            Shape::rect(Rect::<f32, Raw>::new(
+                Point::default(),
+                Size::new(size, size),
+            ))
+            .fill(Fill::new(pattern.clone()))
+        };
+
+        let mut batch = GpuBatch::new(Size::new(2, 2), None);
+        batch.add_pattern_shape(&shape(8.))?;
+        assert!(!batch.items.is_empty());
+
+        // The vertices for 22,500 tiles can't be indexed with u16s
+        let mut batch = GpuBatch::new(Size::new(2, 2), None);
+        assert!(matches!(
+            batch.add_pattern_shape(&shape(300.)),
+            Err(KludgineError::TessellationError(
+                lyon_tessellation::TessellationError::TooManyVertices
+            ))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    math::{Length, Point, Rect, Surround},
+    sprite::{
+        packed::{json_point, json_rect},
+        SpriteSource, SpriteSourceLocation, SpriteTrim,
+    },
+    KludgineError, KludgineResult,
+};
+use json::JsonValue;
+use std::collections::HashMap;
+
+/// A named area of a sprite, such as an Aseprite slice. A slice can change
+/// between frames, so it is made up of keys that each apply from their frame
+/// until the next key.
+#[derive(Debug, Clone)]
+pub struct SpriteSlice {
+    pub name: String,
+    /// The user data attached to the slice
+    pub data: Option<String>,
+    /// The slice's keys, ordered by frame
+    pub keys: Vec<SpriteSliceKey>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpriteSliceKey {
+    /// The first frame this key applies to
+    pub frame: usize,
+    /// The area of the frame covered by the slice
+    pub bounds: Rect<u32>,
+    /// The stretchable center of a nine-slice, relative to `bounds`
+    pub center: Option<Rect<u32>>,
+    /// The slice's pivot point, relative to `bounds`
+    pub pivot: Option<Point<u32>>,
+}
+
+impl SpriteSlice {
+    /// Returns the key that applies to `frame`
+    pub fn key(&self, frame: usize) -> Option<&SpriteSliceKey> {
+        self.keys.iter().rev().find(|key| key.frame <= frame)
+    }
+}
+
+impl SpriteSliceKey {
+    /// Returns the sizes of the fixed borders around `center`, or None if the
+    /// slice isn't a nine-slice
+    pub fn nine_slice_insets(&self) -> Option<Surround<u32>> {
+        self.center.map(|center| Surround {
+            left: Length::new(center.min_x()),
+            top: Length::new(center.min_y()),
+            right: Length::new(self.bounds.width().saturating_sub(center.max_x())),
+            bottom: Length::new(self.bounds.height().saturating_sub(center.max_y())),
+        })
+    }
+
+    /// Returns the slice's pivot relative to the frame
+    pub fn frame_pivot(&self) -> Option<Point<u32>> {
+        self.pivot
+            .map(|pivot| self.bounds.origin + pivot.to_vector())
+    }
+
+    /// Returns the area of `frame` covered by the slice. If `frame` was
+    /// trimmed, the returned source is trimmed to the pixels that were kept.
+    /// Returns None if `frame` isn't a single rectangle or the slice only
+    /// covers trimmed pixels.
+    pub fn source(&self, frame: &SpriteSource) -> Option<SpriteSource> {
+        let rect = match &frame.location {
+            SpriteSourceLocation::Rect(rect) => rect,
+            _ => return None,
+        };
+        let trim = match &frame.trim {
+            Some(trim) => trim,
+            None => {
+                return Some(SpriteSource::new(
+                    Rect::new(
+                        rect.origin + self.bounds.origin.to_vector(),
+                        self.bounds.size,
+                    ),
+                    frame.texture.clone(),
+                ))
+            }
+        };
+
+        // The slice's bounds are relative to the untrimmed frame
+        let kept = Rect::new(Point::origin() + trim.offset, rect.size);
+        let visible = kept.intersection(&self.bounds)?;
+        let source = SpriteSource::new(
+            Rect::new(rect.origin + (visible.origin - kept.origin), visible.size),
+            frame.texture.clone(),
+        );
+        if visible == self.bounds {
+            Some(source)
+        } else {
+            Some(source.with_trim(SpriteTrim {
+                offset: visible.origin - self.bounds.origin,
+                original_size: self.bounds.size,
+            }))
+        }
+    }
+}
+
+/// A layer of a sprite, as listed in an Aseprite export
+#[derive(Debug, Clone)]
+pub struct SpriteLayer {
+    pub name: String,
+    /// The name of the group containing the layer
+    pub group: Option<String>,
+    pub opacity: f32,
+    /// The blend mode name, such as `normal` or `multiply`
+    pub blend_mode: Option<String>,
+    /// The user data attached to the layer
+    pub data: Option<String>,
+}
+
+pub(crate) fn parse_aseprite_slices(meta: &JsonValue) -> KludgineResult<Vec<SpriteSlice>> {
+    meta["slices"]
+        .members()
+        .map(|slice| {
+            let name = slice["name"]
+                .as_str()
+                .ok_or_else(|| aseprite_error("slice name was not valid"))?;
+            let mut keys = slice["keys"]
+                .members()
+                .map(|key| {
+                    Ok(SpriteSliceKey {
+                        frame: key["frame"]
+                            .as_usize()
+                            .ok_or_else(|| aseprite_error("slice key frame was not numeric"))?,
+                        bounds: json_rect(&key["bounds"])
+                            .ok_or_else(|| aseprite_error("slice bounds were not valid"))?,
+                        center: optional(&key["center"], json_rect)
+                            .map_err(|_| aseprite_error("slice center was not valid"))?,
+                        pivot: optional(&key["pivot"], json_point)
+                            .map_err(|_| aseprite_error("slice pivot was not valid"))?,
+                    })
+                })
+                .collect::<KludgineResult<Vec<_>>>()?;
+            keys.sort_by_key(|key| key.frame);
+
+            Ok(SpriteSlice {
+                name: name.to_owned(),
+                data: slice["data"].as_str().map(str::to_owned),
+                keys,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn parse_aseprite_layers(meta: &JsonValue) -> KludgineResult<Vec<SpriteLayer>> {
+    meta["layers"]
+        .members()
+        .map(|layer| {
+            let name = layer["name"]
+                .as_str()
+                .ok_or_else(|| aseprite_error("layer name was not valid"))?;
+            Ok(SpriteLayer {
+                name: name.to_owned(),
+                group: layer["group"].as_str().map(str::to_owned),
+                // Aseprite stores opacity from 0 to 255
+                opacity: layer["opacity"]
+                    .as_f32()
+                    .map_or(1., |opacity| opacity / 255.),
+                blend_mode: layer["blendMode"].as_str().map(str::to_owned),
+                data: layer["data"].as_str().map(str::to_owned),
+            })
+        })
+        .collect()
+}
+
+/// Collects the events of each frame from the user data of the layers' cels.
+/// Each cel's user data can list multiple events separated by commas.
+pub(crate) fn parse_aseprite_frame_events(
+    meta: &JsonValue,
+) -> KludgineResult<HashMap<usize, Vec<String>>> {
+    let mut events = HashMap::<usize, Vec<String>>::new();
+    for cel in meta["layers"]
+        .members()
+        .flat_map(|layer| layer["cels"].members())
+    {
+        let frame = cel["frame"]
+            .as_usize()
+            .ok_or_else(|| aseprite_error("cel frame was not numeric"))?;
+        if let Some(data) = cel["data"].as_str() {
+            events.entry(frame).or_default().extend(
+                data.split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_owned),
+            );
+        }
+    }
+    Ok(events)
+}
+
+/// Returns the pivot of `frame` from the first slice that has a pivot
+pub(crate) fn frame_pivot(slices: &[SpriteSlice], frame: usize) -> Option<Point<u32>> {
+    slices
+        .iter()
+        .find_map(|slice| slice.key(frame).and_then(SpriteSliceKey::frame_pivot))
+}
+
+fn optional<T, F: Fn(&JsonValue) -> Option<T>>(
+    value: &JsonValue,
+    parse: F,
+) -> Result<Option<T>, ()> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        parse(value).map(Some).ok_or(())
+    }
+}
+
+fn aseprite_error(message: &str) -> KludgineError {
+    KludgineError::SpriteParseError(format!("invalid aseprite json: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::{Size, Vector},
+        texture::Texture,
+    };
+    use image::{DynamicImage, RgbaImage};
+
+    #[test]
+    fn slices_test() -> KludgineResult<()> {
+        let meta = json::parse(
+            r##"{
+                "slices": [
+                    { "name": "panel", "color": "#0000ffff", "data": "ui", "keys": [
+                        { "frame": 0, "bounds": { "x": 0, "y": 0, "w": 16, "h": 12 }, "center": { "x": 4, "y": 3, "w": 8, "h": 5 } }
+                    ] },
+                    { "name": "feet", "color": "#0000ffff", "keys": [
+                        { "frame": 2, "bounds": { "x": 5, "y": 6, "w": 4, "h": 4 }, "pivot": { "x": 2, "y": 4 } },
+                        { "frame": 0, "bounds": { "x": 4, "y": 6, "w": 4, "h": 4 }, "pivot": { "x": 2, "y": 4 } }
+                    ] }
+                ],
+                "layers": [
+                    { "name": "Body", "opacity": 255, "blendMode": "normal" },
+                    { "name": "Shadow", "group": "Effects", "opacity": 51, "blendMode": "multiply", "data": "shadow" }
+                ]
+            }"##,
+        )?;
+
+        let slices = parse_aseprite_slices(&meta)?;
+        assert_eq!(slices.len(), 2);
+        let panel = slices[0].key(3).unwrap();
+        assert_eq!(slices[0].data.as_deref(), Some("ui"));
+        let insets = panel.nine_slice_insets().unwrap();
+        assert_eq!(
+            (
+                insets.left.get(),
+                insets.top.get(),
+                insets.right.get(),
+                insets.bottom.get()
+            ),
+            (4, 3, 4, 4)
+        );
+
+        // Keys are sorted, and each applies until the next key
+        assert_eq!(slices[1].key(1).unwrap().bounds.origin, Point::new(4, 6));
+        assert_eq!(slices[1].key(2).unwrap().bounds.origin, Point::new(5, 6));
+        assert_eq!(frame_pivot(&slices, 0), Some(Point::new(6, 10)));
+        assert_eq!(frame_pivot(&slices, 5), Some(Point::new(7, 10)));
+
+        let frame = SpriteSource::new(
+            Rect::new(Point::new(16, 0), Size::new(16, 12)),
+            Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(32, 12))),
+        );
+        let source = slices[1].key(0).unwrap().source(&frame).unwrap();
+        assert_eq!(
+            source.location.bounds(),
+            Rect::new(Point::new(20, 6), Size::new(4, 4))
+        );
+
+        // A frame trimmed to (2, 1)-(14, 11) is packed at (16, 0)
+        let trimmed = SpriteSource::new(
+            Rect::new(Point::new(16, 0), Size::new(12, 10)),
+            frame.texture.clone(),
+        )
+        .with_trim(SpriteTrim {
+            offset: Vector::new(2, 1),
+            original_size: Size::new(16, 12),
+        });
+        let source = slices[1].key(0).unwrap().source(&trimmed).unwrap();
+        assert_eq!(
+            source.location.bounds(),
+            Rect::new(Point::new(18, 5), Size::new(4, 4))
+        );
+        assert!(source.trim.is_none());
+        // The panel covers the trimmed borders, which stay transparent
+        let source = panel.source(&trimmed).unwrap();
+        assert_eq!(
+            source.location.bounds(),
+            Rect::new(Point::new(16, 0), Size::new(12, 10))
+        );
+        let trim = source.trim.unwrap();
+        assert_eq!(trim.offset, Vector::new(2, 1));
+        assert_eq!(trim.original_size, Size::new(16, 12));
+
+        let layers = parse_aseprite_layers(&meta)?;
+        assert_eq!(layers[0].opacity, 1.);
+        assert_eq!(layers[1].group.as_deref(), Some("Effects"));
+        assert!((layers[1].opacity - 0.2).abs() < f32::EPSILON);
+        assert_eq!(layers[1].blend_mode.as_deref(), Some("multiply"));
+
+        let invalid =
+            json::parse(r#"{ "slices": [ { "name": "a", "keys": [ { "frame": 0 } ] } ] }"#)?;
+        assert!(parse_aseprite_slices(&invalid).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    math::{Rect, Scaled, Size, Surround, Vector},
+    scene::Target,
+    sprite::{SpriteRotation, SpriteSliceKey, SpriteSource, SpriteSourceLocation, SpriteTint},
+};
+
+/// How the stretchable parts of a [`NineSlice`] fill their area
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NineSliceFill {
+    Stretch,
+    /// Repeats the part at its natural size, cropping the last repetition
+    Tile,
+}
+
+/// Draws a sprite as a stretchable frame. The corners, whose sizes are given
+/// by `insets`, are drawn at their natural size. The edges between them are
+/// stretched or tiled along their length, and the center fills the rest.
+#[derive(Debug, Clone)]
+pub struct NineSlice {
+    pub insets: Surround<u32>,
+    pub edges: NineSliceFill,
+    /// How to fill the center, or None to leave it empty
+    pub center: Option<NineSliceFill>,
+}
+
+impl NineSlice {
+    pub fn new(insets: Surround<u32>) -> Self {
+        Self {
+            insets,
+            edges: NineSliceFill::Stretch,
+            center: Some(NineSliceFill::Stretch),
+        }
+    }
+
+    /// Creates a nine-slice from the insets of a slice key, such as an
+    /// Aseprite 9-patch slice
+    pub fn from_slice_key(key: &SpriteSliceKey) -> Option<Self> {
+        key.nine_slice_insets().map(Self::new)
+    }
+
+    pub fn with_edges(mut self, edges: NineSliceFill) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    pub fn with_center(mut self, center: NineSliceFill) -> Self {
+        self.center = Some(center);
+        self
+    }
+
+    pub fn without_center(mut self) -> Self {
+        self.center = None;
+        self
+    }
+
+    pub async fn render_within(
+        &self,
+        source: &SpriteSource,
+        scene: &Target,
+        bounds: Rect<f32, Scaled>,
+    ) {
+        self.render_with_alpha(source, scene, bounds, 1.).await
+    }
+
+    pub async fn render_with_alpha(
+        &self,
+        source: &SpriteSource,
+        scene: &Target,
+        bounds: Rect<f32, Scaled>,
+        alpha: f32,
+    ) {
+        self.render_with_tint(
+            source,
+            scene,
+            bounds,
+            SpriteTint::default().with_alpha(alpha),
+        )
+        .await
+    }
+
+    /// Renders `source` sliced to fill `bounds`. When `bounds` is smaller
+    /// than the corners, the corners are shrunk to fit. Sources that aren't
+    /// a single unrotated rectangle are stretched to fill `bounds`.
+    pub async fn render_with_tint(
+        &self,
+        source: &SpriteSource,
+        scene: &Target,
+        bounds: Rect<f32, Scaled>,
+        tint: SpriteTint,
+    ) {
+        let area = match &source.location {
+            SpriteSourceLocation::Rect(area) => *area,
+            _ => {
+                source
+                    .render_with_tint(scene, bounds, SpriteRotation::default(), tint)
+                    .await;
+                return;
+            }
+        };
+
+        let columns = spans(
+            area.size.width,
+            self.insets.left.get(),
+            self.insets.right.get(),
+            bounds.size.width,
+        );
+        let rows = spans(
+            area.size.height,
+            self.insets.top.get(),
+            self.insets.bottom.get(),
+            bounds.size.height,
+        );
+
+        for (row, (source_y, source_height, y, height)) in rows.iter().enumerate() {
+            for (column, (source_x, source_width, x, width)) in columns.iter().enumerate() {
+                let fill = match (column, row) {
+                    (1, 1) => match self.center {
+                        Some(fill) => (fill, fill),
+                        None => continue,
+                    },
+                    (1, _) => (self.edges, NineSliceFill::Stretch),
+                    (_, 1) => (NineSliceFill::Stretch, self.edges),
+                    _ => (NineSliceFill::Stretch, NineSliceFill::Stretch),
+                };
+                let part = Rect::new(
+                    area.origin + Vector::new(*source_x, *source_y),
+                    Size::new(*source_width, *source_height),
+                );
+                let destination = Rect::new(
+                    bounds.origin + Vector::new(*x, *y),
+                    Size::new(*width, *height),
+                );
+                render_part(source, part, destination, fill, scene, tint).await;
+            }
+        }
+    }
+}
+
+/// Splits a length into its start, middle and end spans, returning the
+/// source offset and length, and destination offset and length of each.
+fn spans(
+    source_length: u32,
+    start: u32,
+    end: u32,
+    destination_length: f32,
+) -> [(u32, u32, f32, f32); 3] {
+    let start = start.min(source_length);
+    let end = end.min(source_length - start);
+    let middle = source_length - start - end;
+
+    let fixed = (start + end) as f32;
+    let shrink = if fixed > destination_length && fixed > 0. {
+        destination_length / fixed
+    } else {
+        1.
+    };
+    let start_length = start as f32 * shrink;
+    let end_length = end as f32 * shrink;
+    let middle_length = destination_length - start_length - end_length;
+
+    [
+        (0, start, 0., start_length),
+        (start, middle, start_length, middle_length),
+        (
+            start + middle,
+            end,
+            start_length + middle_length,
+            end_length,
+        ),
+    ]
+}
+
+async fn render_part(
+    source: &SpriteSource,
+    part: Rect<u32>,
+    destination: Rect<f32, Scaled>,
+    fill: (NineSliceFill, NineSliceFill),
+    scene: &Target,
+    tint: SpriteTint,
+) {
+    if part.is_empty() || destination.is_empty() {
+        return;
+    }
+
+    let (horizontal, vertical) = fill;
+    let tiles_x = tiles(part.size.width, destination.size.width, horizontal);
+    let tiles_y = tiles(part.size.height, destination.size.height, vertical);
+    for (source_y, source_height, y, height) in tiles_y.iter() {
+        for (source_x, source_width, x, width) in tiles_x.iter() {
+            SpriteSource::new(
+                Rect::new(
+                    part.origin + Vector::new(*source_x, *source_y),
+                    Size::new(*source_width, *source_height),
+                ),
+                source.texture.clone(),
+            )
+            .render_with_tint(
+                scene,
+                Rect::new(
+                    destination.origin + Vector::new(*x, *y),
+                    Size::new(*width, *height),
+                ),
+                SpriteRotation::default(),
+                tint,
+            )
+            .await;
+        }
+    }
+}
+
+/// Returns the repetitions of a part along one axis, in the same form as
+/// `spans`
+fn tiles(
+    source_length: u32,
+    destination_length: f32,
+    fill: NineSliceFill,
+) -> Vec<(u32, u32, f32, f32)> {
+    match fill {
+        NineSliceFill::Stretch => vec![(0, source_length, 0., destination_length)],
+        NineSliceFill::Tile => {
+            let tile_length = source_length as f32;
+            let mut tiles = Vec::new();
+            let mut offset = 0.;
+            while offset < destination_length {
+                let length = tile_length.min(destination_length - offset);
+                let pixels = ((length / tile_length * source_length as f32).round() as u32)
+                    .max(1)
+                    .min(source_length);
+                tiles.push((0, pixels, offset, length));
+                offset += tile_length;
+            }
+            tiles
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::{Length, Point, ScreenScale},
+        scene::Scene,
+        style::theme::Minimal,
+        texture::Texture,
+        window::SoftwareRenderer,
+        KludgineResult,
+    };
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn pixel(x: u32, y: u32) -> Rgba<u8> {
+        Rgba([x as u8 * 60 + 10, y as u8 * 60 + 10, 200, 255])
+    }
+
+    async fn render(
+        nine_slice: &NineSlice,
+        image_size: Size<u32>,
+        size: Size<f32>,
+    ) -> KludgineResult<RgbaImage> {
+        let mut image = RgbaImage::new(image_size.width, image_size.height);
+        for (x, y, color) in image.enumerate_pixels_mut() {
+            *color = pixel(x, y);
+        }
+        let source = SpriteSource::new(
+            Rect::new(Point::default(), image_size),
+            Texture::new(DynamicImage::ImageRgba8(image)),
+        );
+
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            size.cast_unit(),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+        nine_slice
+            .render_within(
+                &source,
+                &target,
+                Rect::new(Point::default(), size.cast_unit()),
+            )
+            .await;
+        SoftwareRenderer::default().render(&target).await
+    }
+
+    #[async_test]
+    async fn stretch_test() -> KludgineResult<()> {
+        let nine_slice = NineSlice::new(Surround {
+            left: Length::new(1),
+            top: Length::new(1),
+            right: Length::new(1),
+            bottom: Length::new(1),
+        });
+        let rendered = render(&nine_slice, Size::new(3, 3), Size::new(5., 4.)).await?;
+        // Corners are unscaled
+        assert_eq!(rendered.get_pixel(0, 0), &pixel(0, 0));
+        assert_eq!(rendered.get_pixel(4, 0), &pixel(2, 0));
+        assert_eq!(rendered.get_pixel(0, 3), &pixel(0, 2));
+        assert_eq!(rendered.get_pixel(4, 3), &pixel(2, 2));
+        // Edges and the center are stretched
+        for x in 1..4 {
+            assert_eq!(rendered.get_pixel(x, 0), &pixel(1, 0));
+            assert_eq!(rendered.get_pixel(x, 2), &pixel(1, 1));
+        }
+        assert_eq!(rendered.get_pixel(0, 2), &pixel(0, 1));
+
+        let rendered = render(
+            &nine_slice.without_center(),
+            Size::new(3, 3),
+            Size::new(5., 4.),
+        )
+        .await?;
+        assert_eq!(rendered.get_pixel(2, 2), &Rgba([0, 0, 0, 0]));
+        assert_eq!(rendered.get_pixel(4, 2), &pixel(2, 1));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn tile_test() -> KludgineResult<()> {
+        let nine_slice = NineSlice::new(Surround {
+            left: Length::new(1),
+            top: Length::new(1),
+            right: Length::new(1),
+            bottom: Length::new(1),
+        })
+        .with_edges(NineSliceFill::Tile);
+        let rendered = render(&nine_slice, Size::new(4, 3), Size::new(7., 3.)).await?;
+        // The top edge repeats its two pixels, cropping the last repetition
+        let top = (1..6)
+            .map(|x| *rendered.get_pixel(x, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            top,
+            vec![
+                pixel(1, 0),
+                pixel(2, 0),
+                pixel(1, 0),
+                pixel(2, 0),
+                pixel(1, 0)
+            ]
+        );
+        assert_eq!(rendered.get_pixel(6, 0), &pixel(3, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn shrink_test() {
+        // Corners shrink to share the available space
+        let spans = spans(10, 4, 4, 4.);
+        assert_eq!(spans[0], (0, 4, 0., 2.));
+        assert_eq!(spans[1], (4, 2, 2., 0.));
+        assert_eq!(spans[2], (6, 4, 2., 2.));
+    }
+}
@@ -0,0 +1,433 @@
+use crate::{
+    math::{Point, Rect, Size, Vector},
+    sprite::{
+        AnimationMode, SpriteAnimation, SpriteAnimations, SpriteCollection, SpriteFrame, SpriteMap,
+        SpriteSource, SpriteSourceLocation, SpriteTrim,
+    },
+    texture::Texture,
+    KludgineError, KludgineResult,
+};
+use json::JsonValue;
+use roxmltree::Document;
+use std::{collections::HashMap, time::Duration};
+
+impl SpriteMap<String> {
+    /// Loads a [TexturePacker](https://www.codeandweb.com/texturepacker) JSON
+    /// export, in either the hash or array format. Sprites are named by their
+    /// `filename`. Rotated and trimmed frames are supported.
+    pub async fn load_texture_packer_json(
+        raw_json: &str,
+        texture: Texture,
+    ) -> KludgineResult<Self> {
+        let json = json::parse(raw_json)?;
+        let meta = &json["meta"];
+        if meta["size"].is_object() {
+            let texture_size = texture.size();
+            if meta["size"]["w"] != texture_size.width || meta["size"]["h"] != texture_size.height {
+                return Err(texture_packer_error("size did not match input texture"));
+            }
+        }
+
+        let frames: Vec<(String, &JsonValue)> = if json["frames"].is_array() {
+            json["frames"]
+                .members()
+                .map(|frame| {
+                    frame["filename"]
+                        .as_str()
+                        .map(|name| (name.to_owned(), frame))
+                        .ok_or_else(|| texture_packer_error("frame is missing its filename"))
+                })
+                .collect::<KludgineResult<_>>()?
+        } else if json["frames"].is_object() {
+            json["frames"]
+                .entries()
+                .map(|(name, frame)| (name.to_owned(), frame))
+                .collect()
+        } else {
+            return Err(texture_packer_error("no `frames` section"));
+        };
+
+        let mut sprites = HashMap::new();
+        for (name, frame) in frames {
+            let area = json_rect(&frame["frame"]).ok_or_else(|| {
+                texture_packer_error(format!("frame {} has an invalid location", name))
+            })?;
+            // Rotated frames list the size of the sprite before it was rotated
+            let location = if frame["rotated"].as_bool().unwrap_or_default() {
+                SpriteSourceLocation::Rotated(Rect::new(
+                    area.origin,
+                    Size::new(area.size.height, area.size.width),
+                ))
+            } else {
+                SpriteSourceLocation::Rect(area)
+            };
+            let trim = if frame["trimmed"].as_bool().unwrap_or_default() {
+                json_trim(frame).ok_or_else(|| {
+                    texture_packer_error(format!("frame {} has invalid trim information", name))
+                })?
+            } else {
+                None
+            };
+            sprites.insert(name, packed_source(location, trim, &texture)?);
+        }
+
+        Ok(Self::new(sprites))
+    }
+
+    /// Loads a Starling or Sparrow XML texture atlas. Sprites are named by the
+    /// `name` of each `SubTexture`. Rotated and trimmed sprites are supported.
+    pub async fn load_starling_xml(raw_xml: &str, texture: Texture) -> KludgineResult<Self> {
+        let document = Document::parse(raw_xml).map_err(|err| starling_error(err.to_string()))?;
+        let root = document.root_element();
+        if !root.has_tag_name("TextureAtlas") {
+            return Err(starling_error("root element must be TextureAtlas"));
+        }
+
+        let mut sprites = HashMap::new();
+        for sub_texture in root
+            .children()
+            .filter(|node| node.has_tag_name("SubTexture"))
+        {
+            let name = sub_texture
+                .attribute("name")
+                .ok_or_else(|| starling_error("SubTexture is missing its name"))?;
+            let attribute = |attribute: &str| {
+                sub_texture
+                    .attribute(attribute)
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .map(|value| value as u32)
+                    .ok_or_else(|| {
+                        starling_error(format!("SubTexture {} has an invalid {}", name, attribute))
+                    })
+            };
+            let area = Rect::new(
+                Point::new(attribute("x")?, attribute("y")?),
+                Size::new(attribute("width")?, attribute("height")?),
+            );
+            let location = if sub_texture.attribute("rotated") == Some("true") {
+                SpriteSourceLocation::Rotated(area)
+            } else {
+                SpriteSourceLocation::Rect(area)
+            };
+            // Trimmed sprites list the position of the original image relative
+            // to the trimmed area, which is zero or negative.
+            let trim = match sub_texture.attribute("frameWidth") {
+                Some(_) => {
+                    let offset = |attribute: &str| {
+                        sub_texture
+                            .attribute(attribute)
+                            .and_then(|value| value.parse::<f32>().ok())
+                            .map(|value| (-value).max(0.) as u32)
+                            .ok_or_else(|| {
+                                starling_error(format!(
+                                    "SubTexture {} has an invalid {}",
+                                    name, attribute
+                                ))
+                            })
+                    };
+                    Some(SpriteTrim {
+                        offset: Vector::new(offset("frameX")?, offset("frameY")?),
+                        original_size: Size::new(
+                            attribute("frameWidth")?,
+                            attribute("frameHeight")?,
+                        ),
+                    })
+                }
+                None => None,
+            };
+            sprites.insert(name.to_owned(), packed_source(location, trim, &texture)?);
+        }
+
+        Ok(Self::new(sprites))
+    }
+
+    /// Groups the sprites into animations using their names. Names ending in
+    /// a frame number, such as `walk_0001.png`, become frames of an animation
+    /// tagged with the rest of the name (`walk`), ordered by frame number.
+    /// Each frame is shown for `frame_duration`. The untagged animation
+    /// contains every sprite, ordered by tag and frame number.
+    pub async fn animations(&self, frame_duration: Option<Duration>) -> SpriteAnimations {
+        let mut names = self
+            .keys()
+            .await
+            .into_iter()
+            .map(|name| {
+                let (tag, frame) = split_frame_name(&name);
+                (tag, frame, name)
+            })
+            .collect::<Vec<_>>();
+        names.sort();
+
+        let mut animations = HashMap::<Option<String>, SpriteAnimation>::new();
+        for (tag, _, name) in names {
+            let frame = SpriteFrame {
+                source: self.sprite(&name).await.unwrap(),
+                duration: frame_duration,
+                pivot: None,
+                events: Vec::new(),
+            };
+            for tag in [Some(tag), None].iter() {
+                animations
+                    .entry(tag.clone())
+                    .or_insert_with(|| SpriteAnimation::new(Vec::new(), AnimationMode::Forward))
+                    .frames
+                    .push(frame.clone());
+            }
+        }
+
+        SpriteAnimations::new(animations)
+    }
+}
+
+/// Splits a sprite name into its tag and frame number, ignoring any file
+/// extension
+fn split_frame_name(name: &str) -> (String, usize) {
+    let stem = match name.rfind('.') {
+        Some(index) if !name[index + 1..].contains('/') => &name[..index],
+        _ => name,
+    };
+    let tag = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let frame = stem[tag.len()..].parse().unwrap_or_default();
+    let tag = tag.trim_end_matches(&['_', '-', ' '][..]);
+    (tag.to_owned(), frame)
+}
+
+fn packed_source(
+    location: SpriteSourceLocation,
+    trim: Option<SpriteTrim>,
+    texture: &Texture,
+) -> KludgineResult<SpriteSource> {
+    let texture_bounds = Rect::new(Point::default(), texture.size());
+    if !texture_bounds.contains_rect(&location.bounds()) {
+        return Err(KludgineError::SpriteParseError(format!(
+            "sprite location {:?} is outside of the texture",
+            location.bounds()
+        )));
+    }
+
+    Ok(SpriteSource {
+        location,
+        texture: texture.clone(),
+        trim,
+    })
+}
+
+/// Reads the trim information shared by TexturePacker and Aseprite exports.
+/// Returns None when the frame isn't actually trimmed.
+pub(crate) fn json_trim(frame: &JsonValue) -> Option<Option<SpriteTrim>> {
+    let trimmed = json_rect(&frame["spriteSourceSize"])?;
+    let original_size = Size::new(
+        frame["sourceSize"]["w"].as_u32()?,
+        frame["sourceSize"]["h"].as_u32()?,
+    );
+    if trimmed == Rect::new(Point::default(), original_size) {
+        return Some(None);
+    }
+
+    Some(Some(SpriteTrim {
+        offset: trimmed.origin.to_vector(),
+        original_size,
+    }))
+}
+
+pub(crate) fn json_rect(value: &JsonValue) -> Option<Rect<u32>> {
+    Some(Rect::new(
+        json_point(value)?,
+        Size::new(value["w"].as_u32()?, value["h"].as_u32()?),
+    ))
+}
+
+pub(crate) fn json_point(value: &JsonValue) -> Option<Point<u32>> {
+    Some(Point::new(value["x"].as_u32()?, value["y"].as_u32()?))
+}
+
+fn texture_packer_error<S: Into<String>>(message: S) -> KludgineError {
+    KludgineError::SpriteParseError(format!("invalid texturepacker json: {}", message.into()))
+}
+
+fn starling_error<S: Into<String>>(message: S) -> KludgineError {
+    KludgineError::SpriteParseError(format!("invalid starling xml: {}", message.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::ScreenScale,
+        scene::{Scene, Target},
+        sprite::SpriteRotation,
+        style::theme::Minimal,
+        window::SoftwareRenderer,
+    };
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    fn texture() -> Texture {
+        Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(32, 16)))
+    }
+
+    #[async_test]
+    async fn texture_packer_test() -> KludgineResult<()> {
+        let hash = SpriteMap::load_texture_packer_json(
+            r#"{
+                "frames": {
+                    "walk_2.png": { "frame": { "x": 0, "y": 0, "w": 8, "h": 4 }, "rotated": true, "trimmed": false },
+                    "walk_1.png": { "frame": { "x": 4, "y": 0, "w": 4, "h": 4 }, "rotated": false, "trimmed": false },
+                    "idle.png": { "frame": { "x": 8, "y": 0, "w": 2, "h": 2 } },
+                    "jump.png": { "frame": { "x": 10, "y": 0, "w": 3, "h": 2 }, "trimmed": true,
+                        "spriteSourceSize": { "x": 1, "y": 4, "w": 3, "h": 2 }, "sourceSize": { "w": 5, "h": 8 } }
+                },
+                "meta": { "size": { "w": 32, "h": 16 } }
+            }"#,
+            texture(),
+        )
+        .await?;
+        let walk = hash.sprite(&String::from("walk_2.png")).await.unwrap();
+        assert!(matches!(
+            walk.location,
+            SpriteSourceLocation::Rotated(rect) if rect == Rect::new(Point::new(0, 0), Size::new(4, 8))
+        ));
+        assert_eq!(walk.location.size(), Size::new(8, 4));
+        assert!(walk.trim.is_none());
+
+        let jump = hash.sprite(&String::from("jump.png")).await.unwrap();
+        assert_eq!(
+            jump.trim,
+            Some(SpriteTrim {
+                offset: Vector::new(1, 4),
+                original_size: Size::new(5, 8),
+            })
+        );
+        assert_eq!(jump.location.size(), Size::new(3, 2));
+        assert_eq!(jump.size(), Size::new(5, 8));
+
+        let animations = hash.animations(Some(Duration::from_millis(50))).await;
+        let walk = animations.frames_for(&Some("walk")).await.unwrap();
+        assert_eq!(walk.frames.len(), 2);
+        assert_eq!(walk.frames[0].source.location.size(), Size::new(4, 4));
+        assert_eq!(walk.frames[0].duration, Some(Duration::from_millis(50)));
+        let all = animations
+            .frames_for(&Option::<String>::None)
+            .await
+            .unwrap();
+        assert_eq!(all.frames.len(), 4);
+
+        let array = SpriteMap::load_texture_packer_json(
+            r#"{
+                "frames": [
+                    { "filename": "a", "frame": { "x": 0, "y": 0, "w": 32, "h": 16 } }
+                ]
+            }"#,
+            texture(),
+        )
+        .await?;
+        assert_eq!(array.keys().await, vec![String::from("a")]);
+
+        assert!(SpriteMap::load_texture_packer_json(
+            r#"{ "frames": [ { "filename": "a", "frame": { "x": 1, "y": 0, "w": 32, "h": 16 } } ] }"#,
+            texture(),
+        )
+        .await
+        .is_err());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn starling_test() -> KludgineResult<()> {
+        let map = SpriteMap::load_starling_xml(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <TextureAtlas imagePath="atlas.png">
+                <SubTexture name="run-01" x="0" y="0" width="4" height="8" rotated="true"/>
+                <SubTexture name="run-02" x="4" y="0" width="6" height="8" frameX="-2" frameY="0" frameWidth="10" frameHeight="8"/>
+            </TextureAtlas>"#,
+            texture(),
+        )
+        .await?;
+        let run = map.sprite(&String::from("run-01")).await.unwrap();
+        assert_eq!(run.location.size(), Size::new(8, 4));
+        let run = map.sprite(&String::from("run-02")).await.unwrap();
+        assert_eq!(run.size(), Size::new(10, 8));
+        assert_eq!(run.trim.unwrap().offset, Vector::new(2, 0));
+        let animations = map.animations(None).await;
+        assert_eq!(
+            animations
+                .frames_for(&Some("run"))
+                .await
+                .unwrap()
+                .frames
+                .len(),
+            2
+        );
+
+        assert!(SpriteMap::load_starling_xml("<Atlas/>", texture())
+            .await
+            .is_err());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn rotated_render_test() -> KludgineResult<()> {
+        // A 1x2 sprite, red above green, stored rotated clockwise
+        let mut image = RgbaImage::new(2, 1);
+        image.put_pixel(0, 0, Rgba([0, 255, 0, 255]));
+        image.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        let source = SpriteSource {
+            location: SpriteSourceLocation::Rotated(Rect::new(Point::new(0, 0), Size::new(2, 1))),
+            texture: Texture::new(DynamicImage::ImageRgba8(image)),
+            trim: None,
+        };
+
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(2., 2.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+        source
+            .render_at(&target, Point::new(0., 0.), SpriteRotation::default())
+            .await;
+        let rendered = SoftwareRenderer::default().render(&target).await?;
+        assert_eq!(rendered.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(rendered.get_pixel(0, 1), &Rgba([0, 255, 0, 255]));
+        assert_eq!(rendered.get_pixel(1, 0), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn trimmed_render_test() -> KludgineResult<()> {
+        // A single red pixel trimmed from the bottom right of a 2x2 image
+        let mut image = RgbaImage::new(1, 1);
+        image.put_pixel(0, 0, Rgba([255, 0, 0, 255]));
+        let source = SpriteSource::new(
+            Rect::new(Point::new(0, 0), Size::new(1, 1)),
+            Texture::new(DynamicImage::ImageRgba8(image)),
+        )
+        .with_trim(SpriteTrim {
+            offset: Vector::new(1, 1),
+            original_size: Size::new(2, 2),
+        });
+
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(4., 4.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+        // Stretching the original image to 4x4 scales the trimmed area too
+        source
+            .render_within(
+                &target,
+                Rect::new(Point::new(0., 0.), Size::new(4., 4.)),
+                SpriteRotation::default(),
+            )
+            .await;
+        let rendered = SoftwareRenderer::default().render(&target).await?;
+        assert_eq!(rendered.get_pixel(1, 1), &Rgba([0, 0, 0, 0]));
+        assert_eq!(rendered.get_pixel(2, 2), &Rgba([255, 0, 0, 255]));
+        assert_eq!(rendered.get_pixel(3, 3), &Rgba([255, 0, 0, 255]));
+        Ok(())
+    }
+}
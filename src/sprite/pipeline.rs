@@ -22,10 +22,22 @@ pub struct Uniforms {
 pub struct Vertex {
     pub position: Vector3D<f32, ScreenSpace>,
     pub uv: Vector2D<f32, ScreenSpace>,
+    /// The tint color, multiplied with the texture
     pub color: Rgba8,
+    /// The flash color, added to the texture scaled by its alpha
+    pub flash: Rgba8,
 }
 
 impl Vertex {
+    pub fn transform_by(mut self, transform: Option<&euclid::Transform2D<f32, Raw, Raw>>) -> Self {
+        if let Some(transform) = transform {
+            let position = transform.transform_point(Point::new(self.position.x, self.position.y));
+            self.position.x = position.x;
+            self.position.y = position.y;
+        }
+        self
+    }
+
     pub fn rotate_by(mut self, angle: Option<Angle>, origin: Point<f32, Raw>) -> Self {
         if let Some(angle) = angle {
             let origin = origin.to_vector();
@@ -67,6 +79,7 @@ impl<'a> AbstractPipeline<'a> for Pipeline {
                 VertexFormat::Float3,
                 VertexFormat::Float2,
                 VertexFormat::UByte4,
+                VertexFormat::UByte4,
             ],
             pipeline_layout: &[
                 Set(&[Binding {
@@ -5,14 +5,17 @@ layout(set = 1, binding = 1) uniform sampler   sam;
 
 layout(location = 0) in  vec2  f_uv;
 layout(location = 1) in  vec4  f_color;
+layout(location = 2) in  vec4  f_flash;
 
 layout(location = 0) out vec4 fragColor;
 
 void main() {
 	vec4 texel = texture(sampler2D(tex, sam), f_uv);
 
+	// The tint color multiplies the texel, and the flash color is added on top,
+	// scaled by its alpha.
 	fragColor = vec4(
-		mix(texel.rgb, f_color.rgb, f_color.a),
-		texel.a
+		min(texel.rgb * f_color.rgb + f_flash.rgb * f_flash.a, vec3(1.0)),
+		texel.a * f_color.a
 	);
 }
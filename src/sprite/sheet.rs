@@ -32,10 +32,24 @@ where
     T: Debug + Eq + Hash,
 {
     pub async fn new(texture: Texture, tile_size: Size<u32>, tiles: Vec<T>) -> Self {
-        let dimensions = divide_size(texture.size().cast(), tile_size);
+        Self::new_with_spacing(texture, tile_size, 0, 0, tiles).await
+    }
+
+    /// Creates a sheet whose tiles are inset from the edges of the texture by
+    /// `margin` pixels and separated from each other by `spacing` pixels.
+    pub async fn new_with_spacing(
+        texture: Texture,
+        tile_size: Size<u32>,
+        margin: u32,
+        spacing: u32,
+        tiles: Vec<T>,
+    ) -> Self {
+        let dimensions = sheet_dimensions(texture.size(), tile_size, margin, spacing);
         Self {
             texture,
-            data: Handle::new(SpriteSheetData::from_tiles(tiles, tile_size, dimensions)),
+            data: Handle::new(SpriteSheetData::from_tiles(
+                tiles, tile_size, dimensions, margin, spacing,
+            )),
         }
     }
 
@@ -86,12 +100,29 @@ where
     }
 }
 
-fn divide_size(a: Size<u32>, b: Size<u32>) -> Size<u32> {
-    Size::new(a.width / b.width, a.height / b.height)
+/// Returns the number of columns and rows of tiles that fit within a texture
+pub(crate) fn sheet_dimensions(
+    texture_size: Size<u32>,
+    tile_size: Size<u32>,
+    margin: u32,
+    spacing: u32,
+) -> Size<u32> {
+    let fit =
+        |length: u32, tile: u32| (length.saturating_sub(margin * 2) + spacing) / (tile + spacing);
+    Size::new(
+        fit(texture_size.width, tile_size.width),
+        fit(texture_size.height, tile_size.height),
+    )
 }
 
 impl<T: Debug + Eq + Hash> SpriteSheetData<T> {
-    fn from_tiles(tiles: Vec<T>, tile_size: Size<u32>, dimensions: Size<u32>) -> Self {
+    fn from_tiles(
+        tiles: Vec<T>,
+        tile_size: Size<u32>,
+        dimensions: Size<u32>,
+        margin: u32,
+        spacing: u32,
+    ) -> Self {
         let mut sprites = HashMap::new();
 
         for (index, tile) in tiles.into_iter().enumerate() {
@@ -101,7 +132,10 @@ impl<T: Debug + Eq + Hash> SpriteSheetData<T> {
             sprites.insert(
                 tile,
                 Rect::new(
-                    Point::new(x * tile_size.width, y * tile_size.height),
+                    Point::new(
+                        margin + x * (tile_size.width + spacing),
+                        margin + y * (tile_size.height + spacing),
+                    ),
                     tile_size,
                 ),
             );
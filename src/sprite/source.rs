@@ -1,25 +1,43 @@
 use crate::{
-    math::{Box2D, Point, Raw, Rect, Scaled, Size},
+    math::{Box2D, Point, Raw, Rect, Scaled, Size, Vector},
     scene::{Element, Target},
-    sprite::{RenderedSprite, SpriteRotation},
+    sprite::{RenderedSprite, SpriteRotation, SpriteTint, SpriteTransform},
     texture::Texture,
 };
 #[derive(Debug, Clone)]
 pub struct SpriteSource {
     pub location: SpriteSourceLocation,
     pub texture: Texture,
+    /// Set when transparent borders were removed from the sprite when it was
+    /// packed into its texture
+    pub trim: Option<SpriteTrim>,
+}
+
+/// Describes where a trimmed sprite's pixels belong within its original image.
+/// Trimmed sprites are drawn as if the original image was being drawn, keeping
+/// frames of different sizes aligned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpriteTrim {
+    /// The location of the trimmed area within the original image
+    pub offset: Vector<u32>,
+    /// The size of the original image
+    pub original_size: Size<u32>,
 }
 
 #[derive(Debug, Clone)]
 pub enum SpriteSourceLocation {
     Rect(Rect<u32>),
     Joined(Vec<SpriteSourceSublocation>),
+    /// An area of the texture containing the sprite rotated 90 degrees
+    /// clockwise, as produced by atlas packers that rotate sprites to save
+    /// space. The sprite is drawn rotated back to its original orientation.
+    Rotated(Rect<u32>),
 }
 
 impl SpriteSourceLocation {
     pub fn bounds(&self) -> Rect<u32> {
         match self {
-            Self::Rect(rect) => *rect,
+            Self::Rect(rect) | Self::Rotated(rect) => *rect,
             Self::Joined(locations) => locations
                 .iter()
                 .fold(Option::<Rect<u32>>::None, |union, location| {
@@ -33,8 +51,14 @@ impl SpriteSourceLocation {
         }
     }
 
+    /// The size of the sprite when drawn, which is the size of
+    /// [`bounds`](SpriteSourceLocation::bounds) with the width and height
+    /// swapped for rotated sprites
     pub fn size(&self) -> Size<u32> {
-        self.bounds().size
+        match self {
+            Self::Rotated(rect) => Size::new(rect.size.height, rect.size.width),
+            _ => self.bounds().size,
+        }
     }
 }
 
@@ -55,6 +79,7 @@ impl SpriteSource {
         SpriteSource {
             location: SpriteSourceLocation::Rect(location),
             texture,
+            trim: None,
         }
     }
 
@@ -65,6 +90,7 @@ impl SpriteSource {
         Self {
             location: SpriteSourceLocation::Joined(locations.into_iter().collect()),
             texture,
+            trim: None,
         }
     }
 
@@ -99,6 +125,20 @@ impl SpriteSource {
         Self::new(Rect::new(Point::default(), texture.size()), texture)
     }
 
+    pub fn with_trim(mut self, trim: SpriteTrim) -> Self {
+        self.trim = Some(trim);
+        self
+    }
+
+    /// The size of the sprite when drawn at its natural size. For trimmed
+    /// sprites, this is the size of the original image.
+    pub fn size(&self) -> Size<u32> {
+        match &self.trim {
+            Some(trim) => trim.original_size,
+            None => self.location.size(),
+        }
+    }
+
     pub async fn render_at(
         &self,
         scene: &Target,
@@ -135,11 +175,27 @@ impl SpriteSource {
         rotation: SpriteRotation<Scaled>,
         alpha: f32,
     ) {
-        self.render_with_alpha(
+        self.render_at_with_tint(
             scene,
-            Rect::new(location, self.location.size().to_f32().cast_unit()),
+            location,
             rotation,
-            alpha,
+            SpriteTint::default().with_alpha(alpha),
+        )
+        .await
+    }
+
+    pub async fn render_at_with_tint(
+        &self,
+        scene: &Target,
+        location: Point<f32, Scaled>,
+        rotation: SpriteRotation<Scaled>,
+        tint: SpriteTint,
+    ) {
+        self.render_with_tint(
+            scene,
+            Rect::new(location, self.size().to_f32().cast_unit()),
+            rotation,
+            tint,
         )
         .await
     }
@@ -151,7 +207,23 @@ impl SpriteSource {
         rotation: SpriteRotation<Scaled>,
         alpha: f32,
     ) {
-        self.render_with_alpha_in_box(scene, bounds.to_box2d(), rotation, alpha)
+        self.render_with_tint(
+            scene,
+            bounds,
+            rotation,
+            SpriteTint::default().with_alpha(alpha),
+        )
+        .await
+    }
+
+    pub async fn render_with_tint(
+        &self,
+        scene: &Target,
+        bounds: Rect<f32, Scaled>,
+        rotation: SpriteRotation<Scaled>,
+        tint: SpriteTint,
+    ) {
+        self.render_with_tint_in_box(scene, bounds.to_box2d(), rotation, tint)
             .await
     }
 
@@ -161,13 +233,29 @@ impl SpriteSource {
         bounds: Box2D<f32, Scaled>,
         rotation: SpriteRotation<Scaled>,
         alpha: f32,
+    ) {
+        self.render_with_tint_in_box(
+            scene,
+            bounds,
+            rotation,
+            SpriteTint::default().with_alpha(alpha),
+        )
+        .await
+    }
+
+    pub async fn render_with_tint_in_box(
+        &self,
+        scene: &Target,
+        bounds: Box2D<f32, Scaled>,
+        rotation: SpriteRotation<Scaled>,
+        tint: SpriteTint,
     ) {
         let effective_scale = scene.scale_factor().await;
-        self.render_raw_with_alpha_in_box(
+        self.render_raw_with_tint_in_box(
             scene,
             bounds * effective_scale,
             rotation * effective_scale,
-            alpha,
+            tint,
         )
         .await
     }
@@ -178,16 +266,115 @@ impl SpriteSource {
         bounds: Box2D<f32, Raw>,
         rotation: SpriteRotation<Raw>,
         alpha: f32,
+    ) {
+        self.render_raw_with_tint_in_box(
+            scene,
+            bounds,
+            rotation,
+            SpriteTint::default().with_alpha(alpha),
+        )
+        .await
+    }
+
+    pub async fn render_raw_with_tint_in_box(
+        &self,
+        scene: &Target,
+        bounds: Box2D<f32, Raw>,
+        rotation: SpriteRotation<Raw>,
+        tint: SpriteTint,
+    ) {
+        let bounds = Box2D::new(
+            scene.offset_point_raw(bounds.min),
+            scene.offset_point_raw(bounds.max),
+        );
+        let mut rotation = rotation;
+        if self.trim.is_some() {
+            // Rotate around the center of the original image rather than the
+            // trimmed area.
+            rotation.screen_location = rotation.screen_location.or_else(|| Some(bounds.center()));
+        }
+        scene
+            .push_element(Element::Sprite {
+                sprite: RenderedSprite::new(
+                    self.trimmed_bounds(bounds),
+                    rotation,
+                    tint,
+                    self.clone(),
+                ),
+                clip: scene.clip,
+            })
+            .await;
+    }
+
+    pub async fn render_transformed(
+        &self,
+        scene: &Target,
+        bounds: Rect<f32, Scaled>,
+        transform: SpriteTransform,
+        tint: SpriteTint,
+    ) {
+        self.render_transformed_in_box(scene, bounds.to_box2d(), transform, tint)
+            .await
+    }
+
+    pub async fn render_transformed_in_box(
+        &self,
+        scene: &Target,
+        bounds: Box2D<f32, Scaled>,
+        transform: SpriteTransform,
+        tint: SpriteTint,
+    ) {
+        let effective_scale = scene.scale_factor().await;
+        self.render_raw_transformed_in_box(scene, bounds * effective_scale, transform, tint)
+            .await
+    }
+
+    /// Renders the sprite within `bounds` after applying `transform`. The
+    /// transform's pivot is relative to the untrimmed sprite.
+    pub async fn render_raw_transformed_in_box(
+        &self,
+        scene: &Target,
+        bounds: Box2D<f32, Raw>,
+        transform: SpriteTransform,
+        tint: SpriteTint,
     ) {
         let bounds = Box2D::new(
             scene.offset_point_raw(bounds.min),
             scene.offset_point_raw(bounds.max),
         );
+        let matrix = transform.to_matrix(bounds, self.size().to_f32());
         scene
             .push_element(Element::Sprite {
-                sprite: RenderedSprite::new(bounds, rotation, alpha, self.clone()),
+                sprite: RenderedSprite::transformed(
+                    self.trimmed_bounds(bounds),
+                    matrix,
+                    tint,
+                    self.clone(),
+                ),
                 clip: scene.clip,
             })
             .await;
     }
+
+    /// Shrinks `bounds`, which covers the original image, to the area
+    /// covered by the trimmed pixels
+    fn trimmed_bounds(&self, bounds: Box2D<f32, Raw>) -> Box2D<f32, Raw> {
+        match &self.trim {
+            Some(trim) => {
+                let scale_x = bounds.width() / trim.original_size.width as f32;
+                let scale_y = bounds.height() / trim.original_size.height as f32;
+                let size = self.location.size().to_f32();
+                let min = bounds.min
+                    + Vector::new(
+                        trim.offset.x as f32 * scale_x,
+                        trim.offset.y as f32 * scale_y,
+                    );
+                Box2D::new(
+                    min,
+                    min + Vector::new(size.width * scale_x, size.height * scale_y),
+                )
+            }
+            None => bounds,
+        }
+    }
 }
@@ -0,0 +1,481 @@
+use crate::{
+    sprite::{Sprite, SpriteEvent, SpriteUpdate},
+    KludgineError, KludgineResult,
+};
+use std::{collections::HashMap, time::Duration};
+
+/// A condition that must hold for an [`AnimationTransition`] to occur
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransitionCondition {
+    /// The bool parameter is set to the value. Unset bools are false.
+    Bool { name: String, value: bool },
+    /// The float parameter is greater than the threshold. Unset floats are 0.
+    Greater { name: String, threshold: f32 },
+    /// The float parameter is less than the threshold. Unset floats are 0.
+    Less { name: String, threshold: f32 },
+    /// The trigger has been set. Triggers are reset when a transition that
+    /// depends on them occurs.
+    Triggered(String),
+    /// The current animation has finished playing
+    Completed,
+}
+
+/// Controls when a transition switches animations once its conditions hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionTiming {
+    /// Switches immediately
+    Interrupt,
+    /// Waits until the current animation finishes its cycle
+    EndOfCycle,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnimationTransition {
+    pub to: String,
+    pub conditions: Vec<TransitionCondition>,
+    pub timing: TransitionTiming,
+    /// Starts the new animation at the same point of its cycle as the
+    /// previous animation, such as when switching from walking to running
+    pub sync_phase: bool,
+}
+
+impl AnimationTransition {
+    pub fn new<S: Into<String>>(to: S) -> Self {
+        Self {
+            to: to.into(),
+            conditions: Vec::new(),
+            timing: TransitionTiming::Interrupt,
+            sync_phase: false,
+        }
+    }
+
+    pub fn when(mut self, condition: TransitionCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    pub fn when_bool<S: Into<String>>(self, name: S, value: bool) -> Self {
+        self.when(TransitionCondition::Bool {
+            name: name.into(),
+            value,
+        })
+    }
+
+    pub fn when_greater<S: Into<String>>(self, name: S, threshold: f32) -> Self {
+        self.when(TransitionCondition::Greater {
+            name: name.into(),
+            threshold,
+        })
+    }
+
+    pub fn when_less<S: Into<String>>(self, name: S, threshold: f32) -> Self {
+        self.when(TransitionCondition::Less {
+            name: name.into(),
+            threshold,
+        })
+    }
+
+    pub fn when_triggered<S: Into<String>>(self, name: S) -> Self {
+        self.when(TransitionCondition::Triggered(name.into()))
+    }
+
+    pub fn on_complete(self) -> Self {
+        self.when(TransitionCondition::Completed)
+    }
+
+    pub fn at_end_of_cycle(mut self) -> Self {
+        self.timing = TransitionTiming::EndOfCycle;
+        self
+    }
+
+    pub fn with_synced_phase(mut self) -> Self {
+        self.sync_phase = true;
+        self
+    }
+}
+
+/// A state of an [`AnimationStateMachine`], which plays a sprite tag
+#[derive(Debug, Clone)]
+pub struct AnimationState {
+    pub tag: Option<String>,
+    /// Transitions out of this state, checked in order
+    pub transitions: Vec<AnimationTransition>,
+}
+
+impl AnimationState {
+    pub fn new<S: Into<String>>(tag: Option<S>) -> Self {
+        Self {
+            tag: tag.map(|tag| tag.into()),
+            transitions: Vec::new(),
+        }
+    }
+
+    pub fn with_transition(mut self, transition: AnimationTransition) -> Self {
+        self.transitions.push(transition);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AnimationParameter {
+    Bool(bool),
+    Float(f32),
+    Trigger,
+}
+
+/// Switches a [`Sprite`] between animations based on parameters, such as a
+/// character's speed or whether it is on the ground.
+#[derive(Debug, Clone)]
+pub struct AnimationStateMachine {
+    sprite: Sprite,
+    states: HashMap<String, AnimationState>,
+    any_state_transitions: Vec<AnimationTransition>,
+    parameters: HashMap<String, AnimationParameter>,
+    current_state: String,
+    entered: bool,
+    pending: Option<AnimationTransition>,
+}
+
+impl AnimationStateMachine {
+    pub fn new<S: Into<String>>(sprite: Sprite, initial_state: S) -> Self {
+        Self {
+            sprite,
+            states: HashMap::new(),
+            any_state_transitions: Vec::new(),
+            parameters: HashMap::new(),
+            current_state: initial_state.into(),
+            entered: false,
+            pending: None,
+        }
+    }
+
+    pub fn with_state<S: Into<String>>(mut self, name: S, state: AnimationState) -> Self {
+        self.states.insert(name.into(), state);
+        self
+    }
+
+    /// Adds a transition that can occur from every state, checked before
+    /// the current state's transitions
+    pub fn with_any_state_transition(mut self, transition: AnimationTransition) -> Self {
+        self.any_state_transitions.push(transition);
+        self
+    }
+
+    pub fn sprite(&self) -> &Sprite {
+        &self.sprite
+    }
+
+    pub fn current_state(&self) -> &str {
+        &self.current_state
+    }
+
+    pub fn set_bool<S: Into<String>>(&mut self, name: S, value: bool) {
+        self.parameters
+            .insert(name.into(), AnimationParameter::Bool(value));
+    }
+
+    pub fn set_float<S: Into<String>>(&mut self, name: S, value: f32) {
+        self.parameters
+            .insert(name.into(), AnimationParameter::Float(value));
+    }
+
+    pub fn set_trigger<S: Into<String>>(&mut self, name: S) {
+        self.parameters
+            .insert(name.into(), AnimationParameter::Trigger);
+    }
+
+    pub fn reset_trigger(&mut self, name: &str) {
+        if let Some(AnimationParameter::Trigger) = self.parameters.get(name) {
+            self.parameters.remove(name);
+        }
+    }
+
+    /// Switches to `state` immediately, ignoring transitions
+    pub async fn set_state<S: Into<String>>(&mut self, state: S) -> KludgineResult<()> {
+        self.enter(state.into(), false).await
+    }
+
+    /// Checks the transitions and advances the current animation by
+    /// `elapsed`. The events include those of every animation played.
+    pub async fn update(&mut self, elapsed: Option<Duration>) -> KludgineResult<SpriteUpdate> {
+        let mut events = Vec::new();
+        if !self.entered {
+            self.enter(self.current_state.clone(), false).await?;
+        }
+
+        let finished = self.sprite.is_finished().await;
+        if self.check_transitions(finished).await? {
+            events.extend(self.sprite.get_frame_with_events(None).await?.events);
+        }
+
+        let update = self.sprite.get_frame_with_events(elapsed).await?;
+        let cycle_ended = update
+            .events
+            .iter()
+            .any(|event| matches!(event, SpriteEvent::Looped | SpriteEvent::Finished));
+        events.extend(update.events);
+
+        let source = if cycle_ended && self.check_transitions(true).await? {
+            let update = self.sprite.get_frame_with_events(None).await?;
+            events.extend(update.events);
+            update.source
+        } else {
+            update.source
+        };
+
+        Ok(SpriteUpdate { source, events })
+    }
+
+    /// Performs the first transition whose conditions hold, returning true if
+    /// the state changed. Transitions that wait for the end of the cycle are
+    /// remembered until `at_end_of_cycle` is true, and are forgotten if their
+    /// conditions stop holding first. Triggers are consumed when the
+    /// transition is performed.
+    async fn check_transitions(&mut self, at_end_of_cycle: bool) -> KludgineResult<bool> {
+        let finished = self.sprite.is_finished().await;
+        if let Some(pending) = &self.pending {
+            if !self.conditions_hold(pending, finished) {
+                self.pending = None;
+            }
+        }
+
+        if at_end_of_cycle {
+            if let Some(transition) = self.pending.take() {
+                self.consume_triggers(&transition);
+                self.transition(transition).await?;
+                return Ok(true);
+            }
+        }
+
+        let state = self
+            .states
+            .get(&self.current_state)
+            .ok_or_else(|| KludgineError::InvalidAnimationState(self.current_state.clone()))?;
+        let transition = self
+            .any_state_transitions
+            .iter()
+            .filter(|transition| transition.to != self.current_state)
+            .chain(state.transitions.iter())
+            .find(|transition| self.conditions_hold(transition, finished))
+            .cloned();
+
+        match transition {
+            Some(transition) => {
+                if at_end_of_cycle || transition.timing == TransitionTiming::Interrupt {
+                    self.pending = None;
+                    self.consume_triggers(&transition);
+                    self.transition(transition).await?;
+                    Ok(true)
+                } else {
+                    self.pending = Some(transition);
+                    Ok(false)
+                }
+            }
+            None => {
+                self.pending = None;
+                Ok(false)
+            }
+        }
+    }
+
+    fn conditions_hold(&self, transition: &AnimationTransition, finished: bool) -> bool {
+        transition
+            .conditions
+            .iter()
+            .all(|condition| self.condition_holds(condition, finished))
+    }
+
+    fn condition_holds(&self, condition: &TransitionCondition, finished: bool) -> bool {
+        match condition {
+            TransitionCondition::Bool { name, value } => {
+                let current = match self.parameters.get(name) {
+                    Some(AnimationParameter::Bool(current)) => *current,
+                    _ => false,
+                };
+                current == *value
+            }
+            TransitionCondition::Greater { name, threshold } => self.float(name) > *threshold,
+            TransitionCondition::Less { name, threshold } => self.float(name) < *threshold,
+            TransitionCondition::Triggered(name) => {
+                matches!(self.parameters.get(name), Some(AnimationParameter::Trigger))
+            }
+            TransitionCondition::Completed => finished,
+        }
+    }
+
+    fn float(&self, name: &str) -> f32 {
+        match self.parameters.get(name) {
+            Some(AnimationParameter::Float(value)) => *value,
+            _ => 0.,
+        }
+    }
+
+    fn consume_triggers(&mut self, transition: &AnimationTransition) {
+        for condition in transition.conditions.iter() {
+            if let TransitionCondition::Triggered(name) = condition {
+                self.reset_trigger(name);
+            }
+        }
+    }
+
+    async fn transition(&mut self, transition: AnimationTransition) -> KludgineResult<()> {
+        self.enter(transition.to, transition.sync_phase).await
+    }
+
+    async fn enter(&mut self, state: String, sync_phase: bool) -> KludgineResult<()> {
+        let tag = self
+            .states
+            .get(&state)
+            .ok_or_else(|| KludgineError::InvalidAnimationState(state.clone()))?
+            .tag
+            .clone();
+        let phase = if sync_phase && self.entered {
+            Some(self.sprite.phase().await?)
+        } else {
+            None
+        };
+
+        if self.sprite.current_tag().await == tag {
+            self.sprite.restart().await?;
+        } else {
+            self.sprite.set_current_tag(tag).await?;
+        }
+        if let Some(phase) = phase {
+            self.sprite.set_phase(phase).await?;
+        }
+
+        self.current_state = state;
+        self.entered = true;
+        self.pending = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::{Point, Rect, Size},
+        sprite::{
+            AnimationMode, AnimationRepeat, SpriteAnimation, SpriteAnimations, SpriteFrameBuilder,
+            SpriteSource,
+        },
+        texture::Texture,
+    };
+    use image::{DynamicImage, RgbaImage};
+
+    fn animation(row: u32, frames: u32) -> SpriteAnimation {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(4, 4)));
+        SpriteAnimation::new(
+            (0..frames)
+                .map(|x| {
+                    SpriteFrameBuilder::new(SpriteSource::new(
+                        Rect::new(Point::new(x, row), Size::new(1, 1)),
+                        texture.clone(),
+                    ))
+                    .with_duration(Duration::from_millis(10))
+                    .build()
+                })
+                .collect(),
+            AnimationMode::Forward,
+        )
+    }
+
+    fn frame_of(update: &SpriteUpdate) -> Point<u32> {
+        update.source.location.bounds().origin
+    }
+
+    #[async_test]
+    async fn state_machine_test() -> KludgineResult<()> {
+        let sprite = Sprite::from(SpriteAnimations::new(crate::hash_map!(
+            Some("idle".to_owned()) => animation(0, 2),
+            Some("walk".to_owned()) => animation(1, 4),
+            Some("run".to_owned()) => animation(2, 4),
+            Some("attack".to_owned()) => animation(3, 2).with_repeat(AnimationRepeat::HoldLastFrame),
+        )));
+        let mut machine = AnimationStateMachine::new(sprite, "idle")
+            .with_state(
+                "idle",
+                AnimationState::new(Some("idle"))
+                    .with_transition(AnimationTransition::new("walk").when_greater("speed", 0.)),
+            )
+            .with_state(
+                "walk",
+                AnimationState::new(Some("walk"))
+                    .with_transition(
+                        AnimationTransition::new("run")
+                            .when_greater("speed", 5.)
+                            .with_synced_phase(),
+                    )
+                    .with_transition(
+                        AnimationTransition::new("idle")
+                            .when_less("speed", 0.1)
+                            .at_end_of_cycle(),
+                    ),
+            )
+            .with_state("run", AnimationState::new(Some("run")))
+            .with_state(
+                "attack",
+                AnimationState::new(Some("attack"))
+                    .with_transition(AnimationTransition::new("idle").on_complete()),
+            )
+            .with_any_state_transition(AnimationTransition::new("attack").when_triggered("attack"));
+
+        let update = machine.update(None).await?;
+        assert_eq!(frame_of(&update), Point::new(0, 0));
+
+        // Interrupting transitions switch immediately
+        machine.set_float("speed", 1.);
+        let update = machine.update(Some(Duration::from_millis(25))).await?;
+        assert_eq!(machine.current_state(), "walk");
+        assert_eq!(frame_of(&update), Point::new(2, 1));
+
+        // Waiting for the end of the cycle keeps walking until it loops
+        machine.set_float("speed", 0.);
+        machine.update(Some(Duration::from_millis(10))).await?;
+        assert_eq!(machine.current_state(), "walk");
+        let update = machine.update(Some(Duration::from_millis(10))).await?;
+        assert_eq!(machine.current_state(), "idle");
+        assert_eq!(frame_of(&update), Point::new(0, 0));
+
+        // A waiting transition is forgotten if its conditions stop holding
+        machine.set_float("speed", 1.);
+        machine.update(None).await?;
+        assert_eq!(machine.current_state(), "walk");
+        machine.set_float("speed", 0.);
+        machine.update(Some(Duration::from_millis(10))).await?;
+        machine.set_float("speed", 1.);
+        let update = machine.update(Some(Duration::from_millis(35))).await?;
+        assert!(update.events.contains(&SpriteEvent::Looped));
+        assert_eq!(machine.current_state(), "walk");
+        machine.set_float("speed", 0.);
+        machine.update(Some(Duration::from_millis(40))).await?;
+        assert_eq!(machine.current_state(), "idle");
+
+        // Walk to run keeps the phase of the walk cycle
+        machine.set_float("speed", 1.);
+        machine.update(Some(Duration::from_millis(15))).await?;
+        machine.set_float("speed", 10.);
+        let update = machine.update(None).await?;
+        assert_eq!(machine.current_state(), "run");
+        assert_eq!(frame_of(&update), Point::new(1, 2));
+
+        // Triggers work from any state, and are consumed
+        machine.set_trigger("attack");
+        let update = machine.update(None).await?;
+        assert_eq!(machine.current_state(), "attack");
+        assert_eq!(frame_of(&update), Point::new(0, 3));
+        machine.update(Some(Duration::from_millis(15))).await?;
+        assert_eq!(machine.current_state(), "attack");
+
+        // Completing the attack returns to idle
+        machine.set_float("speed", 0.);
+        let update = machine.update(Some(Duration::from_millis(10))).await?;
+        assert!(update.events.contains(&SpriteEvent::Finished));
+        assert_eq!(machine.current_state(), "idle");
+        assert_eq!(frame_of(&update), Point::new(0, 0));
+
+        assert!(machine.set_state("missing").await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,969 @@
+//! Utilities for testing user interfaces without a window or a GPU.
+
+use crate::{
+    math::{Point, Scaled, ScreenScale, Size},
+    runtime::Runtime,
+    scene::{Scene, Target},
+    style::theme::{Minimal, Theme},
+    ui::{global_arena, messages_pending, Index, Indexable, InteractiveComponent, UserInterface},
+    window::{
+        event::{
+            DeviceId, ElementState, Event, InputEvent, MouseButton, MouseScrollDelta, TouchPhase,
+            VirtualKeyCode, WindowEvent,
+        },
+        SoftwareRenderer,
+    },
+    KludgineError, KludgineResult,
+};
+use image::{Rgba, RgbaImage};
+use smol_timeout::TimeoutExt;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// When this environment variable is set, [`assert_snapshot`] overwrites the
+/// golden images instead of comparing against them.
+pub const UPDATE_SNAPSHOTS_VAR: &str = "KLUDGINE_UPDATE_SNAPSHOTS";
+
+/// How long [`HeadlessInterface::settle`] waits for components to process
+/// their messages.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A [`UserInterface`] mounted into a headless [`Scene`] with a fixed size and
+/// scale factor, rendered with a [`SoftwareRenderer`].
+pub struct HeadlessInterface<C>
+where
+    C: InteractiveComponent + 'static,
+{
+    ui: UserInterface<C>,
+    scene: Scene,
+    renderer: SoftwareRenderer,
+    now: Instant,
+    mouse_position: Option<Point<f32, Scaled>>,
+    _event_receiver: async_channel::Receiver<WindowEvent>,
+}
+
+impl<C> HeadlessInterface<C>
+where
+    C: InteractiveComponent + 'static,
+{
+    /// Mounts `root` using the [`Minimal`] theme.
+    pub async fn new(
+        root: C,
+        size: Size<f32, Scaled>,
+        scale_factor: ScreenScale,
+    ) -> KludgineResult<Self> {
+        Self::with_theme(root, Minimal::default().theme(), size, scale_factor).await
+    }
+
+    pub async fn with_theme(
+        root: C,
+        theme: Theme,
+        size: Size<f32, Scaled>,
+        scale_factor: ScreenScale,
+    ) -> KludgineResult<Self> {
+        Runtime::initialize_headless();
+
+        let scene = Scene::headless(theme, size * scale_factor, scale_factor).await;
+        let (event_sender, event_receiver) = async_channel::unbounded();
+        let ui =
+            UserInterface::new(root, scene.clone(), global_arena().clone(), event_sender).await?;
+
+        Ok(Self {
+            ui,
+            scene,
+            renderer: SoftwareRenderer::default(),
+            now: Instant::now(),
+            mouse_position: None,
+            _event_receiver: event_receiver,
+        })
+    }
+
+    pub fn scene(&self) -> &Scene {
+        &self.scene
+    }
+
+    pub fn user_interface(&mut self) -> &mut UserInterface<C> {
+        &mut self.ui
+    }
+
+    /// Updates and renders a single frame, returning the rendered pixels. Input is
+    /// hit-tested against the layout from the most recently rendered frame.
+    pub async fn render(&mut self) -> KludgineResult<RgbaImage> {
+        let mut scene = self.scene.clone();
+        scene.start_frame_at(self.now).await;
+        let target = Target::from(scene);
+
+        self.ui.update(&target, None).await?;
+        self.ui.render().await?;
+
+        self.renderer.render(&target).await
+    }
+
+    /// Renders a frame and compares it against the golden image at `path`. See
+    /// [`assert_snapshot`].
+    pub async fn assert_snapshot<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        tolerance: u8,
+    ) -> KludgineResult<()> {
+        let rendered = self.render().await?;
+        assert_snapshot(&rendered, path, tolerance)
+    }
+
+    /// The time the next rendered frame will report from [`Scene::now`].
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    /// Advances the virtual clock used when rendering frames.
+    pub fn advance_clock(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+
+    /// Returns the first entity whose component is a `T`.
+    pub async fn find_entity<T: InteractiveComponent + 'static>(&self) -> Option<Index> {
+        self.find_entities::<T>().await.into_iter().next()
+    }
+
+    /// Returns every entity whose component is a `T`, in traversal order.
+    pub async fn find_entities<T: InteractiveComponent + 'static>(&self) -> Vec<Index> {
+        let mut entities = Vec::new();
+        for layer in self.ui.layers().await {
+            let mut traverser = global_arena().traverse(&layer.root).await;
+            while let Some(index) = traverser.next().await {
+                if let Some(node) = global_arena().get(&index).await {
+                    let component = node.component.read().await;
+                    if component.component::<T, T>().await.is_some() {
+                        entities.push(index);
+                    }
+                }
+            }
+        }
+        entities
+    }
+
+    /// Returns the center of `entity`, excluding its margin, in the most
+    /// recently rendered frame.
+    pub async fn entity_center<I: Indexable>(
+        &self,
+        entity: &I,
+    ) -> KludgineResult<Point<f32, Scaled>> {
+        let node = global_arena()
+            .get(entity)
+            .await
+            .ok_or(KludgineError::InvalidIndex)?;
+        Ok(node.last_layout().await.bounds_without_margin().center())
+    }
+
+    /// The position the mouse was last moved to, or None if it's outside of
+    /// the window.
+    pub fn mouse_position(&self) -> Option<Point<f32, Scaled>> {
+        self.mouse_position
+    }
+
+    pub async fn move_mouse_to(&mut self, position: Point<f32, Scaled>) -> KludgineResult<()> {
+        self.mouse_position = Some(position);
+        self.send_input(Event::MouseMoved {
+            position: Some(position),
+        })
+        .await
+    }
+
+    pub async fn move_mouse_to_entity<I: Indexable>(&mut self, entity: &I) -> KludgineResult<()> {
+        let center = self.entity_center(entity).await?;
+        self.move_mouse_to(center).await
+    }
+
+    /// Moves the mouse outside of the window.
+    pub async fn move_mouse_away(&mut self) -> KludgineResult<()> {
+        self.mouse_position = None;
+        self.send_input(Event::MouseMoved { position: None }).await
+    }
+
+    pub async fn mouse_down(&mut self, button: MouseButton) -> KludgineResult<()> {
+        self.send_input(Event::MouseButton {
+            button,
+            state: ElementState::Pressed,
+        })
+        .await
+    }
+
+    pub async fn mouse_up(&mut self, button: MouseButton) -> KludgineResult<()> {
+        self.send_input(Event::MouseButton {
+            button,
+            state: ElementState::Released,
+        })
+        .await
+    }
+
+    /// Presses and releases `button` at the current mouse position.
+    pub async fn click(&mut self, button: MouseButton) -> KludgineResult<()> {
+        self.mouse_down(button).await?;
+        self.mouse_up(button).await
+    }
+
+    pub async fn click_entity<I: Indexable>(
+        &mut self,
+        entity: &I,
+        button: MouseButton,
+    ) -> KludgineResult<()> {
+        self.move_mouse_to_entity(entity).await?;
+        self.click(button).await
+    }
+
+    /// Presses `button` at `from`, moves the mouse to `to` in `steps` evenly
+    /// spaced movements, and releases `button`.
+    pub async fn drag(
+        &mut self,
+        from: Point<f32, Scaled>,
+        to: Point<f32, Scaled>,
+        button: MouseButton,
+        steps: usize,
+    ) -> KludgineResult<()> {
+        self.move_mouse_to(from).await?;
+        self.mouse_down(button).await?;
+        let steps = steps.max(1);
+        for step in 1..=steps {
+            let position = from.lerp(to, step as f32 / steps as f32);
+            self.move_mouse_to(position).await?;
+        }
+        self.mouse_up(button).await
+    }
+
+    /// Scrolls the mouse wheel at the current mouse position.
+    pub async fn scroll(&mut self, delta: MouseScrollDelta) -> KludgineResult<()> {
+        self.send_input(Event::MouseWheel {
+            delta,
+            touch_phase: TouchPhase::Moved,
+        })
+        .await
+    }
+
+    pub async fn key_down(&mut self, key: VirtualKeyCode) -> KludgineResult<()> {
+        self.send_key(key, ElementState::Pressed).await
+    }
+
+    pub async fn key_up(&mut self, key: VirtualKeyCode) -> KludgineResult<()> {
+        self.send_key(key, ElementState::Released).await
+    }
+
+    /// Presses and releases `key`.
+    pub async fn press_key(&mut self, key: VirtualKeyCode) -> KludgineResult<()> {
+        self.key_down(key).await?;
+        self.key_up(key).await
+    }
+
+    /// Presses each key in `keys` in order, then releases them in reverse order.
+    /// For example, `&[VirtualKeyCode::LControl, VirtualKeyCode::A]`.
+    pub async fn key_chord(&mut self, keys: &[VirtualKeyCode]) -> KludgineResult<()> {
+        for &key in keys {
+            self.key_down(key).await?;
+        }
+        for &key in keys.iter().rev() {
+            self.key_up(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends each character in `text` to the focused entity.
+    pub async fn type_text(&mut self, text: &str) -> KludgineResult<()> {
+        for character in text.chars() {
+            self.ui.receive_character(character).await?;
+            self.settle().await?;
+        }
+        Ok(())
+    }
+
+    /// Waits until the messages, commands and callbacks sent to components
+    /// have been processed. Components receive them asynchronously, so the
+    /// input methods call this after each input to keep their effects in
+    /// order. Messages sent by other interfaces are waited for as well.
+    pub async fn settle(&self) -> KludgineResult<()> {
+        let deadline = Instant::now() + SETTLE_TIMEOUT;
+        while messages_pending() {
+            if Instant::now() >= deadline {
+                return Err(KludgineError::SettleTimeout(SETTLE_TIMEOUT));
+            }
+            smol::Timer::after(Duration::from_millis(1)).await;
+        }
+        Ok(())
+    }
+
+    async fn send_key(&mut self, key: VirtualKeyCode, state: ElementState) -> KludgineResult<()> {
+        self.scene.set_key_state(key, state).await;
+        self.send_input(Event::Keyboard {
+            scancode: 0,
+            key: Some(key),
+            state,
+        })
+        .await
+    }
+
+    async fn send_input(&mut self, event: Event) -> KludgineResult<()> {
+        self.ui
+            .process_input(InputEvent {
+                // Safe because the id is never passed to winit.
+                device_id: unsafe { DeviceId::dummy() },
+                event,
+            })
+            .await?;
+        self.settle().await
+    }
+}
+
+/// Collects events emitted by components, such as from a callback passed to
+/// `EntityBuilder::callback`. Callbacks are delivered asynchronously, so use
+/// [`EventRecorder::next`] to wait for them.
+#[derive(Clone, Debug)]
+pub struct EventRecorder<T> {
+    sender: async_channel::Sender<T>,
+    receiver: async_channel::Receiver<T>,
+}
+
+impl<T> Default for EventRecorder<T> {
+    fn default() -> Self {
+        let (sender, receiver) = async_channel::unbounded();
+        Self { sender, receiver }
+    }
+}
+
+impl<T> EventRecorder<T> {
+    pub fn record(&self, event: T) {
+        let _ = self.sender.try_send(event);
+    }
+
+    /// Waits up to `timeout` for the next recorded event.
+    pub async fn next(&self, timeout: Duration) -> Option<T> {
+        self.receiver.recv().timeout(timeout).await?.ok()
+    }
+
+    /// Returns the events that have already been recorded without waiting.
+    pub fn drain(&self) -> Vec<T> {
+        let mut events = Vec::new();
+        while let Ok(event) = self.receiver.try_recv() {
+            events.push(event);
+        }
+        events
+    }
+}
+
+/// The result of comparing two images pixel by pixel.
+#[derive(Debug)]
+pub struct ImageComparison {
+    /// The number of pixels that differed by more than the tolerance.
+    pub differing_pixels: usize,
+    /// An image highlighting the differing pixels in red, drawn over a faded
+    /// copy of the expected image.
+    pub diff: RgbaImage,
+}
+
+impl ImageComparison {
+    pub fn matches(&self) -> bool {
+        self.differing_pixels == 0
+    }
+}
+
+/// Compares `actual` against `expected`. A pixel differs if any of its channels
+/// differ by more than `tolerance`. Returns None if the images aren't the same size.
+pub fn compare_images(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    tolerance: u8,
+) -> Option<ImageComparison> {
+    if actual.dimensions() != expected.dimensions() {
+        return None;
+    }
+
+    let mut differing_pixels = 0;
+    let mut diff = RgbaImage::new(expected.width(), expected.height());
+    for ((actual, expected), diff) in actual
+        .pixels()
+        .zip(expected.pixels())
+        .zip(diff.pixels_mut())
+    {
+        let differs = actual
+            .0
+            .iter()
+            .zip(expected.0.iter())
+            .any(|(&a, &e)| (a as i16 - e as i16).abs() > tolerance as i16);
+        *diff = if differs {
+            differing_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            let luma = (expected[0] as u16 + expected[1] as u16 + expected[2] as u16) / 3;
+            let faded = (luma / 4) as u8;
+            Rgba([faded, faded, faded, 255])
+        };
+    }
+
+    Some(ImageComparison {
+        differing_pixels,
+        diff,
+    })
+}
+
+/// Compares `rendered` against the PNG stored at `path`.
+///
+/// On a mismatch, the rendered image and a diff image are written alongside the
+/// golden image as `<name>.actual.png` and `<name>.diff.png`. If the
+/// `KLUDGINE_UPDATE_SNAPSHOTS` environment variable is set, the golden image is
+/// overwritten with `rendered` instead.
+pub fn assert_snapshot<P: AsRef<Path>>(
+    rendered: &RgbaImage,
+    path: P,
+    tolerance: u8,
+) -> KludgineResult<()> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(anyhow::Error::from)?;
+        }
+        rendered.save(path)?;
+        return Ok(());
+    }
+
+    if !path.exists() {
+        return Err(KludgineError::SnapshotMismatch(format!(
+            "{:?} does not exist. Set {} to create it",
+            path, UPDATE_SNAPSHOTS_VAR
+        )));
+    }
+
+    let expected = image::open(path)?.to_rgba8();
+    let comparison = compare_images(rendered, &expected, tolerance);
+    if matches!(&comparison, Some(comparison) if comparison.matches()) {
+        return Ok(());
+    }
+
+    let actual_path = sibling_path(path, "actual");
+    rendered.save(&actual_path)?;
+    match comparison {
+        Some(comparison) => {
+            let diff_path = sibling_path(path, "diff");
+            comparison.diff.save(&diff_path)?;
+            Err(KludgineError::SnapshotMismatch(format!(
+                "{} pixels differ from {:?}. Diff written to {:?}",
+                comparison.differing_pixels, path, diff_path
+            )))
+        }
+        None => Err(KludgineError::SnapshotMismatch(format!(
+            "rendered size {:?} does not match {:?} size {:?}. Rendered image written to {:?}",
+            rendered.dimensions(),
+            path,
+            expected.dimensions(),
+            actual_path
+        ))),
+    }
+}
+
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!("{}.{}.png", stem, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        math::{Length, Points, Vector},
+        shape::CornerRadii,
+        style::{ColorPair, FontSize, Style},
+        text::{rich::RichText, Text},
+        ui::{
+            AbsoluteBounds, Border, Button, Component, ComponentBorder, ComponentOverflow, Context,
+            ControlEvent, Dialog, DialogButton, DialogButtons, Entity, InteractiveComponentExt,
+            Label, Overflow, Scroll, Scrollbar, TextField, TextFieldEvent,
+        },
+    };
+    use async_trait::async_trait;
+
+    #[derive(Debug)]
+    struct ClickTarget {
+        button: Entity<Button>,
+        events: EventRecorder<ControlEvent>,
+    }
+
+    #[async_trait]
+    impl Component for ClickTarget {
+        async fn initialize(&mut self, context: &mut Context) -> KludgineResult<()> {
+            self.button = self
+                .new_entity(context, Button::new("Click"))
+                .await?
+                .callback(&self.entity(context), |event| event)
+                .insert()
+                .await?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl InteractiveComponent for ClickTarget {
+        type Message = ControlEvent;
+        type Command = ();
+        type Event = ();
+
+        async fn receive_message(
+            &mut self,
+            _context: &mut Context,
+            message: Self::Message,
+        ) -> KludgineResult<()> {
+            self.events.record(message);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct TextFieldTarget {
+        events: EventRecorder<TextFieldEvent>,
+    }
+
+    #[async_trait]
+    impl Component for TextFieldTarget {
+        async fn initialize(&mut self, context: &mut Context) -> KludgineResult<()> {
+            self.new_entity(
+                context,
+                TextField::new(RichText::new(vec![Text::span("", Default::default())])),
+            )
+            .await?
+            .callback(&self.entity(context), |event| event)
+            .insert()
+            .await?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl InteractiveComponent for TextFieldTarget {
+        type Message = TextFieldEvent;
+        type Command = ();
+        type Event = ();
+
+        async fn receive_message(
+            &mut self,
+            _context: &mut Context,
+            message: Self::Message,
+        ) -> KludgineResult<()> {
+            self.events.record(message);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct DialogTarget {
+        events: EventRecorder<Option<bool>>,
+    }
+
+    #[async_trait]
+    impl Component for DialogTarget {
+        async fn initialize(&mut self, context: &mut Context) -> KludgineResult<()> {
+            context
+                .new_layer(Dialog::<_, bool>::text("Continue?"))
+                .with(DialogButtons(vec![DialogButton::default()
+                    .caption("Yes")
+                    .value(true)
+                    .primary()]))
+                .callback(&self.entity(context), |value| value)
+                .insert()
+                .await?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl InteractiveComponent for DialogTarget {
+        type Message = Option<bool>;
+        type Command = ();
+        type Event = ();
+
+        async fn receive_message(
+            &mut self,
+            _context: &mut Context,
+            message: Self::Message,
+        ) -> KludgineResult<()> {
+            self.events.record(message);
+            Ok(())
+        }
+    }
+
+    #[derive(Debug)]
+    struct ScrollTarget;
+
+    #[async_trait]
+    impl Component for ScrollTarget {
+        async fn initialize(&mut self, context: &mut Context) -> KludgineResult<()> {
+            self.new_entity(context, Scroll::new(Label::new("Scrolling text")))
+                .await?
+                .with(ComponentOverflow {
+                    horizontal: Overflow::Clip,
+                    vertical: Overflow::Scroll,
+                })
+                .style_sheet(Style::new().with(FontSize::new(36.)))
+                .insert()
+                .await?;
+            Ok(())
+        }
+    }
+
+    impl InteractiveComponent for ScrollTarget {
+        type Message = ();
+        type Command = ();
+        type Event = ();
+    }
+
+    #[derive(Debug)]
+    struct RoundedBorderTarget;
+
+    #[async_trait]
+    impl Component for RoundedBorderTarget {
+        async fn initialize(&mut self, context: &mut Context) -> KludgineResult<()> {
+            let border = |color| Border::new(4., ColorPair::from(color));
+            self.new_entity(context, Label::new(""))
+                .await?
+                .style_sheet(
+                    Style::new().with(
+                        ComponentBorder::default()
+                            .with_top(border(Color::RED))
+                            .with_right(border(Color::LIME))
+                            .with_bottom(border(Color::BLUE))
+                            .with_left(border(Color::YELLOW))
+                            .with_corner_radii(CornerRadii::uniform(Length::new(10.))),
+                    ),
+                )
+                .bounds(
+                    AbsoluteBounds::default()
+                        .with_left(Points::new(0.))
+                        .with_top(Points::new(0.))
+                        .with_right(Points::new(0.))
+                        .with_bottom(Points::new(0.)),
+                )
+                .insert()
+                .await?;
+            Ok(())
+        }
+    }
+
+    impl InteractiveComponent for RoundedBorderTarget {
+        type Message = ();
+        type Command = ();
+        type Event = ();
+    }
+
+    async fn content_offset(entity: &Index) -> Vector<f32, Scaled> {
+        let node = global_arena().get(entity).await.unwrap();
+        node.last_layout().await.content_offset.unwrap_or_default()
+    }
+
+    fn snapshot_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("snapshots")
+            .join(name)
+    }
+
+    #[test]
+    fn compare_images_tolerance() {
+        let expected = RgbaImage::from_pixel(2, 2, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([102, 100, 100, 255]));
+        actual.put_pixel(1, 1, Rgba([100, 110, 100, 255]));
+
+        let comparison = compare_images(&actual, &expected, 2).unwrap();
+        assert_eq!(comparison.differing_pixels, 1);
+        assert_eq!(comparison.diff.get_pixel(1, 1), &Rgba([255, 0, 0, 255]));
+        assert_ne!(comparison.diff.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+
+        assert!(compare_images(&actual, &RgbaImage::new(1, 2), 2).is_none());
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn button_snapshot() -> KludgineResult<()> {
+        let mut ui = HeadlessInterface::new(
+            Button::new("Hello"),
+            Size::new(120., 48.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.assert_snapshot(snapshot_path("button.png"), 2).await
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn label_snapshot() -> KludgineResult<()> {
+        let mut ui = HeadlessInterface::new(
+            Label::new("Hello"),
+            Size::new(40., 16.),
+            ScreenScale::new(2.),
+        )
+        .await?;
+        ui.assert_snapshot(snapshot_path("label.png"), 2).await
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn text_field_snapshot() -> KludgineResult<()> {
+        let mut ui = HeadlessInterface::new(
+            TextField::new(RichText::new(vec![Text::span("Hello", Default::default())])),
+            Size::new(200., 60.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.assert_snapshot(snapshot_path("text_field.png"), 2)
+            .await?;
+
+        // Focusing the field at the end of its text shows the caret there.
+        let text_field = ui.find_entity::<TextField>().await.unwrap();
+        let node = global_arena().get(&text_field).await.unwrap();
+        let bounds = node.last_layout().await.bounds_without_margin();
+        ui.move_mouse_to(Point::new(bounds.max_x() - 1., bounds.center().y))
+            .await?;
+        ui.click(MouseButton::Left).await?;
+        ui.assert_snapshot(snapshot_path("text_field_focused.png"), 2)
+            .await
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn dialog_snapshot() -> KludgineResult<()> {
+        let mut ui = HeadlessInterface::new(
+            DialogTarget {
+                events: Default::default(),
+            },
+            Size::new(200., 100.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.assert_snapshot(snapshot_path("dialog.png"), 2).await
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn scroll_snapshot() -> KludgineResult<()> {
+        let mut ui =
+            HeadlessInterface::new(ScrollTarget, Size::new(100., 60.), ScreenScale::new(1.))
+                .await?;
+        ui.render().await?;
+        ui.move_mouse_to(Point::new(40., 30.)).await?;
+        ui.scroll(MouseScrollDelta::LineDelta(0., -1.)).await?;
+        ui.assert_snapshot(snapshot_path("scroll.png"), 2).await
+    }
+
+    #[async_test]
+    async fn rounded_border_colors_test() -> KludgineResult<()> {
+        let mut ui = HeadlessInterface::new(
+            RoundedBorderTarget,
+            Size::new(100., 60.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        let image = ui.render().await?;
+        assert_eq!(image.get_pixel(50, 1), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(98, 30), &Rgba([0, 255, 0, 255]));
+        assert_eq!(image.get_pixel(50, 58), &Rgba([0, 0, 255, 255]));
+        assert_eq!(image.get_pixel(1, 30), &Rgba([255, 255, 0, 255]));
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn click_button_test() -> KludgineResult<()> {
+        let events = EventRecorder::default();
+        let mut ui = HeadlessInterface::new(
+            ClickTarget {
+                button: Default::default(),
+                events: events.clone(),
+            },
+            Size::new(120., 48.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.render().await?;
+
+        let button = ui.find_entity::<Button>().await.unwrap();
+        ui.click_entity(&button, MouseButton::Left).await?;
+        assert_eq!(ui.mouse_position(), Some(ui.entity_center(&button).await?));
+
+        match events.next(Duration::from_secs(5)).await {
+            Some(ControlEvent::Clicked { button, .. }) => assert_eq!(button, MouseButton::Left),
+            other => unreachable!("unexpected event: {:?}", other),
+        }
+
+        // Clicking outside of the button shouldn't emit an event.
+        ui.move_mouse_to(Point::new(1., 1.)).await?;
+        ui.click(MouseButton::Left).await?;
+        assert!(events.next(Duration::from_millis(100)).await.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn text_field_events_test() -> KludgineResult<()> {
+        let events = EventRecorder::default();
+        let mut ui = HeadlessInterface::new(
+            TextFieldTarget {
+                events: events.clone(),
+            },
+            Size::new(120., 32.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.render().await?;
+
+        // Typing before the field is focused goes nowhere.
+        ui.type_text("x").await?;
+        assert!(events.next(Duration::from_millis(100)).await.is_none());
+
+        let text_field = ui.find_entity::<TextField>().await.unwrap();
+        ui.click_entity(&text_field, MouseButton::Left).await?;
+        ui.type_text("hi").await?;
+
+        let mut last_value = None;
+        let mut last_selection = None;
+        for event in events.drain() {
+            match event {
+                TextFieldEvent::ValueChanged(text) => last_value = Some(text.to_string().await),
+                TextFieldEvent::SelectionChanged { start, end } => {
+                    last_selection = Some((start, end))
+                }
+            }
+        }
+        assert_eq!(last_value.as_deref(), Some("hi"));
+        let (start, end) = last_selection.unwrap();
+        assert_eq!(start.offset, 2);
+        assert!(end.is_none());
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn select_all_test() -> KludgineResult<()> {
+        let events = EventRecorder::default();
+        let mut ui = HeadlessInterface::new(
+            TextFieldTarget {
+                events: events.clone(),
+            },
+            Size::new(120., 32.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.render().await?;
+
+        let text_field = ui.find_entity::<TextField>().await.unwrap();
+        ui.click_entity(&text_field, MouseButton::Left).await?;
+        ui.type_text("hi").await?;
+        events.drain();
+
+        let modifier = if cfg!(target_os = "macos") {
+            VirtualKeyCode::LWin
+        } else {
+            VirtualKeyCode::LControl
+        };
+        ui.key_chord(&[modifier, VirtualKeyCode::A]).await?;
+
+        let selection = events
+            .drain()
+            .into_iter()
+            .rev()
+            .find_map(|event| match event {
+                TextFieldEvent::SelectionChanged { start, end } => Some((start, end)),
+                TextFieldEvent::ValueChanged(_) => None,
+            });
+        let (start, end) = selection.unwrap();
+        assert_eq!(start.offset, 0);
+        assert_eq!(end.unwrap().offset, 2);
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn scroll_wheel_test() -> KludgineResult<()> {
+        let mut ui =
+            HeadlessInterface::new(ScrollTarget, Size::new(100., 60.), ScreenScale::new(1.))
+                .await?;
+        ui.render().await?;
+
+        let label = ui.find_entity::<Label>().await.unwrap();
+        assert_eq!(content_offset(&label).await, Vector::default());
+        ui.move_mouse_to(Point::new(40., 30.)).await?;
+        ui.scroll(MouseScrollDelta::LineDelta(0., -1.)).await?;
+        ui.render().await?;
+
+        // One line scrolls the contents up by 20 points.
+        assert_eq!(content_offset(&label).await, Vector::new(0., -20.));
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn scrollbar_drag_test() -> KludgineResult<()> {
+        let mut ui =
+            HeadlessInterface::new(ScrollTarget, Size::new(100., 60.), ScreenScale::new(1.))
+                .await?;
+        ui.render().await?;
+
+        // The grip starts at the top of the vertical scrollbar.
+        let mut vertical_bar = None;
+        for scrollbar in ui.find_entities::<Scrollbar>().await {
+            let node = global_arena().get(&scrollbar).await.unwrap();
+            let bounds = node.last_layout().await.inner_bounds();
+            if bounds.size.height > bounds.size.width {
+                vertical_bar = Some(bounds);
+            }
+        }
+        let bar = vertical_bar.unwrap();
+        let from = Point::new(bar.center().x, bar.min_y() + 1.);
+        ui.drag(from, from + Vector::new(0., 10.), MouseButton::Left, 5)
+            .await?;
+        ui.render().await?;
+
+        let label = ui.find_entity::<Label>().await.unwrap();
+        let offset = content_offset(&label).await;
+        assert!(offset.y < 0.);
+        assert_eq!(offset.x, 0.);
+        Ok(())
+    }
+
+    #[cfg(feature = "bundled-fonts-enabled")]
+    #[async_test]
+    async fn dialog_button_test() -> KludgineResult<()> {
+        let events = EventRecorder::default();
+        let mut ui = HeadlessInterface::new(
+            DialogTarget {
+                events: events.clone(),
+            },
+            Size::new(200., 100.),
+            ScreenScale::new(1.),
+        )
+        .await?;
+        ui.render().await?;
+
+        let button = ui.find_entity::<Button>().await.unwrap();
+        ui.click_entity(&button, MouseButton::Left).await?;
+        assert_eq!(events.next(Duration::from_secs(5)).await, Some(Some(true)));
+
+        // The dialog closes once a button is clicked.
+        ui.render().await?;
+        assert!(ui.find_entity::<Dialog<Label, bool>>().await.is_none());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn virtual_clock_test() -> KludgineResult<()> {
+        let mut ui =
+            HeadlessInterface::new(Label::new(""), Size::new(10., 10.), ScreenScale::new(1.))
+                .await?;
+        let start = ui.now();
+        ui.render().await?;
+        ui.advance_clock(Duration::from_millis(250));
+        ui.render().await?;
+        assert_eq!(ui.scene().now().await, start + Duration::from_millis(250));
+        assert_eq!(ui.scene().elapsed().await, Some(Duration::from_millis(250)));
+        Ok(())
+    }
+}
@@ -20,6 +20,8 @@ macro_rules! include_texture {
 pub struct Texture {
     pub id: u64,
     pub image: Arc<RgbaImage>,
+    /// Increases each time the texture's contents are replaced
+    pub(crate) revision: u64,
 }
 
 impl Texture {
@@ -29,6 +31,18 @@ impl Texture {
         Self {
             id,
             image: Arc::new(image),
+            revision: 0,
+        }
+    }
+
+    /// Returns a copy of this texture with new contents, keeping its id.
+    /// Renderers draw every revision of a texture using its newest contents,
+    /// so areas drawn from earlier revisions must stay in place.
+    pub(crate) fn with_image(&self, image: RgbaImage) -> Self {
+        Self {
+            id: self.id,
+            image: Arc::new(image),
+            revision: self.revision + 1,
         }
     }
 
@@ -0,0 +1,454 @@
+use crate::{
+    math::{Point, Size, Vector},
+    sprite::{SpriteCollection, SpriteSource},
+    tilemap::PersistentTileProvider,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    ops::{BitOr, BitOrAssign},
+};
+
+/// A set of neighboring tiles that share a tile's terrain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
+pub struct TileNeighbors(pub u8);
+
+impl TileNeighbors {
+    pub const NONE: Self = Self(0);
+    pub const NORTH: Self = Self(1);
+    pub const NORTH_EAST: Self = Self(2);
+    pub const EAST: Self = Self(4);
+    pub const SOUTH_EAST: Self = Self(8);
+    pub const SOUTH: Self = Self(16);
+    pub const SOUTH_WEST: Self = Self(32);
+    pub const WEST: Self = Self(64);
+    pub const NORTH_WEST: Self = Self(128);
+    pub const EDGES: Self = Self(1 | 4 | 16 | 64);
+
+    const DIRECTIONS: [(Self, i32, i32); 8] = [
+        (Self::NORTH, 0, -1),
+        (Self::NORTH_EAST, 1, -1),
+        (Self::EAST, 1, 0),
+        (Self::SOUTH_EAST, 1, 1),
+        (Self::SOUTH, 0, 1),
+        (Self::SOUTH_WEST, -1, 1),
+        (Self::WEST, -1, 0),
+        (Self::NORTH_WEST, -1, -1),
+    ];
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Converts a 4-bit edge index, where north is 1, east is 2, south is 4
+    /// and west is 8
+    pub fn from_edge_index(index: u8) -> Self {
+        let mut neighbors = Self::NONE;
+        for (bit, edge) in [Self::NORTH, Self::EAST, Self::SOUTH, Self::WEST]
+            .iter()
+            .enumerate()
+        {
+            if index & (1 << bit) != 0 {
+                neighbors |= *edge;
+            }
+        }
+        neighbors
+    }
+
+    /// Removes the corners that aren't next to two included edges. A corner
+    /// tile only changes a blob tile's appearance when both edges beside it
+    /// connect, which reduces the 256 combinations to 47.
+    pub fn without_isolated_corners(self) -> Self {
+        let mut neighbors = Self(self.0 & Self::EDGES.0);
+        for &(corner, first, second) in &[
+            (Self::NORTH_EAST, Self::NORTH, Self::EAST),
+            (Self::SOUTH_EAST, Self::SOUTH, Self::EAST),
+            (Self::SOUTH_WEST, Self::SOUTH, Self::WEST),
+            (Self::NORTH_WEST, Self::NORTH, Self::WEST),
+        ] {
+            if self.contains(corner) && self.contains(first) && self.contains(second) {
+                neighbors |= corner;
+            }
+        }
+        neighbors
+    }
+
+    /// Returns the 47 distinct blob combinations, in ascending order
+    pub fn blob_masks() -> Vec<Self> {
+        let masks = (0..=255u8)
+            .map(|mask| Self(mask).without_isolated_corners())
+            .collect::<HashSet<_>>();
+        let mut masks = masks.into_iter().collect::<Vec<_>>();
+        masks.sort();
+        masks
+    }
+}
+
+impl BitOr for TileNeighbors {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for TileNeighbors {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutotileMode {
+    /// Tiles are chosen based on their four edge neighbors, requiring 16 tiles
+    Edge,
+    /// Tiles are chosen based on all eight neighbors, requiring 47 tiles
+    Blob,
+}
+
+/// Maps combinations of neighbors to the tiles of a sprite collection
+#[derive(Debug, Clone)]
+pub struct AutotileRules<K> {
+    mode: AutotileMode,
+    tiles: HashMap<TileNeighbors, K>,
+    default: Option<K>,
+}
+
+impl<K> AutotileRules<K> {
+    pub fn new(mode: AutotileMode) -> Self {
+        Self {
+            mode,
+            tiles: HashMap::new(),
+            default: None,
+        }
+    }
+
+    /// Creates edge rules from 16 tiles, ordered by their 4-bit edge index.
+    /// See [`TileNeighbors::from_edge_index`].
+    pub fn edge_sequence<I: IntoIterator<Item = K>>(tiles: I) -> Self {
+        let mut rules = Self::new(AutotileMode::Edge);
+        for (index, tile) in tiles.into_iter().take(16).enumerate() {
+            rules
+                .tiles
+                .insert(TileNeighbors::from_edge_index(index as u8), tile);
+        }
+        rules
+    }
+
+    /// Creates blob rules from 47 tiles, ordered the same as
+    /// [`TileNeighbors::blob_masks`]
+    pub fn blob_sequence<I: IntoIterator<Item = K>>(tiles: I) -> Self {
+        let mut rules = Self::new(AutotileMode::Blob);
+        for (neighbors, tile) in TileNeighbors::blob_masks().into_iter().zip(tiles) {
+            rules.tiles.insert(neighbors, tile);
+        }
+        rules
+    }
+
+    pub fn with_tile(mut self, neighbors: TileNeighbors, tile: K) -> Self {
+        self.tiles.insert(self.normalize(neighbors), tile);
+        self
+    }
+
+    /// Sets the tile used when no tile matches a combination of neighbors
+    pub fn with_default(mut self, tile: K) -> Self {
+        self.default = Some(tile);
+        self
+    }
+
+    pub fn mode(&self) -> AutotileMode {
+        self.mode
+    }
+
+    /// Returns the tile for `neighbors`. Blob rules without a matching tile
+    /// fall back to the tile for the edges alone, and then to the default.
+    pub fn tile(&self, neighbors: TileNeighbors) -> Option<&K> {
+        let neighbors = self.normalize(neighbors);
+        self.tiles
+            .get(&neighbors)
+            .or_else(|| {
+                self.tiles
+                    .get(&TileNeighbors(neighbors.0 & TileNeighbors::EDGES.0))
+            })
+            .or(self.default.as_ref())
+    }
+
+    fn normalize(&self, neighbors: TileNeighbors) -> TileNeighbors {
+        match self.mode {
+            AutotileMode::Edge => TileNeighbors(neighbors.0 & TileNeighbors::EDGES.0),
+            AutotileMode::Blob => neighbors.without_isolated_corners(),
+        }
+    }
+}
+
+/// A grid of terrain types, used to pick tiles with an [`Autotiler`]
+#[derive(Debug, Clone)]
+pub struct TerrainMap<T> {
+    size: Size<u32>,
+    terrain: Vec<Option<T>>,
+}
+
+impl<T> TerrainMap<T> {
+    pub fn new(size: Size<u32>) -> Self {
+        let mut terrain = Vec::new();
+        terrain.resize_with((size.width * size.height) as usize, || None);
+        Self { size, terrain }
+    }
+
+    pub fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    pub fn get(&self, location: Point<i32>) -> Option<&T> {
+        self.index(location)
+            .and_then(|index| self.terrain[index].as_ref())
+    }
+
+    /// Sets the terrain at `location`, returning the previous terrain
+    pub fn set(&mut self, location: Point<u32>, terrain: Option<T>) -> Option<T> {
+        let index = self.index(location.cast()).expect("location out of bounds");
+        std::mem::replace(&mut self.terrain[index], terrain)
+    }
+
+    fn contains(&self, location: Point<i32>) -> bool {
+        location.x >= 0
+            && location.y >= 0
+            && location.x < self.size.width as i32
+            && location.y < self.size.height as i32
+    }
+
+    fn index(&self, location: Point<i32>) -> Option<usize> {
+        if self.contains(location) {
+            Some((location.x + location.y * self.size.width as i32) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Picks tiles for a [`TerrainMap`] based on the terrain surrounding each
+/// tile. Each terrain type has its own [`AutotileRules`].
+#[derive(Debug, Clone)]
+pub struct Autotiler<T, K> {
+    rules: HashMap<T, AutotileRules<K>>,
+    connections: HashSet<(T, T)>,
+    connect_out_of_bounds: bool,
+}
+
+impl<T, K> Default for Autotiler<T, K> {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::new(),
+            connections: HashSet::new(),
+            connect_out_of_bounds: true,
+        }
+    }
+}
+
+impl<T, K> Autotiler<T, K>
+where
+    T: Clone + Eq + Hash,
+    K: Send + Sync,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_terrain(mut self, terrain: T, rules: AutotileRules<K>) -> Self {
+        self.rules.insert(terrain, rules);
+        self
+    }
+
+    /// Makes tiles of `terrain` connect to neighbors of `other`, as well as
+    /// to neighbors of the same terrain
+    pub fn with_connection(mut self, terrain: T, other: T) -> Self {
+        self.connections.insert((terrain, other));
+        self
+    }
+
+    /// Sets whether tiles connect to the area outside of the map. Defaults to
+    /// true, which avoids drawing edges along the map's borders.
+    pub fn with_connect_out_of_bounds(mut self, connect: bool) -> Self {
+        self.connect_out_of_bounds = connect;
+        self
+    }
+
+    /// Returns the neighbors of `location` that connect to its terrain
+    pub fn neighbors(&self, terrain: &TerrainMap<T>, location: Point<i32>) -> TileNeighbors {
+        let mut neighbors = TileNeighbors::NONE;
+        if let Some(center) = terrain.get(location) {
+            for &(neighbor, x, y) in &TileNeighbors::DIRECTIONS {
+                let neighbor_location = location + Vector::new(x, y);
+                let connects = if terrain.contains(neighbor_location) {
+                    match terrain.get(neighbor_location) {
+                        Some(other) => {
+                            center == other
+                                || self.connections.contains(&(center.clone(), other.clone()))
+                        }
+                        None => false,
+                    }
+                } else {
+                    self.connect_out_of_bounds
+                };
+                if connects {
+                    neighbors |= neighbor;
+                }
+            }
+        }
+        neighbors
+    }
+
+    /// Returns the sprite for `location`, or None if it has no terrain or its
+    /// terrain has no matching tile
+    pub async fn sprite<C: SpriteCollection<K>>(
+        &self,
+        collection: &C,
+        terrain: &TerrainMap<T>,
+        location: Point<i32>,
+    ) -> Option<SpriteSource> {
+        let rules = self.rules.get(terrain.get(location)?)?;
+        let tile = rules.tile(self.neighbors(terrain, location))?;
+        collection.sprite(tile).await
+    }
+
+    /// Creates a tile provider containing a tile for every location of
+    /// `terrain`
+    pub async fn build<C: SpriteCollection<K>>(
+        &self,
+        collection: &C,
+        terrain: &TerrainMap<T>,
+    ) -> PersistentTileProvider {
+        let size = terrain.size();
+        let mut tiles = Vec::with_capacity((size.width * size.height) as usize);
+        for y in 0..size.height as i32 {
+            for x in 0..size.width as i32 {
+                tiles.push(self.sprite(collection, terrain, Point::new(x, y)).await);
+            }
+        }
+        PersistentTileProvider::new(size, tiles)
+    }
+
+    /// Updates the tiles of `provider` after the terrain at `location` has
+    /// changed, including the tiles surrounding it
+    pub async fn update<C: SpriteCollection<K>>(
+        &self,
+        collection: &C,
+        terrain: &TerrainMap<T>,
+        provider: &mut PersistentTileProvider,
+        location: Point<u32>,
+    ) {
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let location = location.cast::<i32>() + Vector::new(x, y);
+                if terrain.contains(location) {
+                    let sprite = self.sprite(collection, terrain, location).await;
+                    provider.set(location.cast(), sprite);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sprite::SpriteSheet, texture::Texture, tilemap::TileProvider};
+    use image::{DynamicImage, RgbaImage};
+
+    #[test]
+    fn blob_masks_test() {
+        let masks = TileNeighbors::blob_masks();
+        assert_eq!(masks.len(), 47);
+        assert_eq!(masks[0], TileNeighbors::NONE);
+        assert_eq!(masks[46], TileNeighbors(255));
+        assert_eq!(
+            (TileNeighbors::NORTH | TileNeighbors::NORTH_EAST).without_isolated_corners(),
+            TileNeighbors::NORTH
+        );
+        assert_eq!(
+            TileNeighbors::from_edge_index(5),
+            TileNeighbors::NORTH | TileNeighbors::SOUTH
+        );
+
+        let rules = AutotileRules::new(AutotileMode::Blob)
+            .with_tile(TileNeighbors::NORTH | TileNeighbors::EAST, 1)
+            .with_default(0);
+        // The corner is ignored when there is no tile for it
+        assert_eq!(
+            rules.tile(TileNeighbors::NORTH | TileNeighbors::EAST | TileNeighbors::NORTH_EAST),
+            Some(&1)
+        );
+        assert_eq!(rules.tile(TileNeighbors::SOUTH), Some(&0));
+    }
+
+    #[async_test]
+    async fn autotile_test() {
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        enum Terrain {
+            Grass,
+            Water,
+        }
+
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(16, 2)));
+        let sheet = SpriteSheet::new(texture, Size::new(1, 1), (0..16).collect()).await;
+        let autotiler = Autotiler::new()
+            .with_terrain(Terrain::Grass, AutotileRules::edge_sequence(0..16))
+            .with_connect_out_of_bounds(false);
+
+        // A horizontal strip of grass, with water that has no rules
+        let mut terrain = TerrainMap::new(Size::new(4, 2));
+        for x in 0..3 {
+            terrain.set(Point::new(x, 0), Some(Terrain::Grass));
+        }
+        terrain.set(Point::new(0, 1), Some(Terrain::Water));
+
+        let tile_index =
+            |sprite: Option<SpriteSource>| sprite.map(|s| s.location.bounds().origin.x);
+        assert_eq!(
+            autotiler.neighbors(&terrain, Point::new(1, 0)),
+            TileNeighbors::EAST | TileNeighbors::WEST
+        );
+        assert_eq!(
+            tile_index(autotiler.sprite(&sheet, &terrain, Point::new(0, 0)).await),
+            Some(2)
+        );
+        assert_eq!(
+            tile_index(autotiler.sprite(&sheet, &terrain, Point::new(1, 0)).await),
+            Some(10)
+        );
+        assert_eq!(
+            tile_index(autotiler.sprite(&sheet, &terrain, Point::new(2, 0)).await),
+            Some(8)
+        );
+        assert_eq!(
+            tile_index(autotiler.sprite(&sheet, &terrain, Point::new(0, 1)).await),
+            None
+        );
+
+        // Water connects to grass once a connection is added
+        let autotiler = autotiler.with_connection(Terrain::Water, Terrain::Grass);
+        assert_eq!(
+            autotiler.neighbors(&terrain, Point::new(0, 1)),
+            TileNeighbors::NORTH | TileNeighbors::NORTH_EAST
+        );
+
+        let mut provider = autotiler.build(&sheet, &terrain).await;
+        terrain.set(Point::new(3, 0), Some(Terrain::Grass));
+        autotiler
+            .update(&sheet, &terrain, &mut provider, Point::new(3, 0))
+            .await;
+        let tile = provider.get_tile(Point::new(2, 0)).await.unwrap();
+        assert_eq!(
+            tile.sprite
+                .get_frame(None)
+                .await
+                .unwrap()
+                .location
+                .bounds()
+                .origin
+                .x,
+            10
+        );
+        assert!(provider.get_tile(Point::new(3, 0)).await.is_some());
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{
+    math::{Point, Size},
+    runtime::Runtime,
+    tilemap::{Tile, TileProvider, TileSprite},
+    Handle, KludgineError, KludgineResult,
+};
+use async_trait::async_trait;
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+/// Loads or generates the tiles of a [`ChunkedTileProvider`]
+#[async_trait]
+pub trait ChunkLoader: Send + Sync + 'static {
+    /// Returns the tiles of `chunk`, row by row. `chunk` is measured in
+    /// chunks, so its first tile is at `chunk * chunk_size`.
+    async fn load_chunk(
+        &self,
+        chunk: Point<i32>,
+        chunk_size: Size<u32>,
+    ) -> KludgineResult<Vec<Option<TileSprite>>>;
+}
+
+#[async_trait]
+impl<F, Fut> ChunkLoader for F
+where
+    F: Fn(Point<i32>, Size<u32>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = KludgineResult<Vec<Option<TileSprite>>>> + Send,
+{
+    async fn load_chunk(
+        &self,
+        chunk: Point<i32>,
+        chunk_size: Size<u32>,
+    ) -> KludgineResult<Vec<Option<TileSprite>>> {
+        self(chunk, chunk_size).await
+    }
+}
+
+/// Provides tiles for unbounded maps by storing them in fixed-size chunks.
+/// Chunks are loaded in the background the first time one of their tiles is
+/// requested, along with the chunks surrounding them, so that chunks are
+/// usually ready before they scroll into view. Until a chunk is loaded, its
+/// tiles are empty.
+///
+/// Once more than `max_loaded_chunks` chunks are loaded, the least recently
+/// used chunks are unloaded. The budget should be larger than the number of
+/// chunks visible at once, including the preloaded chunks around them.
+#[derive(Derivative, Clone)]
+#[derivative(Debug)]
+pub struct ChunkedTileProvider {
+    chunk_size: Size<u32>,
+    max_loaded_chunks: usize,
+    preload_radius: u32,
+    #[derivative(Debug = "ignore")]
+    loader: Arc<dyn ChunkLoader>,
+    data: Handle<ChunkData>,
+}
+
+#[derive(Debug, Default)]
+struct ChunkData {
+    chunks: HashMap<Point<i32>, Chunk>,
+    access_counter: u64,
+    errors: Vec<(Point<i32>, KludgineError)>,
+}
+
+#[derive(Debug)]
+struct Chunk {
+    tiles: Option<Vec<Option<TileSprite>>>,
+    last_used: u64,
+    neighbors_requested: bool,
+}
+
+impl ChunkedTileProvider {
+    pub fn new<L: ChunkLoader>(chunk_size: Size<u32>, loader: L) -> Self {
+        Self {
+            chunk_size,
+            max_loaded_chunks: 64,
+            preload_radius: 1,
+            loader: Arc::new(loader),
+            data: Handle::new(ChunkData::default()),
+        }
+    }
+
+    /// Sets how many chunks can be loaded or loading at once. Defaults to 64.
+    pub fn with_max_loaded_chunks(mut self, max_loaded_chunks: usize) -> Self {
+        self.max_loaded_chunks = max_loaded_chunks.max(1);
+        self
+    }
+
+    /// Sets how many chunks around each requested chunk are loaded ahead of
+    /// time. Defaults to 1.
+    pub fn with_preload_radius(mut self, preload_radius: u32) -> Self {
+        self.preload_radius = preload_radius;
+        self
+    }
+
+    pub fn chunk_size(&self) -> Size<u32> {
+        self.chunk_size
+    }
+
+    /// Returns the chunk that contains the tile `location`
+    pub fn chunk_containing(&self, location: Point<i32>) -> Point<i32> {
+        Point::new(
+            location.x.div_euclid(self.chunk_size.width as i32),
+            location.y.div_euclid(self.chunk_size.height as i32),
+        )
+    }
+
+    pub async fn is_chunk_loaded(&self, chunk: Point<i32>) -> bool {
+        let data = self.data.read().await;
+        matches!(data.chunks.get(&chunk), Some(Chunk { tiles: Some(_), .. }))
+    }
+
+    pub async fn loaded_chunks(&self) -> Vec<Point<i32>> {
+        let data = self.data.read().await;
+        data.chunks
+            .iter()
+            .filter(|(_, chunk)| chunk.tiles.is_some())
+            .map(|(location, _)| *location)
+            .collect()
+    }
+
+    /// Unloads `chunk`, causing it to be loaded again the next time it is
+    /// drawn.
+    pub async fn unload_chunk(&self, chunk: Point<i32>) {
+        let mut data = self.data.write().await;
+        data.chunks.remove(&chunk);
+    }
+
+    /// Returns the errors from chunks that failed to load since the last call.
+    /// Chunks that fail to load are empty until they are unloaded.
+    pub async fn take_load_errors(&self) -> Vec<(Point<i32>, KludgineError)> {
+        let mut data = self.data.write().await;
+        std::mem::take(&mut data.errors)
+    }
+
+    fn request_chunk(&self, data: &mut ChunkData, chunk: Point<i32>) {
+        if data.chunks.contains_key(&chunk) {
+            return;
+        }
+
+        data.access_counter += 1;
+        data.chunks.insert(
+            chunk,
+            Chunk {
+                tiles: None,
+                last_used: data.access_counter,
+                neighbors_requested: false,
+            },
+        );
+        data.evict(self.max_loaded_chunks);
+
+        let loader = self.loader.clone();
+        let handle = self.data.clone();
+        let chunk_size = self.chunk_size;
+        let max_loaded_chunks = self.max_loaded_chunks;
+        Runtime::spawn(async move {
+            let expected_tiles = (chunk_size.width * chunk_size.height) as usize;
+            let result = loader
+                .load_chunk(chunk, chunk_size)
+                .await
+                .and_then(|tiles| {
+                    if tiles.len() == expected_tiles {
+                        Ok(tiles)
+                    } else {
+                        Err(KludgineError::Other(anyhow::Error::msg(format!(
+                            "chunk {:?} has {} tiles, expected {}",
+                            chunk,
+                            tiles.len(),
+                            expected_tiles
+                        ))))
+                    }
+                });
+
+            let mut data = handle.write().await;
+            // The chunk may have been unloaded while it was loading
+            if data.chunks.contains_key(&chunk) {
+                let tiles = match result {
+                    Ok(tiles) => tiles,
+                    Err(err) => {
+                        data.errors.push((chunk, err));
+                        vec![None; expected_tiles]
+                    }
+                };
+                data.chunks.get_mut(&chunk).unwrap().tiles = Some(tiles);
+                data.evict(max_loaded_chunks);
+            }
+        })
+        .detach();
+    }
+
+    fn request_neighbors(&self, data: &mut ChunkData, chunk: Point<i32>) {
+        let radius = self.preload_radius as i32;
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                self.request_chunk(data, Point::new(chunk.x + x, chunk.y + y));
+            }
+        }
+    }
+}
+
+impl ChunkData {
+    /// Unloads the least recently used chunks until at most `max_chunks`
+    /// remain
+    fn evict(&mut self, max_chunks: usize) {
+        while self.chunks.len() > max_chunks {
+            let oldest = self
+                .chunks
+                .iter()
+                .min_by_key(|(_, chunk)| chunk.last_used)
+                .map(|(location, _)| *location)
+                .unwrap();
+            self.chunks.remove(&oldest);
+        }
+    }
+}
+
+#[async_trait]
+impl TileProvider for ChunkedTileProvider {
+    async fn get_tile(&self, location: Point<i32>) -> Option<Tile> {
+        let chunk_location = self.chunk_containing(location);
+        let mut data = self.data.write().await;
+        data.access_counter += 1;
+        let now = data.access_counter;
+
+        let (sprite, request_neighbors) = match data.chunks.get_mut(&chunk_location) {
+            Some(chunk) => {
+                chunk.last_used = now;
+                match &chunk.tiles {
+                    Some(tiles) => {
+                        let x = location.x - chunk_location.x * self.chunk_size.width as i32;
+                        let y = location.y - chunk_location.y * self.chunk_size.height as i32;
+                        let sprite = tiles[(x + y * self.chunk_size.width as i32) as usize].clone();
+                        // Chunks loaded ahead of time load their own
+                        // neighbors once they are drawn.
+                        let request_neighbors = !chunk.neighbors_requested;
+                        chunk.neighbors_requested = true;
+                        (sprite, request_neighbors)
+                    }
+                    None => (None, false),
+                }
+            }
+            None => {
+                self.request_chunk(&mut data, chunk_location);
+                data.chunks
+                    .get_mut(&chunk_location)
+                    .unwrap()
+                    .neighbors_requested = true;
+                (None, true)
+            }
+        };
+
+        if request_neighbors {
+            self.request_neighbors(&mut data, chunk_location);
+        }
+
+        sprite.map(|sprite| Tile {
+            location,
+            sprite,
+            transform: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sprite::SpriteSource, texture::Texture};
+    use image::{DynamicImage, RgbaImage};
+    use std::time::Duration;
+
+    async fn wait_for_chunk(provider: &ChunkedTileProvider, chunk: Point<i32>) {
+        for _ in 0..200 {
+            if provider.is_chunk_loaded(chunk).await {
+                return;
+            }
+            smol::Timer::after(Duration::from_millis(5)).await;
+        }
+        panic!("chunk {:?} never loaded", chunk);
+    }
+
+    async fn wait_for_chunks(provider: &ChunkedTileProvider, min: Point<i32>, max: Point<i32>) {
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                wait_for_chunk(provider, Point::new(x, y)).await;
+            }
+        }
+    }
+
+    async fn test_provider(
+        max_loaded_chunks: usize,
+        preload_radius: u32,
+    ) -> (ChunkedTileProvider, Handle<Vec<Point<i32>>>) {
+        Runtime::initialize_headless();
+        let requests = Handle::new(Vec::new());
+        let sprite = TileSprite::from(
+            SpriteSource::entire_texture(Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(
+                1, 1,
+            ))))
+            .await,
+        );
+        let loader_requests = requests.clone();
+        let provider = ChunkedTileProvider::new(
+            Size::new(2, 2),
+            move |chunk: Point<i32>, size: Size<u32>| {
+                let requests = loader_requests.clone();
+                let sprite = sprite.clone();
+                async move {
+                    requests.write().await.push(chunk);
+                    if chunk.x < 0 {
+                        return Err(KludgineError::OutOfBounds);
+                    }
+                    // Only the first tile of each chunk is filled in
+                    let mut tiles = vec![None; (size.width * size.height) as usize];
+                    tiles[0] = Some(sprite);
+                    Ok(tiles)
+                }
+            },
+        )
+        .with_max_loaded_chunks(max_loaded_chunks)
+        .with_preload_radius(preload_radius);
+        (provider, requests)
+    }
+
+    #[async_test]
+    async fn streaming_test() -> KludgineResult<()> {
+        let (provider, requests) = test_provider(64, 1).await;
+        assert_eq!(
+            provider.chunk_containing(Point::new(-1, 3)),
+            Point::new(-1, 1)
+        );
+
+        // Missing chunks are empty rather than waiting for the loader
+        assert!(provider.get_tile(Point::new(2, 2)).await.is_none());
+        wait_for_chunk(&provider, Point::new(1, 1)).await;
+        let tile = provider.get_tile(Point::new(2, 2)).await.unwrap();
+        assert_eq!(tile.location, Point::new(2, 2));
+        assert!(provider.get_tile(Point::new(3, 3)).await.is_none());
+
+        // The surrounding chunks were requested along with it
+        wait_for_chunks(&provider, Point::new(0, 0), Point::new(2, 2)).await;
+        assert_eq!(requests.read().await.len(), 9);
+        assert_eq!(provider.loaded_chunks().await.len(), 9);
+
+        // Drawing a preloaded chunk loads its neighbors
+        assert!(provider.get_tile(Point::new(4, 4)).await.is_some());
+        wait_for_chunks(&provider, Point::new(1, 1), Point::new(3, 3)).await;
+        assert_eq!(requests.read().await.len(), 14);
+
+        // Failed chunks are empty and report their errors
+        assert!(provider.get_tile(Point::new(-2, 2)).await.is_none());
+        wait_for_chunk(&provider, Point::new(-1, 1)).await;
+        assert!(provider.get_tile(Point::new(-2, 2)).await.is_none());
+        let errors = provider.take_load_errors().await;
+        assert!(!errors.is_empty());
+        assert!(errors.iter().all(|(chunk, _)| chunk.x < 0));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn eviction_test() -> KludgineResult<()> {
+        let (provider, requests) = test_provider(2, 0).await;
+        provider.get_tile(Point::new(0, 0)).await;
+        wait_for_chunk(&provider, Point::new(0, 0)).await;
+        provider.get_tile(Point::new(2, 0)).await;
+        wait_for_chunk(&provider, Point::new(1, 0)).await;
+
+        // Using chunk (0, 0) makes chunk (1, 0) the least recently used
+        assert!(provider.get_tile(Point::new(0, 0)).await.is_some());
+        provider.get_tile(Point::new(4, 0)).await;
+        wait_for_chunk(&provider, Point::new(2, 0)).await;
+        let mut loaded = provider.loaded_chunks().await;
+        loaded.sort_by_key(|chunk| chunk.x);
+        assert_eq!(loaded, vec![Point::new(0, 0), Point::new(2, 0)]);
+
+        // Evicted chunks are loaded again when needed
+        assert!(provider.get_tile(Point::new(2, 0)).await.is_none());
+        wait_for_chunk(&provider, Point::new(1, 0)).await;
+        assert_eq!(requests.read().await.len(), 4);
+
+        provider.unload_chunk(Point::new(1, 0)).await;
+        assert!(!provider.is_chunk_loaded(Point::new(1, 0)).await);
+        Ok(())
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{
+    math::{Point, Rect, Scale, Scaled, Size, Unknown, Vector},
+    scene::Target,
+    tilemap::{draw_tiles, TileDrawContext, TileGrid, TileProjection, TileProvider},
+    KludgineResult,
+};
+use std::ops::{Deref, DerefMut};
+
+/// A single layer within a [`LayeredTileMap`]
+#[derive(Debug)]
+pub struct TileLayer<P> {
+    provider: P,
+    /// How far the layer moves relative to the map's location. `(1, 1)` moves
+    /// with the map, `(0, 0)` stays in place, and values in between scroll
+    /// slower than the map, making the layer appear further away.
+    pub parallax: Vector<f32>,
+    /// An offset in the same units as the tile size, scaled along with the
+    /// tiles when drawn.
+    pub offset: Vector<f32>,
+    pub opacity: f32,
+    pub visible: bool,
+    /// Layers are drawn from the lowest z-order to the highest. Layers with the
+    /// same z-order are drawn in the order they were added.
+    pub z_order: i32,
+}
+
+impl<P> TileLayer<P> {
+    pub fn new(provider: P) -> Self {
+        Self {
+            provider,
+            parallax: Vector::new(1., 1.),
+            offset: Vector::zero(),
+            opacity: 1.,
+            visible: true,
+            z_order: 0,
+        }
+    }
+
+    pub fn with_parallax(mut self, parallax: Vector<f32>) -> Self {
+        self.parallax = parallax;
+        self
+    }
+
+    pub fn with_offset(mut self, offset: Vector<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+
+    pub fn with_visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    pub fn with_z_order(mut self, z_order: i32) -> Self {
+        self.z_order = z_order;
+        self
+    }
+
+    /// Returns where the layer's origin is drawn when its map is drawn at
+    /// `location`
+    pub fn draw_location(
+        &self,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Point<f32, Scaled> {
+        Point::new(location.x * self.parallax.x, location.y * self.parallax.y) + self.offset * scale
+    }
+}
+
+impl<P> Deref for TileLayer<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.provider
+    }
+}
+
+impl<P> DerefMut for TileLayer<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+}
+
+/// Renders a stack of [`TileLayer`]s that share a tile size, drawn back to
+/// front in a single call.
+#[derive(Debug)]
+pub struct LayeredTileMap<P> {
+    tile_size: Size<u32>,
+    stagger: Option<Size<u32>>,
+    projection: TileProjection,
+    sprite_overhang: Option<u32>,
+    layers: Vec<TileLayer<P>>,
+}
+
+impl<P> LayeredTileMap<P>
+where
+    P: TileProvider,
+{
+    pub fn new(tile_size: Size<u32>) -> Self {
+        Self {
+            tile_size,
+            stagger: None,
+            projection: TileProjection::default(),
+            sprite_overhang: None,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Sets the height of each row of an orthogonal map, allowing rows to
+    /// overlap. Use [`LayeredTileMap::set_projection`] for staggered layouts.
+    pub fn set_stagger(&mut self, stagger: Size<u32>) {
+        self.stagger = Some(stagger);
+    }
+
+    pub fn set_projection(&mut self, projection: TileProjection) {
+        self.projection = projection;
+    }
+
+    pub fn projection(&self) -> TileProjection {
+        self.projection
+    }
+
+    /// Sets how far sprites can extend above the top of their tiles on maps
+    /// that aren't orthogonal. See [`TileMap::set_sprite_overhang`].
+    pub fn set_sprite_overhang(&mut self, overhang: u32) {
+        self.sprite_overhang = Some(overhang);
+    }
+
+    /// Returns the tile containing `point`, in the map's unscaled coordinates
+    pub fn tile_at(&self, point: Point<f32>) -> Point<i32> {
+        self.grid().tile_at(point)
+    }
+
+    /// Returns the bounding box of `tile`, in the map's unscaled coordinates
+    pub fn tile_bounds(&self, tile: Point<i32>) -> Rect<f32> {
+        self.grid().tile_bounds(tile)
+    }
+
+    /// Adds a layer, returning its index
+    pub fn push_layer(&mut self, layer: TileLayer<P>) -> usize {
+        self.layers.push(layer);
+        self.layers.len() - 1
+    }
+
+    pub fn layers(&self) -> &[TileLayer<P>] {
+        &self.layers
+    }
+
+    pub fn layer(&self, index: usize) -> Option<&TileLayer<P>> {
+        self.layers.get(index)
+    }
+
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut TileLayer<P>> {
+        self.layers.get_mut(index)
+    }
+
+    /// Returns the tile of `layer` under the scene point `point` when the map
+    /// is drawn at `location` with `scale`, taking the layer's parallax and
+    /// offset into account.
+    pub fn scene_point_to_tile(
+        &self,
+        layer: usize,
+        point: Point<f32, Scaled>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Option<Point<i32>> {
+        let layer = self.layers.get(layer)?;
+        Some(
+            self.grid()
+                .scene_point_to_tile(point, layer.draw_location(location, scale), scale),
+        )
+    }
+
+    /// Returns the area of the scene covered by the bounding box of `tile` in
+    /// `layer` when the map is drawn at `location` with `scale`
+    pub fn tile_to_scene(
+        &self,
+        layer: usize,
+        tile: Point<i32>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Option<Rect<f32, Scaled>> {
+        let layer = self.layers.get(layer)?;
+        Some(
+            self.grid()
+                .tile_to_scene(tile, layer.draw_location(location, scale), scale),
+        )
+    }
+
+    pub async fn draw(&self, scene: &Target, location: Point<f32, Scaled>) -> KludgineResult<()> {
+        self.draw_scaled(scene, location, Scale::identity()).await
+    }
+
+    /// Draws every visible layer. `location` is where the map's origin is
+    /// drawn for layers without parallax.
+    pub async fn draw_scaled(
+        &self,
+        scene: &Target,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> KludgineResult<()> {
+        let context = TileDrawContext::new(scene).await;
+        let grid = self.grid();
+
+        let mut layers = self
+            .layers
+            .iter()
+            .filter(|layer| layer.visible && layer.opacity > 0.)
+            .collect::<Vec<_>>();
+        // sort_by_key is stable, preserving the order layers were added in
+        layers.sort_by_key(|layer| layer.z_order);
+
+        for layer in layers {
+            let location = layer.draw_location(location, scale);
+            draw_tiles(
+                &layer.provider,
+                scene,
+                &context,
+                &grid,
+                location,
+                scale,
+                layer.opacity,
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    fn grid(&self) -> TileGrid {
+        let grid = TileGrid::new(self.tile_size, self.stagger, self.projection);
+        match self.sprite_overhang {
+            Some(overhang) => grid.with_sprite_overhang(overhang),
+            None => grid,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::ScreenScale, scene::Scene, sprite::SpriteSource, style::theme::Minimal,
+        texture::Texture, tilemap::PersistentTileProvider, window::SoftwareRenderer,
+    };
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    async fn solid_tile(color: [u8; 4]) -> SpriteSource {
+        SpriteSource::entire_texture(Texture::new(DynamicImage::ImageRgba8(
+            RgbaImage::from_pixel(1, 1, Rgba(color)),
+        )))
+        .await
+    }
+
+    async fn filled_layer(color: [u8; 4]) -> TileLayer<PersistentTileProvider> {
+        let tile = solid_tile(color).await;
+        TileLayer::new(PersistentTileProvider::new(
+            Size::new(8, 8),
+            vec![Some(tile); 64],
+        ))
+    }
+
+    #[async_test]
+    async fn layered_draw_test() -> KludgineResult<()> {
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(8., 8.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+
+        let mut map = LayeredTileMap::new(Size::new(2, 2));
+        // Added first, but drawn last due to its z-order
+        let mut foreground = PersistentTileProvider::blank(Size::new(1, 1));
+        foreground.set(Point::new(0, 0), Some(solid_tile([0, 0, 255, 255]).await));
+        map.push_layer(
+            TileLayer::new(foreground)
+                .with_z_order(1)
+                .with_parallax(Vector::new(0.5, 0.5))
+                .with_offset(Vector::new(0., 1.)),
+        );
+        map.push_layer(filled_layer([255, 0, 0, 255]).await);
+        let hidden = map.push_layer(filled_layer([0, 255, 0, 255]).await.with_z_order(2));
+        map.layer_mut(hidden).unwrap().visible = false;
+        map.push_layer(
+            filled_layer([255, 255, 255, 255])
+                .await
+                .with_z_order(1)
+                .with_opacity(0.),
+        );
+
+        map.draw_scaled(&target, Point::new(-4., -4.), Scale::new(2.))
+            .await?;
+        let image = SoftwareRenderer::default().render(&target).await?;
+
+        // The foreground's tile is 4x4, drawn at half of the map's location
+        // plus its scaled offset: (-2, 0)
+        assert_eq!(
+            map.tile_to_scene(0, Point::new(0, 0), Point::new(-4., -4.), Scale::new(2.)),
+            Some(Rect::new(Point::new(-2., 0.), Size::new(4., 4.)))
+        );
+        assert_eq!(
+            map.scene_point_to_tile(0, Point::new(2.5, 0.), Point::new(-4., -4.), Scale::new(2.)),
+            Some(Point::new(1, 0))
+        );
+        assert_eq!(
+            map.scene_point_to_tile(1, Point::new(2.5, 0.), Point::new(-4., -4.), Scale::new(2.)),
+            Some(Point::new(1, 1))
+        );
+        assert_eq!(image.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(image.get_pixel(1, 3), &Rgba([0, 0, 255, 255]));
+        assert_eq!(image.get_pixel(2, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(0, 4), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(7, 7), &Rgba([255, 0, 0, 255]));
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
-    math::{Box2D, Length, Point, PointExt, Raw, Scale, Scaled, Size, SizeExt, Unknown},
+    math::{Point, Rect, Scale, Scaled, ScreenScale, Size, Unknown},
     scene::Target,
-    sprite::{Sprite, SpriteRotation, SpriteSource},
+    sprite::{Sprite, SpriteRotation, SpriteSource, SpriteTint, SpriteTransform},
     KludgineResult,
 };
 use async_trait::async_trait;
@@ -10,6 +10,21 @@ use std::{
     ops::{Deref, DerefMut},
     time::Duration,
 };
+mod autotile;
+mod chunked;
+mod layered;
+mod projection;
+mod serialization;
+mod tiled;
+pub(crate) use self::projection::TileGrid;
+pub use self::{
+    autotile::*,
+    chunked::*,
+    layered::*,
+    projection::{StaggerAxis, StaggerIndex, TileProjection},
+    serialization::*,
+    tiled::*,
+};
 
 /// TileMap renders tiles retrieved from a TileProvider
 #[derive(Debug)]
@@ -17,6 +32,8 @@ pub struct TileMap<P> {
     provider: P,
     tile_size: Size<u32>,
     stagger: Option<Size<u32>>,
+    projection: TileProjection,
+    sprite_overhang: Option<u32>,
 }
 
 impl<P> TileMap<P>
@@ -28,13 +45,64 @@ where
             tile_size,
             provider,
             stagger: None,
+            projection: TileProjection::default(),
+            sprite_overhang: None,
         }
     }
 
+    /// Sets the height of each row of an orthogonal map, allowing rows to
+    /// overlap. Use [`TileMap::set_projection`] for staggered layouts.
     pub fn set_stagger(&mut self, stagger: Size<u32>) {
         self.stagger = Some(stagger);
     }
 
+    pub fn set_projection(&mut self, projection: TileProjection) {
+        self.projection = projection;
+    }
+
+    pub fn projection(&self) -> TileProjection {
+        self.projection
+    }
+
+    /// Sets how far sprites can extend above the top of their tiles on maps
+    /// that aren't orthogonal, so that tiles below the visible area whose
+    /// sprites reach into it are still drawn. Defaults to the tile height.
+    pub fn set_sprite_overhang(&mut self, overhang: u32) {
+        self.sprite_overhang = Some(overhang);
+    }
+
+    /// Returns the tile containing `point`, in the map's unscaled coordinates
+    pub fn tile_at(&self, point: Point<f32>) -> Point<i32> {
+        self.grid().tile_at(point)
+    }
+
+    /// Returns the bounding box of `tile`, in the map's unscaled coordinates
+    pub fn tile_bounds(&self, tile: Point<i32>) -> Rect<f32> {
+        self.grid().tile_bounds(tile)
+    }
+
+    /// Returns the tile under the scene point `point` when the map is drawn
+    /// at `location` with `scale`, as passed to [`TileMap::draw_scaled`]
+    pub fn scene_point_to_tile(
+        &self,
+        point: Point<f32, Scaled>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Point<i32> {
+        self.grid().scene_point_to_tile(point, location, scale)
+    }
+
+    /// Returns the area of the scene covered by `tile`'s bounding box when the
+    /// map is drawn at `location` with `scale`
+    pub fn tile_to_scene(
+        &self,
+        tile: Point<i32>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Rect<f32, Scaled> {
+        self.grid().tile_to_scene(tile, location, scale)
+    }
+
     pub async fn draw(&self, scene: &Target, location: Point<f32, Scaled>) -> KludgineResult<()> {
         self.draw_scaled(scene, location, Scale::identity()).await
     }
@@ -45,76 +113,98 @@ where
         location: Point<f32, Scaled>,
         scale: Scale<f32, Unknown, Scaled>,
     ) -> KludgineResult<()> {
-        let tile_height = if let Some(stagger) = &self.stagger {
-            stagger.height
-        } else {
-            self.tile_size.height
-        };
-        let tile_size = Size::<u32>::new(self.tile_size.width, tile_height).cast::<f32>() * scale;
-
-        // We need to start at the upper-left of inverting the location
-        let min_x = (-location.x / tile_size.width).floor() as i32;
-        let min_y = (-location.y / tile_size.height).floor() as i32;
-        let extra_x = tile_size.width() - Length::new(1.);
-        let extra_y = tile_size.height() - Length::new(1.);
-        let scene_size = scene.size().await;
-        let total_width = scene_size.width() + extra_x;
-        let total_height = scene_size.height() + extra_y;
-        let tiles_wide = (total_width / tile_size.width as f32).get().ceil() as i32;
-        let tiles_high = (total_height / tile_size.height as f32).get().ceil() as i32;
-
-        let elapsed = scene.elapsed().await;
-
-        let mut render_calls = Vec::new();
-        let effective_scale = scene.scale_factor().await;
-        let tile_size = tile_size * effective_scale;
-        let location = location * effective_scale;
-        let mut y_pos = tile_size.height() * min_y as f32 + location.y();
-        for y in min_y..(min_y + tiles_high) {
-            let mut x_pos = tile_size.width() * min_x as f32 + location.x();
-            let next_y = y_pos + tile_size.height();
-            for x in min_x..(min_x + tiles_wide) {
-                let next_x = x_pos + tile_size.width();
-                render_calls.push(
-                    self.draw_one_tile(
-                        Point::new(x, y),
-                        Box2D::new(
-                            Point::from_lengths(x_pos, y_pos),
-                            Point::from_lengths(next_x, next_y),
-                        )
-                        .round(),
-                        scene,
-                        elapsed,
-                    ),
-                );
-                x_pos = next_x;
-            }
-            y_pos = next_y;
+        let context = TileDrawContext::new(scene).await;
+        draw_tiles(
+            &self.provider,
+            scene,
+            &context,
+            &self.grid(),
+            location,
+            scale,
+            1.,
+        )
+        .await
+    }
+
+    fn grid(&self) -> TileGrid {
+        let grid = TileGrid::new(self.tile_size, self.stagger, self.projection);
+        match self.sprite_overhang {
+            Some(overhang) => grid.with_sprite_overhang(overhang),
+            None => grid,
         }
+    }
+}
 
-        let _ = futures::future::join_all(render_calls)
-            .await
-            .into_iter()
-            .collect::<Result<_, _>>()?;
+/// Scene information shared by every layer drawn in a frame
+struct TileDrawContext {
+    scene_size: Size<f32, Scaled>,
+    effective_scale: ScreenScale,
+    elapsed: Option<Duration>,
+}
 
-        Ok(())
+impl TileDrawContext {
+    async fn new(scene: &Target) -> Self {
+        Self {
+            scene_size: scene.size().await,
+            effective_scale: scene.scale_factor().await,
+            elapsed: scene.elapsed().await,
+        }
     }
+}
 
-    async fn draw_one_tile(
-        &self,
-        tile: Point<i32>,
-        destination: Box2D<f32, Raw>,
-        scene: &Target,
-        elapsed: Option<Duration>,
-    ) -> KludgineResult<()> {
-        if let Some(tile) = self.provider.get_tile(tile).await {
-            let sprite = tile.sprite.get_frame(elapsed).await?;
-            sprite
-                .render_raw_with_alpha_in_box(scene, destination, SpriteRotation::default(), 1.)
-                .await;
+async fn draw_tiles<P: TileProvider>(
+    provider: &P,
+    scene: &Target,
+    context: &TileDrawContext,
+    grid: &TileGrid,
+    location: Point<f32, Scaled>,
+    scale: Scale<f32, Unknown, Scaled>,
+    alpha: f32,
+) -> KludgineResult<()> {
+    // The area of the map that is visible within the scene
+    let view = Rect::new(Point::origin() - location.to_vector(), context.scene_size) / scale;
+    let tiles = futures::future::join_all(grid.visible_tiles(view).into_iter().map(
+        |tile_location| async move {
+            provider
+                .get_tile(tile_location)
+                .await
+                .map(|tile| (tile_location, tile))
+        },
+    ))
+    .await;
+
+    // Tiles are rendered in order, so that overlapping tiles are drawn back to
+    // front
+    for (tile_location, tile) in tiles.into_iter().flatten() {
+        let sprite = tile.sprite.get_frame(context.elapsed).await?;
+        let bounds = (grid.sprite_bounds(tile_location, sprite.size()) * scale)
+            .translate(location.to_vector())
+            * context.effective_scale;
+        match tile.transform {
+            Some(transform) => {
+                sprite
+                    .render_raw_transformed_in_box(
+                        scene,
+                        bounds.to_box2d().round(),
+                        transform,
+                        SpriteTint::default().with_alpha(alpha),
+                    )
+                    .await
+            }
+            None => {
+                sprite
+                    .render_raw_with_alpha_in_box(
+                        scene,
+                        bounds.to_box2d().round(),
+                        SpriteRotation::default(),
+                        alpha,
+                    )
+                    .await
+            }
         }
-        Ok(())
     }
+
+    Ok(())
 }
 
 /// TileProvider is how a TileMap retrieves tiles to render
@@ -123,6 +213,16 @@ pub trait TileProvider {
     async fn get_tile(&self, location: Point<i32>) -> Option<Tile>;
 }
 
+#[async_trait]
+impl<T> TileProvider for Box<T>
+where
+    T: TileProvider + Send + Sync + ?Sized,
+{
+    async fn get_tile(&self, location: Point<i32>) -> Option<Tile> {
+        self.as_ref().get_tile(location).await
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TileSprite {
     Sprite(Sprite),
@@ -155,6 +255,8 @@ impl TileSprite {
 pub struct Tile {
     pub location: Point<i32>,
     pub sprite: TileSprite,
+    /// Flips or rotates the sprite within the tile
+    pub transform: Option<SpriteTransform>,
 }
 
 /// Provides a simple interface for tile maps that have specific bounds
@@ -203,6 +305,7 @@ impl PersistentTileProvider {
                     Tile {
                         location: Point::new(index - y * dimensions.width, y),
                         sprite: sprite.into(),
+                        transform: None,
                     }
                 })
             })
@@ -221,10 +324,19 @@ impl PersistentTileProvider {
             sprite.map(|sprite| Tile {
                 location: Point::new(location.x as i32, location.y as i32),
                 sprite: sprite.into(),
+                transform: None,
             }),
         )
     }
 
+    /// Sets the transform of the tile at `location`, if there is one
+    pub fn set_transform(&mut self, location: Point<u32>, transform: Option<SpriteTransform>) {
+        let index = self.point_to_index(location);
+        if let Some(tile) = &mut self.tiles[index] {
+            tile.transform = transform;
+        }
+    }
+
     fn point_to_index(&self, location: Point<u32>) -> usize {
         (location.x + location.y * self.dimensions.width) as usize
     }
@@ -268,3 +380,60 @@ impl<T> DerefMut for TileMap<T> {
         &mut self.provider
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picking_test() {
+        let location = Point::new(10., 20.);
+        let scale = Scale::new(2.);
+        let mut map = PersistentTileMap::persistent_with_size(Size::new(16, 16), Size::new(4, 4));
+        assert_eq!(
+            map.scene_point_to_tile(Point::new(41., 51.), location, scale),
+            Point::new(0, 0)
+        );
+        assert_eq!(
+            map.scene_point_to_tile(Point::new(42., 52.), location, scale),
+            Point::new(1, 1)
+        );
+        assert_eq!(
+            map.scene_point_to_tile(Point::new(9., 19.), location, scale),
+            Point::new(-1, -1)
+        );
+        assert_eq!(
+            map.tile_to_scene(Point::new(1, 1), location, scale),
+            Rect::new(Point::new(42., 52.), Size::new(32., 32.))
+        );
+
+        // Staggered rows are half as tall
+        map.set_stagger(Size::new(8, 8));
+        assert_eq!(
+            map.scene_point_to_tile(Point::new(42., 52.), location, scale),
+            Point::new(1, 2)
+        );
+        assert_eq!(
+            map.tile_to_scene(Point::new(1, 2), location, scale),
+            Rect::new(Point::new(42., 52.), Size::new(32., 16.))
+        );
+
+        for &projection in &[
+            TileProjection::Isometric,
+            TileProjection::Hexagonal {
+                axis: StaggerAxis::Columns,
+                index: StaggerIndex::Even,
+                side_length: 8,
+            },
+        ] {
+            map.set_projection(projection);
+            for y in -2..3 {
+                for x in -2..3 {
+                    let tile = Point::new(x, y);
+                    let center = map.tile_to_scene(tile, location, scale).center();
+                    assert_eq!(map.scene_point_to_tile(center, location, scale), tile);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,463 @@
+use crate::math::{Point, Rect, Scale, Scaled, Size, Unknown, Vector};
+use std::cmp::Ordering;
+
+/// How a tile map's tile coordinates are laid out on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileProjection {
+    /// Tiles are laid out in rows and columns
+    #[default]
+    Orthogonal,
+    /// Diamond isometric. The x axis runs down and to the right, and the y axis
+    /// runs down and to the left. The bounding box of tile `(0, 0)` starts at
+    /// the origin.
+    Isometric,
+    /// Staggered isometric. Every other row or column of diamond-shaped tiles
+    /// is shifted by half a tile, so the map fills a rectangle.
+    Staggered {
+        axis: StaggerAxis,
+        index: StaggerIndex,
+    },
+    /// Hexagonal tiles, with every other row or column shifted by half a tile.
+    /// `side_length` is the length of the sides parallel to the stagger axis:
+    /// the vertical sides of pointy-topped hexagons or the horizontal sides of
+    /// flat-topped hexagons.
+    Hexagonal {
+        axis: StaggerAxis,
+        index: StaggerIndex,
+        side_length: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaggerAxis {
+    /// Every other row is shifted to the right. Hexagons are pointy-topped.
+    Rows,
+    /// Every other column is shifted down. Hexagons are flat-topped.
+    Columns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StaggerIndex {
+    /// Odd rows or columns are shifted
+    Odd,
+    /// Even rows or columns are shifted
+    Even,
+}
+
+impl StaggerIndex {
+    fn is_shifted(self, line: i32) -> bool {
+        let odd = line.rem_euclid(2) == 1;
+        match self {
+            StaggerIndex::Odd => odd,
+            StaggerIndex::Even => !odd,
+        }
+    }
+}
+
+/// The geometry of a tile map, in the same units as its tile size
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TileGrid {
+    tile_size: Size<f32>,
+    /// The row height of orthogonal maps, which `TileMap::set_stagger`
+    /// overrides
+    row_height: f32,
+    projection: TileProjection,
+    /// How far sprites can extend above the top of their tiles
+    sprite_overhang: f32,
+}
+
+impl TileGrid {
+    pub fn new(
+        tile_size: Size<u32>,
+        stagger: Option<Size<u32>>,
+        projection: TileProjection,
+    ) -> Self {
+        let row_height = stagger.map_or(tile_size.height, |stagger| stagger.height);
+        Self {
+            tile_size: tile_size.cast(),
+            row_height: row_height as f32,
+            projection,
+            sprite_overhang: tile_size.height as f32,
+        }
+    }
+
+    /// Sets how far sprites can extend above the top of their tiles on maps
+    /// that aren't orthogonal. Defaults to the tile height.
+    pub fn with_sprite_overhang(mut self, overhang: u32) -> Self {
+        self.sprite_overhang = overhang as f32;
+        self
+    }
+
+    /// Returns the bounding box of `tile`
+    pub fn tile_bounds(&self, tile: Point<i32>) -> Rect<f32> {
+        let Size { width, height, .. } = self.tile_size;
+        let x = tile.x as f32;
+        let y = tile.y as f32;
+        let origin = match self.projection {
+            TileProjection::Orthogonal => {
+                return Rect::new(
+                    Point::new(x * width, y * self.row_height),
+                    Size::new(width, self.row_height),
+                )
+            }
+            TileProjection::Isometric => Point::new((x - y) * width / 2., (x + y) * height / 2.),
+            TileProjection::Staggered { axis, index } => {
+                self.staggered_origin(tile, axis, index, 0.)
+            }
+            TileProjection::Hexagonal {
+                axis,
+                index,
+                side_length,
+            } => self.staggered_origin(tile, axis, index, side_length as f32),
+        };
+        Rect::new(origin, self.tile_size)
+    }
+
+    fn staggered_origin(
+        &self,
+        tile: Point<i32>,
+        axis: StaggerAxis,
+        index: StaggerIndex,
+        side_length: f32,
+    ) -> Point<f32> {
+        let Size { width, height, .. } = self.tile_size;
+        match axis {
+            StaggerAxis::Rows => {
+                let shift = if index.is_shifted(tile.y) {
+                    width / 2.
+                } else {
+                    0.
+                };
+                Point::new(
+                    tile.x as f32 * width + shift,
+                    tile.y as f32 * (height + side_length) / 2.,
+                )
+            }
+            StaggerAxis::Columns => {
+                let shift = if index.is_shifted(tile.x) {
+                    height / 2.
+                } else {
+                    0.
+                };
+                Point::new(
+                    tile.x as f32 * (width + side_length) / 2.,
+                    tile.y as f32 * height + shift,
+                )
+            }
+        }
+    }
+
+    /// Returns the tile under `point` when the map is drawn at `location`
+    /// with `scale`
+    pub fn scene_point_to_tile(
+        &self,
+        point: Point<f32, Scaled>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Point<i32> {
+        self.tile_at((point - location.to_vector()) / scale)
+    }
+
+    /// Returns the area of the scene `tile`'s bounding box covers when the map
+    /// is drawn at `location` with `scale`
+    pub fn tile_to_scene(
+        &self,
+        tile: Point<i32>,
+        location: Point<f32, Scaled>,
+        scale: Scale<f32, Unknown, Scaled>,
+    ) -> Rect<f32, Scaled> {
+        (self.tile_bounds(tile) * scale).translate(location.to_vector())
+    }
+
+    /// Returns where a sprite is drawn for `tile`. Orthogonal maps stretch
+    /// sprites to fill their tile. Other projections draw sprites at their own
+    /// size, aligned to the bottom-left of the tile, so sprites taller than
+    /// their tile overlap the tiles behind them.
+    pub fn sprite_bounds(&self, tile: Point<i32>, sprite_size: Size<u32>) -> Rect<f32> {
+        let bounds = self.tile_bounds(tile);
+        if self.projection == TileProjection::Orthogonal {
+            return bounds;
+        }
+
+        let size = sprite_size.cast::<f32>();
+        Rect::new(
+            Point::new(bounds.min_x(), bounds.max_y() - size.height),
+            size,
+        )
+    }
+
+    /// Returns the tile whose shape contains `point`
+    pub fn tile_at(&self, point: Point<f32>) -> Point<i32> {
+        let Size { width, height, .. } = self.tile_size;
+        match self.projection {
+            TileProjection::Orthogonal => Point::new(
+                (point.x / width).floor() as i32,
+                (point.y / self.row_height).floor() as i32,
+            ),
+            TileProjection::Isometric => {
+                // Measured from the top corner of tile (0, 0)
+                let across = (point.x - width / 2.) / width;
+                let down = point.y / height;
+                Point::new(
+                    (down + across).floor() as i32,
+                    (down - across).floor() as i32,
+                )
+            }
+            TileProjection::Staggered { axis, index } => {
+                self.staggered_tile_at(point, axis, index, 0.)
+            }
+            TileProjection::Hexagonal {
+                axis,
+                index,
+                side_length,
+            } => self.staggered_tile_at(point, axis, index, side_length as f32),
+        }
+    }
+
+    fn staggered_tile_at(
+        &self,
+        point: Point<f32>,
+        axis: StaggerAxis,
+        index: StaggerIndex,
+        side_length: f32,
+    ) -> Point<i32> {
+        let Size { width, height, .. } = self.tile_size;
+        // Find the row or column the point is in, ignoring the slanted edges,
+        // then pick whichever neighboring tile's shape contains the point.
+        let nearby = match axis {
+            StaggerAxis::Rows => {
+                let row = (point.y / ((height + side_length) / 2.)).floor() as i32;
+                let shift = if index.is_shifted(row) {
+                    width / 2.
+                } else {
+                    0.
+                };
+                Point::new(((point.x - shift) / width).floor() as i32, row)
+            }
+            StaggerAxis::Columns => {
+                let column = (point.x / ((width + side_length) / 2.)).floor() as i32;
+                let shift = if index.is_shifted(column) {
+                    height / 2.
+                } else {
+                    0.
+                };
+                Point::new(column, ((point.y - shift) / height).floor() as i32)
+            }
+        };
+
+        let mut closest = nearby;
+        let mut closest_distance = f32::MAX;
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let tile = nearby + Vector::new(x, y);
+                let distance = self.shape_distance(tile, point, axis, side_length);
+                if distance < closest_distance {
+                    closest = tile;
+                    closest_distance = distance;
+                }
+            }
+        }
+        closest
+    }
+
+    /// Returns how far `point` is from the center of `tile`'s hexagon, scaled
+    /// so that the hexagon's edges are at 1. A side length of 0 describes a
+    /// diamond.
+    fn shape_distance(
+        &self,
+        tile: Point<i32>,
+        point: Point<f32>,
+        axis: StaggerAxis,
+        side_length: f32,
+    ) -> f32 {
+        let Size { width, height, .. } = self.tile_size;
+        let offset = point - self.tile_bounds(tile).center();
+        let dx = offset.x.abs();
+        let dy = offset.y.abs();
+        match axis {
+            StaggerAxis::Rows => {
+                (dx / (width / 2.)).max((dy + dx * (height - side_length) / width) / (height / 2.))
+            }
+            StaggerAxis::Columns => {
+                (dy / (height / 2.)).max((dx + dy * (width - side_length) / height) / (width / 2.))
+            }
+        }
+    }
+
+    /// Returns every tile that overlaps `view`, in the order they should be
+    /// drawn
+    pub fn visible_tiles(&self, view: Rect<f32>) -> Vec<Point<i32>> {
+        let view = if self.projection == TileProjection::Orthogonal {
+            view
+        } else {
+            // Include the tiles below the view whose sprites may extend up
+            // into it
+            Rect::new(
+                view.origin,
+                Size::new(view.width(), view.height() + self.sprite_overhang),
+            )
+        };
+        let corners = [
+            self.tile_at(view.min()),
+            self.tile_at(Point::new(view.max_x(), view.min_y())),
+            self.tile_at(Point::new(view.min_x(), view.max_y())),
+            self.tile_at(view.max()),
+        ];
+        // Staggered tiles overlap their neighbors' bounding boxes, so include
+        // an extra tile in each direction before culling.
+        let min_x = corners.iter().map(|tile| tile.x).min().unwrap() - 1;
+        let max_x = corners.iter().map(|tile| tile.x).max().unwrap() + 1;
+        let min_y = corners.iter().map(|tile| tile.y).min().unwrap() - 1;
+        let max_y = corners.iter().map(|tile| tile.y).max().unwrap() + 1;
+
+        let mut tiles = Vec::new();
+        for y in min_y..=max_y {
+            for x in min_x..=max_x {
+                let tile = Point::new(x, y);
+                if self.tile_bounds(tile).intersects(&view) {
+                    tiles.push(tile);
+                }
+            }
+        }
+
+        if self.projection != TileProjection::Orthogonal {
+            // Tiles lower on the screen are in front of the tiles above them
+            tiles.sort_by(|a, b| {
+                let a = self.tile_bounds(*a).origin;
+                let b = self.tile_bounds(*b).origin;
+                (a.y, a.x)
+                    .partial_cmp(&(b.y, b.x))
+                    .unwrap_or(Ordering::Equal)
+            });
+        }
+        tiles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn projections() -> Vec<TileProjection> {
+        let mut projections = vec![TileProjection::Orthogonal, TileProjection::Isometric];
+        for &axis in &[StaggerAxis::Rows, StaggerAxis::Columns] {
+            for &index in &[StaggerIndex::Odd, StaggerIndex::Even] {
+                projections.push(TileProjection::Staggered { axis, index });
+                projections.push(TileProjection::Hexagonal {
+                    axis,
+                    index,
+                    side_length: 12,
+                });
+            }
+        }
+        projections
+    }
+
+    #[test]
+    fn round_trip_test() {
+        for projection in projections() {
+            let grid = TileGrid::new(Size::new(32, 24), None, projection);
+            for y in -3..4 {
+                for x in -3..4 {
+                    let tile = Point::new(x, y);
+                    let center = grid.tile_bounds(tile).center();
+                    assert_eq!(grid.tile_at(center), tile, "{:?}", projection);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn isometric_test() {
+        let grid = TileGrid::new(Size::new(64, 32), None, TileProjection::Isometric);
+        assert_eq!(
+            grid.tile_bounds(Point::new(1, 0)).origin,
+            Point::new(32., 16.)
+        );
+        assert_eq!(
+            grid.tile_bounds(Point::new(0, 1)).origin,
+            Point::new(-32., 16.)
+        );
+        // The corners of tile (0, 0)'s bounding box belong to its neighbors
+        assert_eq!(grid.tile_at(Point::new(1., 1.)), Point::new(-1, 0));
+        assert_eq!(grid.tile_at(Point::new(63., 31.)), Point::new(1, 0));
+        assert_eq!(grid.tile_at(Point::new(32., 16.)), Point::new(0, 0));
+
+        // Tile (0, 0), the corners of its four neighbors, and the tiles below
+        // whose sprites may reach into the view, from back to front
+        let tiles = grid.visible_tiles(Rect::new(Point::new(0., 0.), Size::new(64., 32.)));
+        assert_eq!(
+            tiles,
+            vec![
+                Point::new(-1, 0),
+                Point::new(0, -1),
+                Point::new(0, 0),
+                Point::new(0, 1),
+                Point::new(1, 0),
+                Point::new(1, 1),
+                Point::new(1, 2),
+                Point::new(2, 1)
+            ]
+        );
+        // Tall sprites are aligned to the bottom of their tile
+        assert_eq!(
+            grid.sprite_bounds(Point::new(0, 0), Size::new(64, 48)),
+            Rect::new(Point::new(0., -16.), Size::new(64., 48.))
+        );
+    }
+
+    #[test]
+    fn sprite_overhang_test() {
+        let view = Rect::new(Point::new(0., 0.), Size::new(64., 32.));
+        let grid = TileGrid::new(Size::new(64, 32), None, TileProjection::Isometric);
+        assert!(!grid.visible_tiles(view).contains(&Point::new(2, 2)));
+
+        // Sprites three tiles tall reach up from two rows further down
+        let grid = grid.with_sprite_overhang(64);
+        let tiles = grid.visible_tiles(view);
+        assert!(tiles.contains(&Point::new(2, 2)));
+        assert!(!tiles.contains(&Point::new(3, 3)));
+
+        // Orthogonal maps stretch sprites to their tiles
+        let grid = TileGrid::new(Size::new(64, 32), None, TileProjection::Orthogonal)
+            .with_sprite_overhang(64);
+        assert_eq!(grid.visible_tiles(view), vec![Point::new(0, 0)]);
+    }
+
+    #[test]
+    fn hexagonal_test() {
+        let grid = TileGrid::new(
+            Size::new(28, 32),
+            None,
+            TileProjection::Hexagonal {
+                axis: StaggerAxis::Rows,
+                index: StaggerIndex::Odd,
+                side_length: 16,
+            },
+        );
+        // Rows are 24 apart, and odd rows are shifted by half a tile
+        assert_eq!(
+            grid.tile_bounds(Point::new(0, 1)).origin,
+            Point::new(14., 24.)
+        );
+        assert_eq!(
+            grid.tile_bounds(Point::new(1, 2)).origin,
+            Point::new(28., 48.)
+        );
+        // Rows overlap, so points in row 1's band can still be in row 0
+        assert_eq!(grid.tile_at(Point::new(20., 26.)), Point::new(0, 0));
+        assert_eq!(grid.tile_at(Point::new(28., 30.)), Point::new(0, 1));
+
+        let tiles = grid.visible_tiles(Rect::new(Point::new(0., 0.), Size::new(56., 48.)));
+        for y in 0..2 {
+            for x in 0..2 {
+                assert!(tiles.contains(&Point::new(x, y)));
+            }
+        }
+        // Rows are drawn top to bottom
+        let rows = tiles.iter().map(|tile| tile.y).collect::<Vec<_>>();
+        let mut sorted = rows.clone();
+        sorted.sort_unstable();
+        assert_eq!(rows, sorted);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    math::{Rect, Size},
+    sprite::{Sprite, SpriteAnimations, SpriteTransform},
+    tilemap::{PersistentTileProvider, TileSprite},
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, hash::Hash};
+
+/// The contents of a [`PersistentTileProvider`], stored as tile ids instead
+/// of sprites. Consecutive tiles with the same id and transform are stored as
+/// a single [`TileRun`], keeping large areas of the same tile small on disk.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileMapData<T> {
+    pub width: u32,
+    pub height: u32,
+    /// The tiles in row order
+    pub runs: Vec<TileRun<T>>,
+}
+
+/// `count` consecutive tiles, or empty spaces if `tile` is None
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileRun<T> {
+    pub count: u32,
+    pub tile: Option<T>,
+    /// The transform of each tile in the run
+    pub transform: Option<SpriteTransform>,
+}
+
+impl<T> TileMapData<T> {
+    pub fn size(&self) -> Size<u32> {
+        Size::new(self.width, self.height)
+    }
+
+    /// Returns every tile in row order
+    pub fn tiles(&self) -> impl Iterator<Item = Option<&T>> {
+        self.runs
+            .iter()
+            .flat_map(|run| (0..run.count).map(move |_| run.tile.as_ref()))
+    }
+
+    /// Returns every tile's transform in row order
+    pub fn transforms(&self) -> impl Iterator<Item = Option<SpriteTransform>> + '_ {
+        self.runs
+            .iter()
+            .flat_map(|run| (0..run.count).map(move |_| run.transform))
+    }
+}
+
+/// Maps tile ids to the sprites they represent, and sprites back to their
+/// ids. Sprite sources are identified by their texture and location, and
+/// animated sprites by their animations, so every instance created with
+/// [`Sprite::new_instance`] shares the id of the sprite it was created from.
+#[derive(Debug, Clone)]
+pub struct TilePalette<T> {
+    sprites: HashMap<T, TileSprite>,
+    source_ids: HashMap<(u64, Rect<u32>), T>,
+    animated_ids: Vec<(Sprite, T)>,
+}
+
+impl<T> Default for TilePalette<T> {
+    fn default() -> Self {
+        Self {
+            sprites: HashMap::new(),
+            source_ids: HashMap::new(),
+            animated_ids: Vec::new(),
+        }
+    }
+}
+
+impl<T> TilePalette<T>
+where
+    T: Clone + Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<S: Into<TileSprite>>(&mut self, id: T, sprite: S) {
+        let sprite = sprite.into();
+        match &sprite {
+            TileSprite::SpriteSource(source) => {
+                self.source_ids
+                    .insert((source.texture.id, source.location.bounds()), id.clone());
+            }
+            TileSprite::Sprite(sprite) => self.animated_ids.push((sprite.clone(), id.clone())),
+        }
+        self.sprites.insert(id, sprite);
+    }
+
+    pub fn sprite(&self, id: &T) -> Option<&TileSprite> {
+        self.sprites.get(id)
+    }
+
+    pub async fn id_of(&self, sprite: &TileSprite) -> Option<&T> {
+        match sprite {
+            TileSprite::SpriteSource(source) => self
+                .source_ids
+                .get(&(source.texture.id, source.location.bounds())),
+            TileSprite::Sprite(sprite) => {
+                let animations = sprite.animations().await;
+                for (existing, id) in &self.animated_ids {
+                    if SpriteAnimations::ptr_eq(&existing.animations().await, &animations) {
+                        return Some(id);
+                    }
+                }
+                None
+            }
+        }
+    }
+}
+
+impl<T, S> From<HashMap<T, S>> for TilePalette<T>
+where
+    T: Clone + Eq + Hash,
+    S: Into<TileSprite>,
+{
+    /// Creates a palette from a map of sprites, such as the one returned from
+    /// [`SpriteSheet::all_sprites`](crate::sprite::SpriteSheet::all_sprites)
+    fn from(sprites: HashMap<T, S>) -> Self {
+        let mut palette = Self::default();
+        for (id, sprite) in sprites {
+            palette.insert(id, sprite);
+        }
+        palette
+    }
+}
+
+impl PersistentTileProvider {
+    /// Returns the map's tiles as ids from `palette`. Tiles whose sprites
+    /// aren't in the palette are stored as empty.
+    pub async fn to_data<T>(&self, palette: &TilePalette<T>) -> TileMapData<T>
+    where
+        T: Clone + Eq + Hash,
+    {
+        let mut runs = Vec::<TileRun<T>>::new();
+        for tile in &self.tiles {
+            let (id, transform) = match tile {
+                Some(tile) => match palette.id_of(&tile.sprite).await {
+                    Some(id) => (Some(id.clone()), tile.transform),
+                    None => (None, None),
+                },
+                None => (None, None),
+            };
+            match runs.last_mut() {
+                Some(run) if run.tile == id && run.transform == transform => run.count += 1,
+                _ => runs.push(TileRun {
+                    count: 1,
+                    tile: id,
+                    transform,
+                }),
+            }
+        }
+
+        TileMapData {
+            width: self.dimensions.width,
+            height: self.dimensions.height,
+            runs,
+        }
+    }
+
+    /// Creates a map from `data`, looking up each tile's sprite in `palette`.
+    /// Ids that aren't in the palette become empty tiles, as do any tiles
+    /// missing from the end of `data`. Each animated tile is given its own
+    /// instance of its sprite.
+    pub async fn from_data<T>(data: &TileMapData<T>, palette: &TilePalette<T>) -> Self
+    where
+        T: Clone + Eq + Hash,
+    {
+        let size = data.size();
+        let mut tiles = Vec::with_capacity((size.width * size.height) as usize);
+        let mut transforms = Vec::with_capacity(tiles.capacity());
+        for (id, transform) in data
+            .tiles()
+            .zip(data.transforms())
+            .take((size.width * size.height) as usize)
+        {
+            tiles.push(match id.and_then(|id| palette.sprite(id)) {
+                Some(TileSprite::Sprite(sprite)) => {
+                    Some(TileSprite::Sprite(sprite.new_instance().await))
+                }
+                other => other.cloned(),
+            });
+            transforms.push(transform);
+        }
+        tiles.resize((size.width * size.height) as usize, None);
+
+        let mut provider = Self::new(size, tiles);
+        for (tile, transform) in provider.tiles.iter_mut().zip(transforms) {
+            if let Some(tile) = tile {
+                tile.transform = transform;
+            }
+        }
+        provider
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::Point,
+        sprite::{SpriteSheet, SpriteSource},
+        texture::Texture,
+        tilemap::TileProvider,
+        Handle,
+    };
+    use image::{DynamicImage, RgbaImage};
+
+    #[async_test]
+    async fn round_trip_test() {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(4, 2)));
+        let sheet =
+            SpriteSheet::new(texture.clone(), Size::new(2, 2), vec!["grass", "water"]).await;
+        let sprites = sheet.all_sprites().await;
+        let palette = TilePalette::from(sprites.clone());
+
+        let mut provider = PersistentTileProvider::blank(Size::new(3, 2));
+        provider.set(Point::new(0, 0), Some(sprites["grass"].clone()));
+        provider.set(Point::new(1, 0), Some(sprites["grass"].clone()));
+        provider.set(Point::new(2, 0), Some(sprites["water"].clone()));
+        let flipped = SpriteTransform::default().flipped_x();
+        provider.set_transform(Point::new(1, 0), Some(flipped));
+        // An equivalent sprite source is recognized by its location
+        provider.set(
+            Point::new(0, 1),
+            Some(SpriteSource::new(
+                Rect::new(Point::new(2, 0), Size::new(2, 2)),
+                texture,
+            )),
+        );
+
+        let data = provider.to_data(&palette).await;
+        assert_eq!(
+            data,
+            TileMapData {
+                width: 3,
+                height: 2,
+                runs: vec![
+                    // Tiles with different transforms are stored in separate runs
+                    TileRun {
+                        count: 1,
+                        tile: Some("grass"),
+                        transform: None,
+                    },
+                    TileRun {
+                        count: 1,
+                        tile: Some("grass"),
+                        transform: Some(flipped),
+                    },
+                    TileRun {
+                        count: 2,
+                        tile: Some("water"),
+                        transform: None,
+                    },
+                    TileRun {
+                        count: 2,
+                        tile: None,
+                        transform: None,
+                    },
+                ],
+            }
+        );
+
+        let loaded = PersistentTileProvider::from_data(&data, &palette).await;
+        for y in 0..2 {
+            for x in 0..3 {
+                let location = Point::new(x, y);
+                let original = match provider.get_tile(location).await {
+                    Some(tile) => {
+                        Some((palette.id_of(&tile.sprite).await.cloned(), tile.transform))
+                    }
+                    None => None,
+                };
+                let loaded = match loaded.get_tile(location).await {
+                    Some(tile) => {
+                        Some((palette.id_of(&tile.sprite).await.cloned(), tile.transform))
+                    }
+                    None => None,
+                };
+                assert_eq!(original, loaded);
+            }
+        }
+    }
+
+    #[async_test]
+    async fn animated_round_trip_test() {
+        let texture = Texture::new(DynamicImage::ImageRgba8(RgbaImage::new(2, 2)));
+        let sprite = Sprite::single_frame(texture).await;
+        let mut palette = TilePalette::new();
+        palette.insert("torch", sprite.clone());
+
+        // Each tile is its own instance, which is still recognized
+        let mut provider = PersistentTileProvider::blank(Size::new(2, 1));
+        provider.set(Point::new(0, 0), Some(sprite.new_instance().await));
+        provider.set(Point::new(1, 0), Some(sprite.new_instance().await));
+        let data = provider.to_data(&palette).await;
+        assert_eq!(
+            data.runs,
+            vec![TileRun {
+                count: 2,
+                tile: Some("torch"),
+                transform: None,
+            }]
+        );
+
+        let loaded = PersistentTileProvider::from_data(&data, &palette).await;
+        let mut handles = Vec::new();
+        for x in 0..2 {
+            let tile = loaded.get_tile(Point::new(x, 0)).await.unwrap();
+            assert_eq!(palette.id_of(&tile.sprite).await, Some(&"torch"));
+            match tile.sprite {
+                TileSprite::Sprite(sprite) => handles.push(sprite.handle),
+                TileSprite::SpriteSource(_) => unreachable!(),
+            }
+        }
+        // Loaded tiles don't share their animation state
+        assert!(!Handle::ptr_eq(&handles[0], &handles[1]));
+        assert!(!Handle::ptr_eq(&handles[0], &sprite.handle));
+    }
+}
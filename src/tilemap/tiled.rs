@@ -0,0 +1,1727 @@
+use crate::{
+    color::Color,
+    math::{Angle, Point, Size, Vector},
+    sprite::{
+        sheet_dimensions, AnimationMode, Sprite, SpriteAnimation, SpriteAnimations, SpriteFrame,
+        SpriteSheet, SpriteSource, SpriteTransform,
+    },
+    texture::Texture,
+    tilemap::{
+        LayeredTileMap, PersistentTileMap, PersistentTileProvider, StaggerAxis, StaggerIndex,
+        TileLayer, TileMap, TileProjection, TileSprite,
+    },
+    KludgineError, KludgineResult,
+};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use json::JsonValue;
+use roxmltree::{Document, Node};
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    io::Read,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+const FLIPPED_HORIZONTALLY: u32 = 0x8000_0000;
+const FLIPPED_VERTICALLY: u32 = 0x4000_0000;
+const FLIPPED_DIAGONALLY: u32 = 0x2000_0000;
+const ROTATED_HEXAGONAL_120: u32 = 0x1000_0000;
+const GID_MASK: u32 =
+    !(FLIPPED_HORIZONTALLY | FLIPPED_VERTICALLY | FLIPPED_DIAGONALLY | ROTATED_HEXAGONAL_120);
+
+/// A map authored in [Tiled](https://www.mapeditor.org/), loaded from either
+/// the XML (`.tmx`) or JSON (`.tmj`) format along with the tilesets and images
+/// it references.
+#[derive(Debug, Clone)]
+pub struct TiledMap {
+    pub orientation: TiledOrientation,
+    /// The projection matching the map's orientation and stagger settings
+    pub projection: TileProjection,
+    /// The size of the map, in number of tiles
+    pub size: Size<u32>,
+    pub tile_size: Size<u32>,
+    pub background_color: Option<Color>,
+    pub properties: TiledProperties,
+    /// The map's layers from bottom to top. Group layers are flattened, with
+    /// their offset, parallax, opacity and visibility applied to the layers
+    /// within them.
+    pub layers: Vec<TiledLayer>,
+    /// The map's tilesets, ordered by `first_gid`
+    pub tilesets: Vec<TiledTileset>,
+}
+
+impl TiledMap {
+    /// Loads a map from a `.tmx` file, or from a `.tmj`/`.json` file.
+    pub async fn load<P: AsRef<Path>>(path: P) -> KludgineResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(anyhow::Error::from)?;
+        let directory = parent_directory(path);
+        if path.extension() == Some(OsStr::new("tmx")) {
+            Self::parse_tmx(&contents, directory).await
+        } else {
+            Self::parse_tmj(&contents, directory).await
+        }
+    }
+
+    /// Parses a map in the XML format. Tilesets and images are loaded relative
+    /// to `directory`.
+    pub async fn parse_tmx<P: AsRef<Path>>(tmx: &str, directory: P) -> KludgineResult<Self> {
+        parse_tmx_map(tmx, directory.as_ref())?.load().await
+    }
+
+    /// Parses a map in the JSON format. Tilesets and images are loaded relative
+    /// to `directory`.
+    pub async fn parse_tmj<P: AsRef<Path>>(tmj: &str, directory: P) -> KludgineResult<Self> {
+        parse_tmj_map(tmj, directory.as_ref())?.load().await
+    }
+
+    /// Returns the first layer named `name`
+    pub fn layer(&self, name: &str) -> Option<&TiledLayer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    /// Returns the objects from every object layer
+    pub fn objects(&self) -> impl Iterator<Item = &TiledObject> {
+        self.layers.iter().flat_map(|layer| layer.objects().iter())
+    }
+
+    /// Returns the first object named `name` in any object layer
+    pub fn object(&self, name: &str) -> Option<&TiledObject> {
+        self.objects().find(|object| object.name == name)
+    }
+
+    /// Returns the tileset that `gid` belongs to
+    pub fn tileset_for(&self, gid: u32) -> Option<&TiledTileset> {
+        self.tilesets
+            .iter()
+            .rev()
+            .find(|tileset| tileset.first_gid <= gid)
+    }
+
+    /// Returns the custom properties of the tile `gid`
+    pub fn tile_properties(&self, gid: u32) -> Option<&TiledProperties> {
+        let tileset = self.tileset_for(gid)?;
+        tileset.tile_properties.get(&(gid - tileset.first_gid))
+    }
+
+    /// Returns the sprite for the tile `gid`. Animated tiles return a new
+    /// instance of their animation each time.
+    pub async fn tile_sprite(&self, gid: u32) -> Option<TileSprite> {
+        let tileset = self.tileset_for(gid)?;
+        match tileset.sprites.get(&(gid - tileset.first_gid))? {
+            TileSprite::Sprite(sprite) => Some(TileSprite::Sprite(sprite.new_instance().await)),
+            source => Some(source.clone()),
+        }
+    }
+
+    /// Creates a tile provider containing the tiles from `layer`
+    pub async fn tile_provider(&self, layer: &TiledTileLayer) -> PersistentTileProvider {
+        let mut tiles = Vec::with_capacity(layer.tiles.len());
+        for tile in &layer.tiles {
+            tiles.push(match tile {
+                Some(tile) => self.tile_sprite(tile.gid).await,
+                None => None,
+            });
+        }
+
+        let mut provider = PersistentTileProvider::new(layer.size, tiles);
+        for (index, tile) in layer.tiles.iter().enumerate() {
+            if let Some(transform) = tile.and_then(|tile| tile.transform()) {
+                let width = layer.size.width as usize;
+                let location = Point::new((index % width) as u32, (index / width) as u32);
+                provider.set_transform(location, Some(transform));
+            }
+        }
+        provider
+    }
+
+    /// How far the tallest tileset's sprites extend above the map's tiles,
+    /// never less than a tile
+    fn sprite_overhang(&self) -> u32 {
+        self.tilesets
+            .iter()
+            .map(|tileset| {
+                tileset
+                    .tile_size
+                    .height
+                    .saturating_sub(self.tile_size.height)
+            })
+            .max()
+            .unwrap_or_default()
+            .max(self.tile_size.height)
+    }
+
+    /// Creates a tile map containing the tiles from `layer`
+    pub async fn tile_map(&self, layer: &TiledTileLayer) -> PersistentTileMap {
+        let mut map = TileMap::new(self.tile_size, self.tile_provider(layer).await);
+        map.set_projection(self.projection);
+        map.set_sprite_overhang(self.sprite_overhang());
+        map
+    }
+
+    /// Creates a tile map for each tile layer, paired with the layer's name
+    pub async fn tile_maps(&self) -> Vec<(String, PersistentTileMap)> {
+        let mut maps = Vec::new();
+        for layer in &self.layers {
+            if let Some(tiles) = layer.tiles() {
+                maps.push((layer.name.clone(), self.tile_map(tiles).await));
+            }
+        }
+        maps
+    }
+
+    /// Creates a layered tile map containing every tile layer, with the
+    /// offset, parallax, opacity and visibility set in Tiled
+    pub async fn layered_tile_map(&self) -> LayeredTileMap<PersistentTileProvider> {
+        let mut map = LayeredTileMap::new(self.tile_size);
+        map.set_projection(self.projection);
+        map.set_sprite_overhang(self.sprite_overhang());
+        for layer in &self.layers {
+            if let Some(tiles) = layer.tiles() {
+                map.push_layer(
+                    TileLayer::new(self.tile_provider(tiles).await)
+                        .with_offset(layer.offset)
+                        .with_parallax(layer.parallax)
+                        .with_opacity(layer.opacity)
+                        .with_visible(layer.visible),
+                );
+            }
+        }
+        map
+    }
+}
+
+/// The projection a [`TiledMap`] was authored with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiledOrientation {
+    Orthogonal,
+    Isometric,
+    Staggered,
+    Hexagonal,
+}
+
+impl FromStr for TiledOrientation {
+    type Err = KludgineError;
+
+    fn from_str(orientation: &str) -> KludgineResult<Self> {
+        match orientation {
+            "orthogonal" => Ok(Self::Orthogonal),
+            "isometric" => Ok(Self::Isometric),
+            "staggered" => Ok(Self::Staggered),
+            "hexagonal" => Ok(Self::Hexagonal),
+            other => Err(parse_error(format!("unknown orientation {}", other))),
+        }
+    }
+}
+
+/// A tileset referenced by a [`TiledMap`]
+#[derive(Debug, Clone)]
+pub struct TiledTileset {
+    /// The global id of the first tile in this tileset
+    pub first_gid: u32,
+    pub name: String,
+    pub tile_size: Size<u32>,
+    pub tile_count: u32,
+    pub properties: TiledProperties,
+    /// The custom properties of individual tiles, keyed by their id within the
+    /// tileset
+    pub tile_properties: HashMap<u32, TiledProperties>,
+    sprites: HashMap<u32, TileSprite>,
+}
+
+impl TiledTileset {
+    /// Returns the sprite for the tile `id` within this tileset
+    pub fn sprite(&self, id: u32) -> Option<&TileSprite> {
+        self.sprites.get(&id)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledLayer {
+    pub id: u32,
+    pub name: String,
+    /// The offset of the layer in pixels, including the offsets of any groups
+    /// containing it
+    pub offset: Vector<f32>,
+    /// How fast the layer scrolls relative to the map, including the parallax
+    /// of any groups containing it
+    pub parallax: Vector<f32>,
+    pub opacity: f32,
+    pub visible: bool,
+    pub properties: TiledProperties,
+    pub contents: TiledLayerContents,
+}
+
+impl TiledLayer {
+    pub fn tiles(&self) -> Option<&TiledTileLayer> {
+        match &self.contents {
+            TiledLayerContents::Tiles(tiles) => Some(tiles),
+            TiledLayerContents::Objects(_) => None,
+        }
+    }
+
+    /// Returns the objects in this layer, which is empty for tile layers
+    pub fn objects(&self) -> &[TiledObject] {
+        match &self.contents {
+            TiledLayerContents::Tiles(_) => &[],
+            TiledLayerContents::Objects(objects) => objects,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TiledLayerContents {
+    Tiles(TiledTileLayer),
+    Objects(Vec<TiledObject>),
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledTileLayer {
+    /// The size of the layer, in number of tiles
+    pub size: Size<u32>,
+    /// The layer's tiles, row by row
+    pub tiles: Vec<Option<TiledTile>>,
+}
+
+impl TiledTileLayer {
+    fn new(size: Size<u32>, gids: Vec<u32>) -> KludgineResult<Self> {
+        let tile_count = (size.width as usize)
+            .checked_mul(size.height as usize)
+            .ok_or_else(|| {
+                parse_error(format!(
+                    "layer is too large: {}x{}",
+                    size.width, size.height
+                ))
+            })?;
+        if gids.len() != tile_count {
+            return Err(parse_error(format!(
+                "layer has {} tiles but is {}x{}",
+                gids.len(),
+                size.width,
+                size.height
+            )));
+        }
+
+        Ok(Self {
+            size,
+            tiles: gids.into_iter().map(TiledTile::from_raw).collect(),
+        })
+    }
+
+    pub fn tile(&self, location: Point<u32>) -> Option<TiledTile> {
+        if location.x >= self.size.width || location.y >= self.size.height {
+            return None;
+        }
+
+        self.tiles[location.x as usize + location.y as usize * self.size.width as usize]
+    }
+}
+
+/// A placed tile, with the flip flags Tiled stores in the upper bits of its id
+/// separated out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TiledTile {
+    pub gid: u32,
+    pub flipped_horizontally: bool,
+    pub flipped_vertically: bool,
+    pub flipped_diagonally: bool,
+}
+
+impl TiledTile {
+    /// Decodes a raw global tile id. Returns None for empty tiles.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        let gid = raw & GID_MASK;
+        if gid == 0 {
+            return None;
+        }
+
+        Some(Self {
+            gid,
+            flipped_horizontally: raw & FLIPPED_HORIZONTALLY != 0,
+            flipped_vertically: raw & FLIPPED_VERTICALLY != 0,
+            flipped_diagonally: raw & FLIPPED_DIAGONALLY != 0,
+        })
+    }
+
+    /// Returns the transform that applies this tile's flips, or None if it
+    /// isn't flipped
+    pub fn transform(&self) -> Option<SpriteTransform> {
+        if self.flipped_diagonally {
+            // Tiled flips diagonally by swapping the axes before applying the
+            // other flips, which is a quarter turn of a vertically flipped tile
+            Some(SpriteTransform {
+                flip_x: self.flipped_vertically,
+                flip_y: !self.flipped_horizontally,
+                rotation: Angle::degrees(90.),
+                ..SpriteTransform::default()
+            })
+        } else if self.flipped_horizontally || self.flipped_vertically {
+            Some(SpriteTransform {
+                flip_x: self.flipped_horizontally,
+                flip_y: self.flipped_vertically,
+                ..SpriteTransform::default()
+            })
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledObject {
+    pub id: u32,
+    pub name: String,
+    /// The object's class, which older versions of Tiled call its type
+    pub class: String,
+    pub location: Point<f32>,
+    pub size: Size<f32>,
+    pub rotation: Angle,
+    pub visible: bool,
+    /// The tile displayed by tile objects
+    pub tile: Option<TiledTile>,
+    pub shape: TiledObjectShape,
+    pub properties: TiledProperties,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledObjectShape {
+    Rectangle,
+    Ellipse,
+    Point,
+    /// A closed polygon, with points relative to the object's location
+    Polygon(Vec<Point<f32>>),
+    /// An open polyline, with points relative to the object's location
+    Polyline(Vec<Point<f32>>),
+    Text(String),
+}
+
+pub type TiledProperties = HashMap<String, TiledProperty>;
+
+/// A custom property value
+#[derive(Debug, Clone, PartialEq)]
+pub enum TiledProperty {
+    String(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Color(Color),
+    /// A path, relative to the file the property was defined in
+    File(String),
+    /// The id of an object on the map
+    Object(u32),
+    Class(TiledProperties),
+}
+
+impl TiledProperty {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) | Self::File(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            Self::Object(value) => Some(i64::from(*value)),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(value) => Some(*value),
+            Self::Int(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Self::Color(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_class(&self) -> Option<&TiledProperties> {
+        match self {
+            Self::Class(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// A map that has been parsed but whose tilesets haven't been loaded yet
+struct MapDefinition {
+    orientation: TiledOrientation,
+    projection: TileProjection,
+    size: Size<u32>,
+    tile_size: Size<u32>,
+    background_color: Option<Color>,
+    properties: TiledProperties,
+    layers: Vec<TiledLayer>,
+    tilesets: Vec<TilesetDefinition>,
+}
+
+impl MapDefinition {
+    async fn load(self) -> KludgineResult<TiledMap> {
+        let mut tilesets = Vec::with_capacity(self.tilesets.len());
+        for tileset in self.tilesets {
+            tilesets.push(tileset.load().await?);
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        Ok(TiledMap {
+            orientation: self.orientation,
+            projection: self.projection,
+            size: self.size,
+            tile_size: self.tile_size,
+            background_color: self.background_color,
+            properties: self.properties,
+            layers: self.layers,
+            tilesets,
+        })
+    }
+}
+
+struct TilesetDefinition {
+    first_gid: u32,
+    name: String,
+    tile_size: Size<u32>,
+    tile_count: Option<u32>,
+    margin: u32,
+    spacing: u32,
+    image: Option<PathBuf>,
+    properties: TiledProperties,
+    tiles: HashMap<u32, TileDefinition>,
+}
+
+struct TileDefinition {
+    image: Option<PathBuf>,
+    properties: TiledProperties,
+    /// Pairs of tile ids and how long to display them for
+    animation: Vec<(u32, Duration)>,
+}
+
+impl TilesetDefinition {
+    async fn load(self) -> KludgineResult<TiledTileset> {
+        let mut sources = HashMap::new();
+        if let Some(image) = &self.image {
+            let texture = Texture::load(image)?;
+            let dimensions =
+                sheet_dimensions(texture.size(), self.tile_size, self.margin, self.spacing);
+            let capacity = dimensions.width * dimensions.height;
+            let tile_count = self.tile_count.unwrap_or(capacity).min(capacity);
+            let sheet = SpriteSheet::new_with_spacing(
+                texture,
+                self.tile_size,
+                self.margin,
+                self.spacing,
+                (0..tile_count).collect(),
+            )
+            .await;
+            sources.extend(sheet.all_sprites().await);
+        }
+
+        for (id, tile) in &self.tiles {
+            if let Some(image) = &tile.image {
+                sources.insert(
+                    *id,
+                    SpriteSource::entire_texture(Texture::load(image)?).await,
+                );
+            }
+        }
+
+        let mut sprites = HashMap::with_capacity(sources.len());
+        for (id, source) in &sources {
+            let animation = self
+                .tiles
+                .get(id)
+                .map(|tile| &tile.animation[..])
+                .unwrap_or_default();
+            let sprite = if animation.is_empty() {
+                TileSprite::SpriteSource(source.clone())
+            } else {
+                let frames = animation
+                    .iter()
+                    .map(|(frame, duration)| {
+                        let source = sources.get(frame).cloned().ok_or_else(|| {
+                            parse_error(format!(
+                                "animation frame {} is not in tileset {}",
+                                frame, self.name
+                            ))
+                        })?;
+                        Ok(SpriteFrame {
+                            source,
+                            duration: Some(*duration),
+                            pivot: None,
+                            events: Vec::new(),
+                        })
+                    })
+                    .collect::<KludgineResult<_>>()?;
+                let mut animations = HashMap::new();
+                animations.insert(None, SpriteAnimation::new(frames, AnimationMode::Forward));
+                TileSprite::Sprite(Sprite::new(None, SpriteAnimations::new(animations)))
+            };
+            sprites.insert(*id, sprite);
+        }
+
+        Ok(TiledTileset {
+            first_gid: self.first_gid,
+            name: self.name,
+            tile_size: self.tile_size,
+            tile_count: self.tile_count.unwrap_or(sources.len() as u32),
+            properties: self.properties,
+            tile_properties: self
+                .tiles
+                .into_iter()
+                .filter(|(_, tile)| !tile.properties.is_empty())
+                .map(|(id, tile)| (id, tile.properties))
+                .collect(),
+            sprites,
+        })
+    }
+}
+
+/// The offset, parallax, opacity and visibility that group layers apply to
+/// their children
+#[derive(Clone, Copy)]
+struct LayerInheritance {
+    offset: Vector<f32>,
+    parallax: Vector<f32>,
+    opacity: f32,
+    visible: bool,
+}
+
+impl Default for LayerInheritance {
+    fn default() -> Self {
+        Self {
+            offset: Vector::zero(),
+            parallax: Vector::new(1., 1.),
+            opacity: 1.,
+            visible: true,
+        }
+    }
+}
+
+impl LayerInheritance {
+    fn apply(
+        self,
+        offset: Vector<f32>,
+        parallax: Vector<f32>,
+        opacity: f32,
+        visible: bool,
+    ) -> Self {
+        Self {
+            offset: self.offset + offset,
+            parallax: self.parallax.component_mul(parallax),
+            opacity: self.opacity * opacity,
+            visible: self.visible && visible,
+        }
+    }
+
+    fn layer(
+        self,
+        id: u32,
+        name: String,
+        properties: TiledProperties,
+        contents: TiledLayerContents,
+    ) -> TiledLayer {
+        TiledLayer {
+            id,
+            name,
+            offset: self.offset,
+            parallax: self.parallax,
+            opacity: self.opacity,
+            visible: self.visible,
+            properties,
+            contents,
+        }
+    }
+}
+
+fn parse_error<S: Into<String>>(message: S) -> KludgineError {
+    KludgineError::TiledParseError(message.into())
+}
+
+fn parent_directory(path: &Path) -> &Path {
+    path.parent().unwrap_or_else(|| Path::new(""))
+}
+
+fn load_external_tileset(first_gid: u32, path: &Path) -> KludgineResult<TilesetDefinition> {
+    let contents = std::fs::read_to_string(path).map_err(anyhow::Error::from)?;
+    let directory = parent_directory(path);
+    if path.extension() == Some(OsStr::new("tsx")) {
+        let document = Document::parse(&contents).map_err(|err| parse_error(err.to_string()))?;
+        parse_tmx_tileset(first_gid, document.root_element(), directory)
+    } else {
+        parse_tmj_tileset(first_gid, &json::parse(&contents)?, directory)
+    }
+}
+
+fn tile_projection(
+    orientation: TiledOrientation,
+    stagger_axis: Option<&str>,
+    stagger_index: Option<&str>,
+    side_length: Option<u32>,
+) -> KludgineResult<TileProjection> {
+    let axis = match stagger_axis.unwrap_or("y") {
+        "y" => StaggerAxis::Rows,
+        "x" => StaggerAxis::Columns,
+        other => return Err(parse_error(format!("unknown stagger axis {}", other))),
+    };
+    let index = match stagger_index.unwrap_or("odd") {
+        "odd" => StaggerIndex::Odd,
+        "even" => StaggerIndex::Even,
+        other => return Err(parse_error(format!("unknown stagger index {}", other))),
+    };
+
+    Ok(match orientation {
+        TiledOrientation::Orthogonal => TileProjection::Orthogonal,
+        TiledOrientation::Isometric => TileProjection::Isometric,
+        TiledOrientation::Staggered => TileProjection::Staggered { axis, index },
+        TiledOrientation::Hexagonal => TileProjection::Hexagonal {
+            axis,
+            index,
+            side_length: side_length.unwrap_or_default(),
+        },
+    })
+}
+
+/// Parses a color in Tiled's `#AARRGGBB` or `#RRGGBB` format. Empty colors are
+/// transparent.
+fn parse_color(color: &str) -> KludgineResult<Color> {
+    let hex = color.trim_start_matches('#');
+    if hex.is_empty() {
+        return Ok(Color::new(0., 0., 0., 0.));
+    }
+
+    let value = u32::from_str_radix(hex, 16)
+        .map_err(|_| parse_error(format!("invalid color {}", color)))?;
+    let channel = |shift: u32| ((value >> shift) & 0xFF) as f32 / 255.;
+    match hex.len() {
+        6 => Ok(Color::new(channel(16), channel(8), channel(0), 1.)),
+        8 => Ok(Color::new(channel(16), channel(8), channel(0), channel(24))),
+        _ => Err(parse_error(format!("invalid color {}", color))),
+    }
+}
+
+fn parse_property(kind: &str, value: &str) -> KludgineResult<TiledProperty> {
+    let invalid = || parse_error(format!("invalid {} property value {}", kind, value));
+    Ok(match kind {
+        "string" => TiledProperty::String(value.to_owned()),
+        "int" => TiledProperty::Int(value.parse().map_err(|_| invalid())?),
+        "float" => TiledProperty::Float(value.parse().map_err(|_| invalid())?),
+        "bool" => TiledProperty::Bool(value.parse().map_err(|_| invalid())?),
+        "color" => TiledProperty::Color(parse_color(value)?),
+        "file" => TiledProperty::File(value.to_owned()),
+        "object" => TiledProperty::Object(value.parse().map_err(|_| invalid())?),
+        other => return Err(parse_error(format!("unknown property type {}", other))),
+    })
+}
+
+/// Decodes the tile ids of a layer stored as csv or as base64 encoded
+/// little-endian integers, optionally compressed.
+fn decode_tile_data(
+    data: &str,
+    encoding: &str,
+    compression: Option<&str>,
+) -> KludgineResult<Vec<u32>> {
+    match encoding {
+        "csv" => data
+            .split(',')
+            .map(str::trim)
+            .filter(|gid| !gid.is_empty())
+            .map(|gid| {
+                gid.parse()
+                    .map_err(|_| parse_error(format!("invalid tile id {}", gid)))
+            })
+            .collect(),
+        "base64" => {
+            let bytes = base64::decode(data.trim())
+                .map_err(|err| parse_error(format!("invalid base64 tile data: {}", err)))?;
+            let bytes = match compression {
+                None | Some("") => bytes,
+                Some("zlib") => decompress(ZlibDecoder::new(&bytes[..]))?,
+                Some("gzip") => decompress(GzDecoder::new(&bytes[..]))?,
+                Some(other) => {
+                    return Err(parse_error(format!(
+                        "unsupported tile data compression {}",
+                        other
+                    )))
+                }
+            };
+            if bytes.len() % 4 != 0 {
+                return Err(parse_error("tile data is not a whole number of tiles"));
+            }
+
+            Ok(bytes
+                .chunks_exact(4)
+                .map(|gid| u32::from_le_bytes([gid[0], gid[1], gid[2], gid[3]]))
+                .collect())
+        }
+        other => Err(parse_error(format!(
+            "unsupported tile data encoding {}",
+            other
+        ))),
+    }
+}
+
+fn decompress<R: Read>(mut reader: R) -> KludgineResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|err| parse_error(format!("invalid compressed tile data: {}", err)))?;
+    Ok(bytes)
+}
+
+fn parse_points(points: &str) -> KludgineResult<Vec<Point<f32>>> {
+    points
+        .split_whitespace()
+        .map(|point| {
+            let mut coordinates = point.split(',').map(str::parse::<f32>);
+            match (coordinates.next(), coordinates.next(), coordinates.next()) {
+                (Some(Ok(x)), Some(Ok(y)), None) => Ok(Point::new(x, y)),
+                _ => Err(parse_error(format!("invalid point {}", point))),
+            }
+        })
+        .collect()
+}
+
+fn xml_attribute<T: FromStr>(node: Node<'_, '_>, name: &str) -> KludgineResult<Option<T>> {
+    node.attribute(name)
+        .map(|value| {
+            value.parse().map_err(|_| {
+                parse_error(format!(
+                    "invalid {} on <{}>: {}",
+                    name,
+                    node.tag_name().name(),
+                    value
+                ))
+            })
+        })
+        .transpose()
+}
+
+fn required_xml_attribute<T: FromStr>(node: Node<'_, '_>, name: &str) -> KludgineResult<T> {
+    xml_attribute(node, name)?
+        .ok_or_else(|| parse_error(format!("<{}> is missing {}", node.tag_name().name(), name)))
+}
+
+fn xml_child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(name))
+}
+
+fn parse_tmx_map(tmx: &str, directory: &Path) -> KludgineResult<MapDefinition> {
+    let document = Document::parse(tmx).map_err(|err| parse_error(err.to_string()))?;
+    let map = document.root_element();
+    if !map.has_tag_name("map") {
+        return Err(parse_error("expected a <map> element"));
+    }
+    if xml_attribute::<u8>(map, "infinite")?.unwrap_or(0) != 0 {
+        return Err(parse_error("infinite maps are not supported"));
+    }
+
+    let mut tilesets = Vec::new();
+    for tileset in map.children().filter(|node| node.has_tag_name("tileset")) {
+        let first_gid = required_xml_attribute(tileset, "firstgid")?;
+        tilesets.push(match tileset.attribute("source") {
+            Some(source) => load_external_tileset(first_gid, &directory.join(source))?,
+            None => parse_tmx_tileset(first_gid, tileset, directory)?,
+        });
+    }
+
+    let mut layers = Vec::new();
+    parse_tmx_layers(map, LayerInheritance::default(), &mut layers)?;
+
+    let orientation = map
+        .attribute("orientation")
+        .unwrap_or("orthogonal")
+        .parse()?;
+    Ok(MapDefinition {
+        orientation,
+        projection: tile_projection(
+            orientation,
+            map.attribute("staggeraxis"),
+            map.attribute("staggerindex"),
+            xml_attribute(map, "hexsidelength")?,
+        )?,
+        size: Size::new(
+            required_xml_attribute(map, "width")?,
+            required_xml_attribute(map, "height")?,
+        ),
+        tile_size: Size::new(
+            required_xml_attribute(map, "tilewidth")?,
+            required_xml_attribute(map, "tileheight")?,
+        ),
+        background_color: map
+            .attribute("backgroundcolor")
+            .map(parse_color)
+            .transpose()?,
+        properties: parse_tmx_properties(map)?,
+        layers,
+        tilesets,
+    })
+}
+
+fn parse_tmx_tileset(
+    first_gid: u32,
+    tileset: Node<'_, '_>,
+    directory: &Path,
+) -> KludgineResult<TilesetDefinition> {
+    let image_path = |node: Node<'_, '_>| -> KludgineResult<Option<PathBuf>> {
+        xml_child(node, "image")
+            .map(|image| {
+                required_xml_attribute::<String>(image, "source")
+                    .map(|source| directory.join(source))
+            })
+            .transpose()
+    };
+
+    let mut tiles = HashMap::new();
+    for tile in tileset.children().filter(|node| node.has_tag_name("tile")) {
+        let animation = match xml_child(tile, "animation") {
+            Some(animation) => animation
+                .children()
+                .filter(|node| node.has_tag_name("frame"))
+                .map(|frame| {
+                    Ok((
+                        required_xml_attribute(frame, "tileid")?,
+                        Duration::from_millis(required_xml_attribute(frame, "duration")?),
+                    ))
+                })
+                .collect::<KludgineResult<_>>()?,
+            None => Vec::new(),
+        };
+        tiles.insert(
+            required_xml_attribute(tile, "id")?,
+            TileDefinition {
+                image: image_path(tile)?,
+                properties: parse_tmx_properties(tile)?,
+                animation,
+            },
+        );
+    }
+
+    Ok(TilesetDefinition {
+        first_gid,
+        name: tileset.attribute("name").unwrap_or_default().to_owned(),
+        tile_size: Size::new(
+            required_xml_attribute(tileset, "tilewidth")?,
+            required_xml_attribute(tileset, "tileheight")?,
+        ),
+        tile_count: xml_attribute(tileset, "tilecount")?,
+        margin: xml_attribute(tileset, "margin")?.unwrap_or_default(),
+        spacing: xml_attribute(tileset, "spacing")?.unwrap_or_default(),
+        image: image_path(tileset)?,
+        properties: parse_tmx_properties(tileset)?,
+        tiles,
+    })
+}
+
+fn parse_tmx_layers(
+    parent: Node<'_, '_>,
+    inherited: LayerInheritance,
+    layers: &mut Vec<TiledLayer>,
+) -> KludgineResult<()> {
+    for node in parent.children().filter(|node| node.is_element()) {
+        let kind = node.tag_name().name();
+        if !matches!(kind, "layer" | "objectgroup" | "group") {
+            continue;
+        }
+
+        let layer = inherited.apply(
+            Vector::new(
+                xml_attribute(node, "offsetx")?.unwrap_or_default(),
+                xml_attribute(node, "offsety")?.unwrap_or_default(),
+            ),
+            Vector::new(
+                xml_attribute(node, "parallaxx")?.unwrap_or(1.),
+                xml_attribute(node, "parallaxy")?.unwrap_or(1.),
+            ),
+            xml_attribute(node, "opacity")?.unwrap_or(1.),
+            xml_attribute::<u8>(node, "visible")?.unwrap_or(1) != 0,
+        );
+        let contents = match kind {
+            "group" => {
+                parse_tmx_layers(node, layer, layers)?;
+                continue;
+            }
+            "layer" => TiledLayerContents::Tiles(parse_tmx_tile_layer(node)?),
+            _ => TiledLayerContents::Objects(
+                node.children()
+                    .filter(|child| child.has_tag_name("object"))
+                    .map(parse_tmx_object)
+                    .collect::<KludgineResult<_>>()?,
+            ),
+        };
+
+        layers.push(layer.layer(
+            xml_attribute(node, "id")?.unwrap_or_default(),
+            node.attribute("name").unwrap_or_default().to_owned(),
+            parse_tmx_properties(node)?,
+            contents,
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_tmx_tile_layer(layer: Node<'_, '_>) -> KludgineResult<TiledTileLayer> {
+    let data = xml_child(layer, "data").ok_or_else(|| parse_error("<layer> is missing <data>"))?;
+    let gids = match data.attribute("encoding") {
+        Some(encoding) => decode_tile_data(
+            data.text().unwrap_or_default(),
+            encoding,
+            data.attribute("compression"),
+        )?,
+        None => data
+            .children()
+            .filter(|node| node.has_tag_name("tile"))
+            .map(|tile| Ok(xml_attribute(tile, "gid")?.unwrap_or_default()))
+            .collect::<KludgineResult<_>>()?,
+    };
+
+    TiledTileLayer::new(
+        Size::new(
+            required_xml_attribute(layer, "width")?,
+            required_xml_attribute(layer, "height")?,
+        ),
+        gids,
+    )
+}
+
+fn parse_tmx_object(object: Node<'_, '_>) -> KludgineResult<TiledObject> {
+    let mut shape = TiledObjectShape::Rectangle;
+    for child in object.children().filter(|node| node.is_element()) {
+        shape = match child.tag_name().name() {
+            "ellipse" => TiledObjectShape::Ellipse,
+            "point" => TiledObjectShape::Point,
+            "polygon" => TiledObjectShape::Polygon(parse_points(
+                &required_xml_attribute::<String>(child, "points")?,
+            )?),
+            "polyline" => {
+                TiledObjectShape::Polyline(parse_points(&required_xml_attribute::<String>(
+                    child, "points",
+                )?)?)
+            }
+            "text" => TiledObjectShape::Text(child.text().unwrap_or_default().to_owned()),
+            _ => continue,
+        };
+    }
+
+    Ok(TiledObject {
+        id: xml_attribute(object, "id")?.unwrap_or_default(),
+        name: object.attribute("name").unwrap_or_default().to_owned(),
+        class: object
+            .attribute("class")
+            .or_else(|| object.attribute("type"))
+            .unwrap_or_default()
+            .to_owned(),
+        location: Point::new(
+            xml_attribute(object, "x")?.unwrap_or_default(),
+            xml_attribute(object, "y")?.unwrap_or_default(),
+        ),
+        size: Size::new(
+            xml_attribute(object, "width")?.unwrap_or_default(),
+            xml_attribute(object, "height")?.unwrap_or_default(),
+        ),
+        rotation: Angle::degrees(xml_attribute(object, "rotation")?.unwrap_or_default()),
+        visible: xml_attribute::<u8>(object, "visible")?.unwrap_or(1) != 0,
+        tile: xml_attribute(object, "gid")?.and_then(TiledTile::from_raw),
+        shape,
+        properties: parse_tmx_properties(object)?,
+    })
+}
+
+fn parse_tmx_properties(node: Node<'_, '_>) -> KludgineResult<TiledProperties> {
+    let mut properties = HashMap::new();
+    if let Some(list) = xml_child(node, "properties") {
+        for property in list.children().filter(|node| node.has_tag_name("property")) {
+            let value = match property.attribute("type").unwrap_or("string") {
+                "class" => TiledProperty::Class(parse_tmx_properties(property)?),
+                kind => parse_property(
+                    kind,
+                    // Multi-line strings are stored as the element's text
+                    property
+                        .attribute("value")
+                        .or_else(|| property.text())
+                        .unwrap_or_default(),
+                )?,
+            };
+            properties.insert(required_xml_attribute(property, "name")?, value);
+        }
+    }
+    Ok(properties)
+}
+
+fn json_u32(value: &JsonValue, name: &str) -> KludgineResult<u32> {
+    value[name]
+        .as_u32()
+        .ok_or_else(|| parse_error(format!("{} is missing or invalid", name)))
+}
+
+fn parse_tmj_map(tmj: &str, directory: &Path) -> KludgineResult<MapDefinition> {
+    let map = json::parse(tmj)?;
+    if map["infinite"].as_bool().unwrap_or_default() {
+        return Err(parse_error("infinite maps are not supported"));
+    }
+
+    let mut tilesets = Vec::new();
+    for tileset in map["tilesets"].members() {
+        let first_gid = json_u32(tileset, "firstgid")?;
+        tilesets.push(match tileset["source"].as_str() {
+            Some(source) => load_external_tileset(first_gid, &directory.join(source))?,
+            None => parse_tmj_tileset(first_gid, tileset, directory)?,
+        });
+    }
+
+    let mut layers = Vec::new();
+    parse_tmj_layers(&map["layers"], LayerInheritance::default(), &mut layers)?;
+
+    let orientation = map["orientation"]
+        .as_str()
+        .unwrap_or("orthogonal")
+        .parse()?;
+    Ok(MapDefinition {
+        orientation,
+        projection: tile_projection(
+            orientation,
+            map["staggeraxis"].as_str(),
+            map["staggerindex"].as_str(),
+            map["hexsidelength"].as_u32(),
+        )?,
+        size: Size::new(json_u32(&map, "width")?, json_u32(&map, "height")?),
+        tile_size: Size::new(json_u32(&map, "tilewidth")?, json_u32(&map, "tileheight")?),
+        background_color: map["backgroundcolor"]
+            .as_str()
+            .map(parse_color)
+            .transpose()?,
+        properties: parse_tmj_properties(&map["properties"])?,
+        layers,
+        tilesets,
+    })
+}
+
+fn parse_tmj_tileset(
+    first_gid: u32,
+    tileset: &JsonValue,
+    directory: &Path,
+) -> KludgineResult<TilesetDefinition> {
+    let mut tiles = HashMap::new();
+    for tile in tileset["tiles"].members() {
+        let animation = tile["animation"]
+            .members()
+            .map(|frame| {
+                Ok((
+                    json_u32(frame, "tileid")?,
+                    Duration::from_millis(json_u32(frame, "duration")?.into()),
+                ))
+            })
+            .collect::<KludgineResult<_>>()?;
+        tiles.insert(
+            json_u32(tile, "id")?,
+            TileDefinition {
+                image: tile["image"].as_str().map(|image| directory.join(image)),
+                properties: parse_tmj_properties(&tile["properties"])?,
+                animation,
+            },
+        );
+    }
+
+    Ok(TilesetDefinition {
+        first_gid,
+        name: tileset["name"].as_str().unwrap_or_default().to_owned(),
+        tile_size: Size::new(
+            json_u32(tileset, "tilewidth")?,
+            json_u32(tileset, "tileheight")?,
+        ),
+        tile_count: tileset["tilecount"].as_u32(),
+        margin: tileset["margin"].as_u32().unwrap_or_default(),
+        spacing: tileset["spacing"].as_u32().unwrap_or_default(),
+        image: tileset["image"].as_str().map(|image| directory.join(image)),
+        properties: parse_tmj_properties(&tileset["properties"])?,
+        tiles,
+    })
+}
+
+fn parse_tmj_layers(
+    list: &JsonValue,
+    inherited: LayerInheritance,
+    layers: &mut Vec<TiledLayer>,
+) -> KludgineResult<()> {
+    for node in list.members() {
+        let layer = inherited.apply(
+            Vector::new(
+                node["offsetx"].as_f32().unwrap_or_default(),
+                node["offsety"].as_f32().unwrap_or_default(),
+            ),
+            Vector::new(
+                node["parallaxx"].as_f32().unwrap_or(1.),
+                node["parallaxy"].as_f32().unwrap_or(1.),
+            ),
+            node["opacity"].as_f32().unwrap_or(1.),
+            node["visible"].as_bool().unwrap_or(true),
+        );
+        let contents = match node["type"].as_str() {
+            Some("group") => {
+                parse_tmj_layers(&node["layers"], layer, layers)?;
+                continue;
+            }
+            Some("tilelayer") => TiledLayerContents::Tiles(parse_tmj_tile_layer(node)?),
+            Some("objectgroup") => TiledLayerContents::Objects(
+                node["objects"]
+                    .members()
+                    .map(parse_tmj_object)
+                    .collect::<KludgineResult<_>>()?,
+            ),
+            _ => continue,
+        };
+
+        layers.push(layer.layer(
+            node["id"].as_u32().unwrap_or_default(),
+            node["name"].as_str().unwrap_or_default().to_owned(),
+            parse_tmj_properties(&node["properties"])?,
+            contents,
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_tmj_tile_layer(layer: &JsonValue) -> KludgineResult<TiledTileLayer> {
+    let data = &layer["data"];
+    let gids = match data.as_str() {
+        Some(data) => decode_tile_data(
+            data,
+            layer["encoding"].as_str().unwrap_or("base64"),
+            layer["compression"].as_str(),
+        )?,
+        None => data
+            .members()
+            .map(|gid| gid.as_u32().ok_or_else(|| parse_error("invalid tile id")))
+            .collect::<KludgineResult<_>>()?,
+    };
+
+    TiledTileLayer::new(
+        Size::new(json_u32(layer, "width")?, json_u32(layer, "height")?),
+        gids,
+    )
+}
+
+fn parse_tmj_points(points: &JsonValue) -> KludgineResult<Vec<Point<f32>>> {
+    points
+        .members()
+        .map(|point| match (point["x"].as_f32(), point["y"].as_f32()) {
+            (Some(x), Some(y)) => Ok(Point::new(x, y)),
+            _ => Err(parse_error("invalid point")),
+        })
+        .collect()
+}
+
+fn parse_tmj_object(object: &JsonValue) -> KludgineResult<TiledObject> {
+    let shape = if object["ellipse"].as_bool().unwrap_or_default() {
+        TiledObjectShape::Ellipse
+    } else if object["point"].as_bool().unwrap_or_default() {
+        TiledObjectShape::Point
+    } else if object["polygon"].is_array() {
+        TiledObjectShape::Polygon(parse_tmj_points(&object["polygon"])?)
+    } else if object["polyline"].is_array() {
+        TiledObjectShape::Polyline(parse_tmj_points(&object["polyline"])?)
+    } else if object["text"].is_object() {
+        TiledObjectShape::Text(
+            object["text"]["text"]
+                .as_str()
+                .unwrap_or_default()
+                .to_owned(),
+        )
+    } else {
+        TiledObjectShape::Rectangle
+    };
+
+    Ok(TiledObject {
+        id: object["id"].as_u32().unwrap_or_default(),
+        name: object["name"].as_str().unwrap_or_default().to_owned(),
+        class: object["class"]
+            .as_str()
+            .or_else(|| object["type"].as_str())
+            .unwrap_or_default()
+            .to_owned(),
+        location: Point::new(
+            object["x"].as_f32().unwrap_or_default(),
+            object["y"].as_f32().unwrap_or_default(),
+        ),
+        size: Size::new(
+            object["width"].as_f32().unwrap_or_default(),
+            object["height"].as_f32().unwrap_or_default(),
+        ),
+        rotation: Angle::degrees(object["rotation"].as_f32().unwrap_or_default()),
+        visible: object["visible"].as_bool().unwrap_or(true),
+        tile: object["gid"].as_u32().and_then(TiledTile::from_raw),
+        shape,
+        properties: parse_tmj_properties(&object["properties"])?,
+    })
+}
+
+fn parse_tmj_properties(list: &JsonValue) -> KludgineResult<TiledProperties> {
+    let mut properties = HashMap::new();
+    for property in list.members() {
+        let name = property["name"]
+            .as_str()
+            .ok_or_else(|| parse_error("property is missing name"))?;
+        let value = &property["value"];
+        let kind = property["type"].as_str().unwrap_or("string");
+        let invalid = || parse_error(format!("invalid {} property {}", kind, name));
+        let value = match kind {
+            "class" => TiledProperty::Class(json_class_members(value)),
+            "int" => TiledProperty::Int(value.as_i64().ok_or_else(invalid)?),
+            "float" => TiledProperty::Float(value.as_f64().ok_or_else(invalid)?),
+            "bool" => TiledProperty::Bool(value.as_bool().ok_or_else(invalid)?),
+            "object" => TiledProperty::Object(value.as_u32().ok_or_else(invalid)?),
+            kind => parse_property(kind, value.as_str().ok_or_else(invalid)?)?,
+        };
+        properties.insert(name.to_owned(), value);
+    }
+    Ok(properties)
+}
+
+/// Class values in the JSON format only store the members that differ from
+/// the class's defaults, without their types.
+fn json_class_members(value: &JsonValue) -> TiledProperties {
+    value
+        .entries()
+        .filter_map(|(name, member)| {
+            let member = if let Some(value) = member.as_str() {
+                TiledProperty::String(value.to_owned())
+            } else if let Some(value) = member.as_bool() {
+                TiledProperty::Bool(value)
+            } else if let Some(value) = member.as_i64() {
+                TiledProperty::Int(value)
+            } else if let Some(value) = member.as_f64() {
+                TiledProperty::Float(value)
+            } else if member.is_object() {
+                TiledProperty::Class(json_class_members(member))
+            } else {
+                return None;
+            };
+            Some((name.to_owned(), member))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        math::ScreenScale,
+        scene::{Scene, Target},
+        style::theme::Minimal,
+        tilemap::TileProvider,
+        window::SoftwareRenderer,
+    };
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    /// Writes a 2x2 grid of 2px tiles with a 1px margin and spacing, along with
+    /// an external tileset describing it.
+    fn write_tileset(directory: &Path) -> KludgineResult<()> {
+        std::fs::create_dir_all(directory).map_err(anyhow::Error::from)?;
+        image::RgbaImage::new(7, 7).save(directory.join("tiles.png"))?;
+        std::fs::write(
+            directory.join("tiles.tsx"),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.9" name="tiles" tilewidth="2" tileheight="2" spacing="1" margin="1" tilecount="4" columns="2">
+ <image source="tiles.png" width="7" height="7"/>
+ <tile id="1">
+  <properties><property name="solid" type="bool" value="true"/></properties>
+  <animation><frame tileid="1" duration="100"/><frame tileid="3" duration="200"/></animation>
+ </tile>
+</tileset>"#,
+        )
+        .map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    async fn frame_origin(sprite: &TileSprite, elapsed: Option<Duration>) -> Point<u32> {
+        sprite
+            .get_frame(elapsed)
+            .await
+            .unwrap()
+            .location
+            .bounds()
+            .origin
+    }
+
+    #[async_test]
+    async fn tmx_test() -> KludgineResult<()> {
+        let directory = std::env::temp_dir().join(format!("kludgine-tmx-{}", std::process::id()));
+        write_tileset(&directory)?;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for gid in &[0u32, 4, 0, 1, 0, 0] {
+            encoder
+                .write_all(&gid.to_le_bytes())
+                .map_err(anyhow::Error::from)?;
+        }
+        let water = base64::encode(encoder.finish().map_err(anyhow::Error::from)?);
+        std::fs::write(
+            directory.join("level.tmx"),
+            format!(
+                r##"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.9" orientation="orthogonal" width="3" height="2" tilewidth="2" tileheight="2" infinite="0" backgroundcolor="#80ff0000">
+ <properties>
+  <property name="title" value="Test"/>
+  <property name="gravity" type="float" value="9.5"/>
+  <property name="spawn" type="class"><properties><property name="lives" type="int" value="3"/></properties></property>
+ </properties>
+ <tileset firstgid="1" source="tiles.tsx"/>
+ <layer id="1" name="ground" width="3" height="2">
+  <data encoding="csv">1,2,0,
+3,2147483652,1</data>
+ </layer>
+ <group id="4" name="decor" offsetx="4" opacity="0.5" parallaxy="0.5">
+  <layer id="2" name="water" width="3" height="2" offsety="2" opacity="0.5" visible="0" parallaxx="0.5">
+   <data encoding="base64" compression="zlib">{}</data>
+  </layer>
+  <objectgroup id="3" name="spawns">
+   <object id="1" name="player" type="spawn" x="1" y="2">
+    <properties><property name="facing" value="left"/></properties>
+    <point/>
+   </object>
+   <object id="2" name="zone" x="0" y="0" width="4" height="2" rotation="90">
+    <polygon points="0,0 4,0 4,2"/>
+   </object>
+  </objectgroup>
+ </group>
+</map>"##,
+                water
+            ),
+        )
+        .map_err(anyhow::Error::from)?;
+
+        let map = TiledMap::load(directory.join("level.tmx")).await?;
+        assert_eq!(map.orientation, TiledOrientation::Orthogonal);
+        assert_eq!(map.size, Size::new(3, 2));
+        assert_eq!(
+            map.background_color,
+            Some(Color::new(1., 0., 0., 128. / 255.))
+        );
+        assert_eq!(map.properties["title"].as_str(), Some("Test"));
+        assert_eq!(map.properties["gravity"].as_f64(), Some(9.5));
+        assert_eq!(
+            map.properties["spawn"].as_class().unwrap()["lives"].as_i64(),
+            Some(3)
+        );
+
+        let tileset = &map.tilesets[0];
+        assert_eq!(tileset.name, "tiles");
+        assert_eq!(tileset.tile_count, 4);
+        assert_eq!(
+            map.tile_properties(2).unwrap()["solid"].as_bool(),
+            Some(true)
+        );
+        assert!(map.tile_properties(1).is_none());
+
+        // The margin and spacing offset each tile within the sheet
+        let last = map.tile_sprite(4).await.unwrap();
+        assert_eq!(frame_origin(&last, None).await, Point::new(4, 4));
+        let animated = map.tile_sprite(2).await.unwrap();
+        assert!(matches!(animated, TileSprite::Sprite(_)));
+        assert_eq!(frame_origin(&animated, None).await, Point::new(4, 1));
+        assert_eq!(
+            frame_origin(&animated, Some(Duration::from_millis(150))).await,
+            Point::new(4, 4)
+        );
+        // Each request for an animated tile is a separate instance
+        let animated = map.tile_sprite(2).await.unwrap();
+        assert_eq!(frame_origin(&animated, None).await, Point::new(4, 1));
+
+        assert_eq!(map.layers.len(), 3);
+        let ground = map.layer("ground").unwrap().tiles().unwrap();
+        assert_eq!(ground.tile(Point::new(2, 0)), None);
+        assert_eq!(
+            ground.tile(Point::new(1, 1)),
+            Some(TiledTile {
+                gid: 4,
+                flipped_horizontally: true,
+                flipped_vertically: false,
+                flipped_diagonally: false,
+            })
+        );
+
+        let water = map.layer("water").unwrap();
+        assert_eq!(water.offset, Vector::new(4., 2.));
+        assert!((water.opacity - 0.25).abs() < f32::EPSILON);
+        assert!(!water.visible);
+        assert_eq!(water.parallax, Vector::new(0.5, 0.5));
+        assert_eq!(
+            water.tiles().unwrap().tile(Point::new(1, 0)).unwrap().gid,
+            4
+        );
+        assert_eq!(
+            water.tiles().unwrap().tile(Point::new(0, 1)).unwrap().gid,
+            1
+        );
+
+        let player = map.object("player").unwrap();
+        assert_eq!(player.class, "spawn");
+        assert_eq!(player.location, Point::new(1., 2.));
+        assert_eq!(player.shape, TiledObjectShape::Point);
+        assert_eq!(player.properties["facing"].as_str(), Some("left"));
+        let zone = map.object("zone").unwrap();
+        assert_eq!(zone.rotation, Angle::degrees(90.));
+        assert_eq!(
+            zone.shape,
+            TiledObjectShape::Polygon(vec![
+                Point::new(0., 0.),
+                Point::new(4., 0.),
+                Point::new(4., 2.)
+            ])
+        );
+        assert_eq!(map.layer("spawns").unwrap().offset, Vector::new(4., 0.));
+
+        let maps = map.tile_maps().await;
+        assert_eq!(
+            maps.iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["ground", "water"]
+        );
+        let ground = &maps[0].1;
+        assert!(ground.get_tile(Point::new(0, 0)).await.is_some());
+        assert!(ground.get_tile(Point::new(2, 0)).await.is_none());
+        let tile = ground.get_tile(Point::new(0, 1)).await.unwrap();
+        assert_eq!(frame_origin(&tile.sprite, None).await, Point::new(1, 4));
+
+        std::fs::remove_dir_all(&directory).map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    #[async_test]
+    async fn tmj_test() -> KludgineResult<()> {
+        let directory = std::env::temp_dir().join(format!("kludgine-tmj-{}", std::process::id()));
+        write_tileset(&directory)?;
+        image::RgbaImage::new(3, 5).save(directory.join("tree.png"))?;
+
+        let map = TiledMap::parse_tmj(
+            r##"{
+                "orientation": "isometric",
+                "width": 2,
+                "height": 1,
+                "tilewidth": 2,
+                "tileheight": 2,
+                "infinite": false,
+                "properties": [
+                    { "name": "music", "type": "file", "value": "theme.ogg" },
+                    { "name": "tint", "type": "color", "value": "#00ff00" },
+                    { "name": "spawn", "type": "class", "value": { "lives": 3, "name": "hero" } }
+                ],
+                "tilesets": [
+                    { "firstgid": 1, "source": "tiles.tsx" },
+                    {
+                        "firstgid": 5,
+                        "name": "props",
+                        "tilewidth": 3,
+                        "tileheight": 5,
+                        "tilecount": 1,
+                        "tiles": [{ "id": 0, "image": "tree.png" }]
+                    }
+                ],
+                "layers": [
+                    {
+                        "type": "tilelayer",
+                        "id": 1,
+                        "name": "ground",
+                        "width": 2,
+                        "height": 1,
+                        "data": [5, 1073741826]
+                    },
+                    {
+                        "type": "objectgroup",
+                        "id": 2,
+                        "name": "things",
+                        "offsetx": 1.5,
+                        "objects": [
+                            {
+                                "id": 7,
+                                "name": "path",
+                                "class": "route",
+                                "x": 1,
+                                "y": 1,
+                                "polyline": [{ "x": 0, "y": 0 }, { "x": 2, "y": 1 }],
+                                "properties": [{ "name": "speed", "type": "int", "value": 4 }]
+                            },
+                            { "id": 8, "name": "tree", "gid": 5, "x": 0, "y": 5, "width": 3, "height": 5 }
+                        ]
+                    }
+                ]
+            }"##,
+            &directory,
+        )
+        .await?;
+
+        assert_eq!(map.orientation, TiledOrientation::Isometric);
+        assert_eq!(map.projection, TileProjection::Isometric);
+        assert_eq!(map.properties["music"].as_str(), Some("theme.ogg"));
+        assert_eq!(
+            map.properties["tint"].as_color(),
+            Some(Color::new(0., 1., 0., 1.))
+        );
+        let spawn = map.properties["spawn"].as_class().unwrap();
+        assert_eq!(spawn["lives"].as_i64(), Some(3));
+        assert_eq!(spawn["name"].as_str(), Some("hero"));
+
+        assert_eq!(map.tileset_for(4).unwrap().name, "tiles");
+        assert_eq!(map.tileset_for(5).unwrap().name, "props");
+        let tree = map.tile_sprite(5).await.unwrap();
+        let tree = tree.get_frame(None).await?;
+        assert_eq!(tree.location.size(), Size::new(3, 5));
+
+        let ground = map.layer("ground").unwrap().tiles().unwrap();
+        let flipped = ground.tile(Point::new(1, 0)).unwrap();
+        assert_eq!(flipped.gid, 2);
+        assert!(flipped.flipped_vertically);
+
+        let path = map.object("path").unwrap();
+        assert_eq!(path.class, "route");
+        assert_eq!(path.properties["speed"].as_i64(), Some(4));
+        assert_eq!(
+            path.shape,
+            TiledObjectShape::Polyline(vec![Point::new(0., 0.), Point::new(2., 1.)])
+        );
+        assert_eq!(map.layer("things").unwrap().offset, Vector::new(1.5, 0.));
+        let tree = map.object("tree").unwrap();
+        assert_eq!(tree.tile.unwrap().gid, 5);
+        assert_eq!(tree.shape, TiledObjectShape::Rectangle);
+        assert_eq!(map.objects().count(), 2);
+
+        std::fs::remove_dir_all(&directory).map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    #[async_test]
+    async fn flipped_tiles_test() -> KludgineResult<()> {
+        let directory =
+            std::env::temp_dir().join(format!("kludgine-tiled-flips-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).map_err(anyhow::Error::from)?;
+        let red = image::Rgba([255, 0, 0, 255]);
+        let green = image::Rgba([0, 255, 0, 255]);
+        let blue = image::Rgba([0, 0, 255, 255]);
+        let white = image::Rgba([255, 255, 255, 255]);
+        let mut image = image::RgbaImage::new(2, 2);
+        image.put_pixel(0, 0, red);
+        image.put_pixel(1, 0, green);
+        image.put_pixel(0, 1, blue);
+        image.put_pixel(1, 1, white);
+        image.save(directory.join("flips.png"))?;
+
+        // The same tile flipped horizontally, vertically, diagonally, and both
+        // diagonally and horizontally, which Tiled uses for a clockwise turn
+        let map = TiledMap::parse_tmj(
+            &format!(
+                r#"{{
+                    "orientation": "orthogonal",
+                    "width": 4, "height": 1, "tilewidth": 2, "tileheight": 2,
+                    "tilesets": [{{
+                        "firstgid": 1, "name": "flips", "tilewidth": 2, "tileheight": 2,
+                        "tilecount": 1, "columns": 1, "image": "flips.png",
+                        "imagewidth": 2, "imageheight": 2
+                    }}],
+                    "layers": [{{
+                        "type": "tilelayer", "id": 1, "name": "tiles", "width": 4, "height": 1,
+                        "data": [{}, {}, {}, {}]
+                    }}]
+                }}"#,
+                FLIPPED_HORIZONTALLY | 1,
+                FLIPPED_VERTICALLY | 1,
+                FLIPPED_DIAGONALLY | 1,
+                FLIPPED_DIAGONALLY | FLIPPED_HORIZONTALLY | 1,
+            ),
+            &directory,
+        )
+        .await?;
+        std::fs::remove_dir_all(&directory).map_err(anyhow::Error::from)?;
+
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(8., 2.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        let target = Target::from(scene);
+        let (_, tiles) = &map.tile_maps().await[0];
+        tiles.draw(&target, Point::default()).await?;
+        let rendered = SoftwareRenderer::default().render(&target).await?;
+
+        let tile = |x: u32| {
+            [
+                *rendered.get_pixel(x, 0),
+                *rendered.get_pixel(x + 1, 0),
+                *rendered.get_pixel(x, 1),
+                *rendered.get_pixel(x + 1, 1),
+            ]
+        };
+        assert_eq!(tile(0), [green, red, white, blue]);
+        assert_eq!(tile(2), [blue, white, red, green]);
+        assert_eq!(tile(4), [red, blue, green, white]);
+        assert_eq!(tile(6), [blue, red, white, green]);
+        Ok(())
+    }
+
+    #[test]
+    fn projection_test() -> KludgineResult<()> {
+        let map = parse_tmj_map(
+            r#"{"orientation": "hexagonal", "width": 1, "height": 1, "tilewidth": 28,
+                "tileheight": 32, "staggeraxis": "x", "staggerindex": "even", "hexsidelength": 16}"#,
+            Path::new(""),
+        )?;
+        assert_eq!(
+            map.projection,
+            TileProjection::Hexagonal {
+                axis: StaggerAxis::Columns,
+                index: StaggerIndex::Even,
+                side_length: 16
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_map_test() {
+        assert!(matches!(
+            parse_tmx_map(
+                r#"<map orientation="orthogonal" width="2" height="2" tilewidth="1" tileheight="1" infinite="1"/>"#,
+                Path::new("")
+            ),
+            Err(KludgineError::TiledParseError(_))
+        ));
+        assert!(matches!(
+            parse_tmj_map(
+                r#"{"width": 2, "height": 1, "tilewidth": 1, "tileheight": 1,
+                    "layers": [{"type": "tilelayer", "width": 2, "height": 1, "data": [1]}]}"#,
+                Path::new("")
+            ),
+            Err(KludgineError::TiledParseError(_))
+        ));
+        // A layer whose tile count overflows is rejected rather than wrapping
+        // around to match the data
+        assert!(matches!(
+            parse_tmj_map(
+                r#"{"width": 1, "height": 1, "tilewidth": 1, "tileheight": 1,
+                    "layers": [{"type": "tilelayer", "width": 65536, "height": 65536, "data": []}]}"#,
+                Path::new("")
+            ),
+            Err(KludgineError::TiledParseError(_))
+        ));
+    }
+}
@@ -29,16 +29,48 @@ use async_trait::async_trait;
 use once_cell::sync::OnceCell;
 use std::{
     collections::{HashMap, HashSet},
+    future::Future,
     hash::Hash,
+    sync::atomic::{AtomicUsize, Ordering},
     time::{Duration, Instant},
 };
 
 static UI: OnceCell<HierarchicalArena> = OnceCell::new();
 
+/// The number of messages, commands and callbacks sent to components that
+/// haven't been processed yet
+static PENDING_MESSAGES: AtomicUsize = AtomicUsize::new(0);
+
 pub(crate) fn global_arena() -> &'static HierarchicalArena {
     UI.get_or_init(HierarchicalArena::default)
 }
 
+/// Spawns `delivery`, which delivers a message, command or callback to a
+/// component, counting it as pending until it completes
+pub(crate) fn spawn_delivery<F: Future<Output = ()> + Send + 'static>(delivery: F) {
+    struct Pending;
+
+    impl Drop for Pending {
+        fn drop(&mut self) {
+            PENDING_MESSAGES.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    PENDING_MESSAGES.fetch_add(1, Ordering::SeqCst);
+    let pending = Pending;
+    Runtime::spawn(async move {
+        delivery.await;
+        drop(pending);
+    })
+    .detach();
+}
+
+/// Returns true while any messages, commands or callbacks are waiting to be
+/// delivered to components
+pub(crate) fn messages_pending() -> bool {
+    PENDING_MESSAGES.load(Ordering::SeqCst) > 0
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct UIState {
     data: Handle<UIStateData>,
@@ -101,6 +133,11 @@ impl UIState {
         }
     }
 
+    pub(crate) async fn send_window_event(&self, event: WindowEvent) {
+        let data = self.data.read().await;
+        let _ = data.event_sender.send(event).await;
+    }
+
     async fn clear_redraw_target(&self) {
         let mut data = self.data.write().await;
         data.needs_render = false;
@@ -782,15 +819,14 @@ where
             let component = target_node.component.read().await;
             if let Some(component_handle) = component.component::<C, C>().await {
                 let mut context = self.context.clone();
-                Runtime::spawn(async move {
+                spawn_delivery(async move {
                     let mut component = component_handle.write().await;
                     component
                         .receive_command(&mut context, command)
                         .await
                         .filter_invalid_component_references()
                         .unwrap()
-                })
-                .detach();
+                });
 
                 Ok(())
             } else {
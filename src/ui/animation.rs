@@ -247,7 +247,7 @@ where
     }
 
     pub async fn update(&mut self, context: &mut Context) {
-        let now = Instant::now();
+        let now = context.scene().now().await;
         self.update_current_frame(now);
         if let Some(current_frame) = &mut self.current_frame {
             if let Some(elapsed_since_last_frame) =
@@ -3,6 +3,7 @@ use std::sync::Arc;
 use crate::{
     math::{Point, PointExt, Raw, Rect, Scaled, Size, SizeExt, Surround},
     shape::{Fill, Shape},
+    sprite::SpriteRotation,
     style::{theme::Selector, BackgroundColor, ColorPair, StyleComponent},
     ui::{Context, Entity, Layout, LayoutSolver, LayoutSolverExt, StyledContext},
     window::{
@@ -35,8 +36,11 @@ mod toast;
 pub use self::{
     builder::EntityBuilder,
     button::Button,
-    control::{Border, ComponentBorder, ComponentPadding, ContentOffset, ControlEvent},
-    dialog::{Dialog, DialogButton, DialogButtonSpacing, DialogButtons},
+    control::{
+        BackgroundImage, Border, BorderImage, ComponentBorder, ComponentPadding, ContentOffset,
+        ControlEvent,
+    },
+    dialog::{Dialog, DialogButton, DialogButtonSpacing, DialogButtons, DialogMessage},
     grid::{Grid, GridCommand, GridEvent},
     image::{
         Image, ImageAlphaAnimation, ImageCommand, ImageFrameAnimation, ImageOptions, ImageScaling,
@@ -149,19 +153,59 @@ pub trait Component: Send + Sync {
         Border: StyleComponent<Raw> + Clone + Into<ComponentBorder>,
     {
         let bounds = layout.bounds_without_margin();
+        let border = context
+            .effective_style()?
+            .get::<Border>()
+            .map(|border| -> ComponentBorder { border.clone().into() });
+        let corner_radii = border.as_ref().and_then(|border| border.corner_radii);
         if let Some(background) = context.effective_style()?.get::<Background>() {
             let color_pair = background.clone().into();
             let color = color_pair.themed_color(&context.scene().system_theme().await);
 
             if color.visible() {
-                Shape::rect(bounds)
+                let shape = match corner_radii {
+                    Some(corner_radii) => Shape::rounded_rect(bounds, corner_radii),
+                    None => Shape::rect(bounds),
+                };
+                shape
                     .fill(Fill::new(color))
                     .render_at(Point::default(), context.scene())
                     .await;
             }
         }
-        if let Some(border) = context.effective_style()?.get::<Border>() {
-            let border = border.clone().into();
+        if let Some(image) = context.effective_style()?.get::<BackgroundImage>() {
+            match &image.nine_slice {
+                Some(nine_slice) => {
+                    nine_slice
+                        .render_within(&image.source, context.scene(), bounds)
+                        .await
+                }
+                None => {
+                    image
+                        .source
+                        .render_within(context.scene(), bounds, SpriteRotation::default())
+                        .await
+                }
+            }
+        }
+        if let Some(border) = border {
+            if let Some(image) = &border.image {
+                image
+                    .nine_slice
+                    .render_within(&image.source, context.scene(), bounds)
+                    .await;
+            }
+            if let Some(sides) = border.rounded_sides(bounds) {
+                let theme = context.scene().system_theme().await;
+                for (side, outline) in sides {
+                    Shape::path(outline)
+                        .fill(Fill::new(side.color.themed_color(&theme)))
+                        .render_at(Point::default(), context.scene())
+                        .await;
+                }
+                return Ok(());
+            }
+
             // TODO the borders should be mitered together rather than drawn overlapping
             if let Some(left) = &border.left {
                 Shape::rect(Rect::new(
@@ -1,5 +1,7 @@
 use crate::{
-    math::{Point, Raw, Scale, Scaled, Surround, Vector},
+    math::{Angle, Point, Raw, Rect, Scale, Scaled, Size, Surround, Vector},
+    shape::{CornerRadii, Path, PathBuilder},
+    sprite::{NineSlice, SpriteSource},
     style::{ColorPair, Style, StyleComponent, UnscaledStyleComponent},
     window::event::MouseButton,
 };
@@ -58,6 +60,11 @@ pub struct ComponentBorder {
     pub top: Option<Border>,
     pub right: Option<Border>,
     pub bottom: Option<Border>,
+    /// Skinned art drawn as the border, beneath any colored borders
+    pub image: Option<BorderImage>,
+    /// Rounds the corners of the border and the component's background. Each
+    /// side of the border covers half of the corners next to it.
+    pub corner_radii: Option<CornerRadii<Scaled>>,
 }
 
 impl ComponentBorder {
@@ -67,9 +74,129 @@ impl ComponentBorder {
             top: Some(border.clone()),
             right: Some(border.clone()),
             bottom: Some(border),
+            image: None,
+            corner_radii: None,
         }
     }
 
+    pub fn image(image: BorderImage) -> Self {
+        Self::default().with_image(image)
+    }
+
+    pub fn with_image(mut self, image: BorderImage) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    pub fn with_corner_radii(mut self, corner_radii: CornerRadii<Scaled>) -> Self {
+        self.corner_radii = Some(corner_radii);
+        self
+    }
+
+    /// Returns the outline of each side of a border with rounded corners drawn
+    /// within `bounds`, paired with the side's border. Sides meet halfway
+    /// around each corner, allowing each side to be filled with its own color.
+    pub fn rounded_sides(&self, bounds: Rect<f32, Scaled>) -> Option<Vec<(&Border, Path<Scaled>)>> {
+        let radii = self.corner_radii?.fitted_to(&bounds);
+        let width = |border: &Option<Border>| {
+            border
+                .as_ref()
+                .map(|border| border.width.get())
+                .unwrap_or_default()
+        };
+        let (left, top, right, bottom) = (
+            width(&self.left),
+            width(&self.top),
+            width(&self.right),
+            width(&self.bottom),
+        );
+        let inner_bounds = Rect::new(
+            bounds.origin + Vector::new(left, top),
+            Size::new(
+                (bounds.size.width - left - right).max(0.),
+                (bounds.size.height - top - bottom).max(0.),
+            ),
+        );
+        let inner_radius = |radius: Length<f32, Scaled>, a: f32, b: f32| {
+            Length::new((radius.get() - a.max(b)).max(0.))
+        };
+        let inner_radii = CornerRadii {
+            top_left: inner_radius(radii.top_left, left, top),
+            top_right: inner_radius(radii.top_right, right, top),
+            bottom_right: inner_radius(radii.bottom_right, right, bottom),
+            bottom_left: inner_radius(radii.bottom_left, left, bottom),
+        };
+
+        // The center of each corner's arc, clockwise from the top left, and
+        // the angle its arc starts at
+        let corner = |rect: &Rect<f32, Scaled>, index: usize, radius: Length<f32, Scaled>| {
+            let radius = radius.get();
+            match index {
+                0 => (
+                    Point::new(rect.min_x() + radius, rect.min_y() + radius),
+                    180.,
+                ),
+                1 => (
+                    Point::new(rect.max_x() - radius, rect.min_y() + radius),
+                    270.,
+                ),
+                2 => (Point::new(rect.max_x() - radius, rect.max_y() - radius), 0.),
+                _ => (
+                    Point::new(rect.min_x() + radius, rect.max_y() - radius),
+                    90.,
+                ),
+            }
+        };
+        let radius = |radii: &CornerRadii<Scaled>, index: usize| match index {
+            0 => radii.top_left,
+            1 => radii.top_right,
+            2 => radii.bottom_right,
+            _ => radii.bottom_left,
+        };
+        let arc = |builder: PathBuilder<Scaled>,
+                   rect: &Rect<f32, Scaled>,
+                   radii: &CornerRadii<Scaled>,
+                   index: usize,
+                   start: f32,
+                   sweep: f32| {
+            let radius = radius(radii, index);
+            let (center, angle) = corner(rect, index, radius);
+            if radius.get() > 0. {
+                builder.elliptical_arc(
+                    center,
+                    Vector::new(radius.get(), radius.get()),
+                    Angle::degrees(angle + start),
+                    Angle::degrees(sweep),
+                )
+            } else {
+                builder.line_to(center)
+            }
+        };
+
+        let sides = [&self.top, &self.right, &self.bottom, &self.left];
+        Some(
+            sides
+                .iter()
+                .enumerate()
+                .filter_map(|(index, side)| side.as_ref().map(|side| (index, side)))
+                .map(|(index, side)| {
+                    let next = (index + 1) % 4;
+                    let (center, angle) = corner(&bounds, index, radius(&radii, index));
+                    let start = Angle::degrees(angle + 45.).radians;
+                    let radius = radius(&radii, index).get();
+                    let outline = PathBuilder::new(
+                        center + Vector::new(radius * start.cos(), radius * start.sin()),
+                    );
+                    let outline = arc(outline, &bounds, &radii, index, 45., 45.);
+                    let outline = arc(outline, &bounds, &radii, next, 0., 45.);
+                    let outline = arc(outline, &inner_bounds, &inner_radii, next, 45., -45.);
+                    let outline = arc(outline, &inner_bounds, &inner_radii, index, 90., -45.);
+                    (side, outline.close().build())
+                })
+                .collect(),
+        )
+    }
+
     pub fn with_left(mut self, left: Border) -> Self {
         self.left = Some(left);
         self
@@ -97,5 +224,51 @@ impl UnscaledStyleComponent<Scaled> for ComponentBorder {
     }
 }
 
+/// A nine-slice image drawn around the edges of a component. The center of
+/// the nine-slice is never drawn.
+#[derive(Debug, Clone)]
+pub struct BorderImage {
+    pub source: SpriteSource,
+    pub nine_slice: NineSlice,
+}
+
+impl BorderImage {
+    pub fn new(source: SpriteSource, nine_slice: NineSlice) -> Self {
+        Self {
+            source,
+            nine_slice: nine_slice.without_center(),
+        }
+    }
+}
+
+/// An image drawn behind a component, filling its bounds. Nine-slice images
+/// keep their corners unscaled, allowing buttons and panels to use skinned
+/// art.
+#[derive(Debug, Clone)]
+pub struct BackgroundImage {
+    pub source: SpriteSource,
+    pub nine_slice: Option<NineSlice>,
+}
+
+impl BackgroundImage {
+    pub fn new(source: SpriteSource) -> Self {
+        Self {
+            source,
+            nine_slice: None,
+        }
+    }
+
+    pub fn with_nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
+}
+
+impl UnscaledStyleComponent<Scaled> for BackgroundImage {
+    fn unscaled_should_be_inherited(&self) -> bool {
+        false
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ContentOffset(pub Vector<f32, Scaled>);
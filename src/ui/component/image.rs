@@ -1,6 +1,7 @@
 use crate::{
+    color::Color,
     math::{Point, Rect, Scaled, Size},
-    sprite::{Sprite, SpriteRotation, SpriteSource},
+    sprite::{NineSlice, Sprite, SpriteRotation, SpriteSource, SpriteTint},
     style::theme::Selector,
     ui::{
         animation::{FloatChange, PropertyFrameManager, PropertyMutator, Transition},
@@ -32,6 +33,11 @@ pub struct ImageOptions {
     pub scaling: Option<ImageScaling>,
     pub override_frame: Option<OverrideFrame>,
     pub alpha: f32,
+    /// Colors applied to the image, such as for damage flashes or team colors
+    pub tint: SpriteTint,
+    /// Draws each frame as a nine-slice filling the image's bounds. Scaling
+    /// is ignored when set.
+    pub nine_slice: Option<NineSlice>,
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +52,8 @@ impl Default for ImageOptions {
             scaling: None,
             alpha: 1.,
             override_frame: None,
+            tint: SpriteTint::default(),
+            nine_slice: None,
         }
     }
 }
@@ -60,6 +68,21 @@ impl ImageOptions {
         self.alpha = alpha;
         self
     }
+
+    pub fn tint(mut self, color: Color) -> Self {
+        self.tint.color = color;
+        self
+    }
+
+    pub fn flash(mut self, flash: Color) -> Self {
+        self.tint.flash = flash;
+        self
+    }
+
+    pub fn nine_slice(mut self, nine_slice: NineSlice) -> Self {
+        self.nine_slice = Some(nine_slice);
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -67,6 +90,7 @@ pub enum ImageCommand {
     SetSprite(Sprite),
     SetTag(Option<String>),
     SetAlpha(f32),
+    SetTint(SpriteTint),
     SetOverrideFrame {
         tag: Option<String>,
         frame: OverrideFrame,
@@ -98,6 +122,10 @@ impl InteractiveComponent for Image {
                 context.set_needs_redraw().await;
                 self.options.alpha = alpha;
             }
+            ImageCommand::SetTint(tint) => {
+                context.set_needs_redraw().await;
+                self.options.tint = tint;
+            }
             ImageCommand::SetOverrideFrame { tag, frame } => {
                 context.set_needs_redraw().await;
                 self.sprite.set_current_tag(tag).await?;
@@ -160,6 +188,14 @@ impl Component for Image {
         location: &Layout,
     ) -> KludgineResult<()> {
         let render_bounds = location.inner_bounds();
+        let tint = self.options.tint.with_alpha(self.options.alpha);
+        if let (Some(frame), Some(nine_slice)) = (&self.current_frame, &self.options.nine_slice) {
+            nine_slice
+                .render_with_tint(frame, context.scene(), render_bounds, tint)
+                .await;
+            return Ok(());
+        }
+
         let target_size = self.calculate_target_size(render_bounds.size).await;
         if let Some(frame) = &self.current_frame {
             let target_bounds = Rect::new(
@@ -168,11 +204,11 @@ impl Component for Image {
             );
 
             frame
-                .render_with_alpha(
+                .render_with_tint(
                     context.scene(),
                     target_bounds,
                     SpriteRotation::default(),
-                    self.options.alpha,
+                    tint,
                 )
                 .await
         }
@@ -227,7 +263,7 @@ impl Image {
 
     async fn calculate_target_size(&self, content_size: Size<f32, Scaled>) -> Size<f32, Scaled> {
         if let Some(frame) = &self.current_frame {
-            let size_as_points = frame.location.size().to_f32().cast_unit();
+            let size_as_points = frame.size().to_f32().cast_unit();
             match &self.options.scaling {
                 None => size_as_points,
                 Some(scaling) => match scaling {
@@ -4,7 +4,7 @@ use crate::{
     runtime::Runtime,
     scene::Target,
     shape::Shape,
-    sprite::{Sprite, SpriteRotation, SpriteSource},
+    sprite::{Sprite, SpriteRotation, SpriteSource, SpriteTint, SpriteTransform},
     text::Text,
     tilemap::{TileMap, TileProvider},
     ui::{Component, Context, InteractiveComponent, Layout, StyledContext},
@@ -98,19 +98,32 @@ where
             match &rendered.drawable {
                 Drawable::Sprite(sprite) => {
                     let sprite_size =
-                        sprite.location.size().cast_unit::<Unit>().cast::<f32>() * rendered.scale;
+                        sprite.size().cast_unit::<Unit>().cast::<f32>() * rendered.scale;
 
                     let render_location = rendered.center * rendered.scale - sprite_size / 2.;
-                    sprite
-                        .render_within(
-                            context.scene(),
-                            Rect::new(center + render_location.to_vector(), sprite_size),
-                            rendered
-                                .rotation
-                                .map(|rotation| SpriteRotation::around(rotation, render_location))
-                                .unwrap_or_default(),
-                        )
-                        .await;
+                    let bounds = Rect::new(center + render_location.to_vector(), sprite_size);
+                    if let Some(mut transform) = rendered.transform {
+                        if let Some(rotation) = rendered.rotation {
+                            transform.rotation += rotation;
+                        }
+                        sprite
+                            .render_transformed(context.scene(), bounds, transform, rendered.tint)
+                            .await;
+                    } else {
+                        sprite
+                            .render_with_tint(
+                                context.scene(),
+                                bounds,
+                                rendered
+                                    .rotation
+                                    .map(|rotation| {
+                                        SpriteRotation::around(rotation, render_location)
+                                    })
+                                    .unwrap_or_default(),
+                                rendered.tint,
+                            )
+                            .await;
+                    }
                 }
                 Drawable::Shape(shape) => {
                     let shape = shape.clone() * rendered.scale;
@@ -265,6 +278,8 @@ pub struct RenderedDrawable<Unit> {
     center: Point<f32, Unit>,
     rotation: Option<Angle>,
     scale: Scale<f32, Unit, Scaled>,
+    tint: SpriteTint,
+    transform: Option<SpriteTransform>,
     z: i32,
 }
 
@@ -288,6 +303,8 @@ impl<Unit> RenderedDrawable<Unit> {
             rotation: None,
             z: 0,
             scale: Scale::new(1.),
+            tint: SpriteTint::default(),
+            transform: None,
             render_id: 0,
         }
     }
@@ -312,6 +329,18 @@ impl<Unit> RenderedDrawable<Unit> {
         self
     }
 
+    pub fn with_tint(mut self, tint: SpriteTint) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// Flips, skews or scales sprites around a pivot. Any rotation is added to
+    /// the transform's rotation.
+    pub fn with_transform(mut self, transform: SpriteTransform) -> Self {
+        self.transform = Some(transform);
+        self
+    }
+
     fn with_render_id(mut self, render_id: usize) -> Self {
         self.render_id = render_id;
         self
@@ -513,15 +542,21 @@ fn render_sprite_source<Unit: Sized + Send + Sync + 'static>(
     rotation: Option<&Angle>,
     scaling: Option<&Scaling<Unit>>,
     z: Option<&ZIndex>,
+    tint: Option<&SpriteTint>,
+    transform: Option<&SpriteTransform>,
     #[resource] frame: &CanvasFrame<Unit>,
     #[resource] camera: &CameraState<Unit>,
 ) {
     let mut drawable = RenderedDrawable::new(Drawable::Sprite(sprite.clone()))
         .with_center(*location - camera.look_at.to_vector())
-        .with_z(z.cloned().unwrap_or_default().0);
+        .with_z(z.cloned().unwrap_or_default().0)
+        .with_tint(tint.cloned().unwrap_or_default());
     if let Some(rotation) = rotation {
         drawable = drawable.with_rotation(*rotation);
     }
+    if let Some(transform) = transform {
+        drawable = drawable.with_transform(*transform);
+    }
     if let Some(scaling) = scaling {
         drawable = drawable.with_scale(Scale::new(scaling.0.get() * camera.scale.get()));
     } else {
@@ -645,15 +680,17 @@ fn render_batch<Unit: Clone + Sized + Send + Sync + 'static>(
     rotation: Option<&Angle>,
     scaling: Option<&Scaling<Unit>>,
     z: Option<&ZIndex>,
+    tint: Option<&SpriteTint>,
+    transform: Option<&SpriteTransform>,
     #[resource] frame: &mut CanvasFrame<Unit>,
     #[resource] camera: &CameraState<Unit>,
     #[resource] elapsed: &Option<Duration>,
 ) {
     for drawable in batch.0.iter() {
         match drawable {
-            Drawable::Sprite(sprite) => {
-                render_sprite_source(sprite, location, rotation, scaling, z, frame, camera)
-            }
+            Drawable::Sprite(sprite) => render_sprite_source(
+                sprite, location, rotation, scaling, z, tint, transform, frame, camera,
+            ),
             Drawable::Shape(shape) => {
                 render_shape(shape, location, rotation, scaling, z, frame, camera)
             }
@@ -64,6 +64,9 @@ impl Cursor {
     }
 }
 
+/// The caret's blink state. It blinks using the scene's clock, so frames
+/// rendered with [`Scene::start_frame_at`](crate::scene::Scene::start_frame_at)
+/// blink deterministically.
 #[derive(Debug, Clone)]
 pub struct BlinkState {
     pub visible: bool,
@@ -74,28 +77,26 @@ impl Default for BlinkState {
     fn default() -> Self {
         Self {
             visible: true,
-            change_at: Self::next_blink(),
+            change_at: Self::next_blink(Instant::now()),
         }
     }
 }
 
 impl BlinkState {
-    pub fn next_blink() -> Instant {
-        let now = Instant::now();
+    pub fn next_blink(now: Instant) -> Instant {
         now.checked_add(Duration::from_millis(CURSOR_BLINK_MS))
             .unwrap_or(now)
     }
 
-    pub fn force_on(&mut self) {
+    pub fn force_on(&mut self, now: Instant) {
         self.visible = true;
-        self.change_at = Self::next_blink();
+        self.change_at = Self::next_blink(now);
     }
 
-    pub fn update(&mut self) -> Option<Duration> {
-        let now = Instant::now();
+    pub fn update(&mut self, now: Instant) -> Option<Duration> {
         if self.change_at < now {
             self.visible = !self.visible;
-            self.change_at = Self::next_blink();
+            self.change_at = Self::next_blink(now);
         }
 
         self.change_at.checked_duration_since(now)
@@ -143,7 +144,8 @@ impl Component for TextField {
 
     async fn update(&mut self, context: &mut Context) -> KludgineResult<()> {
         if context.is_focused().await? {
-            if let Some(duration) = self.cursor.blink_state.update() {
+            let now = context.scene().now().await;
+            if let Some(duration) = self.cursor.blink_state.update(now) {
                 context.estimate_next_frame(duration).await;
             } else {
                 context.set_needs_redraw().await;
@@ -291,7 +293,9 @@ impl Component for TextField {
     ) -> KludgineResult<EventStatus> {
         if button == MouseButton::Left {
             context.focus().await?;
-            self.cursor.blink_state.force_on();
+            self.cursor
+                .blink_state
+                .force_on(context.scene().now().await);
 
             let padding = context
                 .style_sheet()
@@ -324,7 +328,9 @@ impl Component for TextField {
         button: MouseButton,
     ) -> KludgineResult<()> {
         if button == MouseButton::Left {
-            self.cursor.blink_state.force_on();
+            self.cursor
+                .blink_state
+                .force_on(context.scene().now().await);
             if let Some(window_position) = window_position {
                 let padding = context
                     .style_sheet()
@@ -456,7 +462,9 @@ impl Component for TextField {
                     _ => {}
                 }
 
-                self.cursor.blink_state.force_on();
+                self.cursor
+                    .blink_state
+                    .force_on(context.scene().now().await);
                 context.set_needs_redraw().await;
             }
         }
@@ -492,7 +500,9 @@ impl TextField {
 
         self.text.insert_str(self.cursor.start, replacement).await;
         self.cursor.start.offset += replacement.len();
-        self.cursor.blink_state.force_on();
+        self.cursor
+            .blink_state
+            .force_on(context.scene().now().await);
 
         self.notify_changed(context).await;
         self.notify_selection_changed(context).await;
@@ -5,6 +5,7 @@ use crate::{
         node::NodeData, Entity, EntityBuilder, HierarchicalArena, Index, Indexable,
         InteractiveComponent, LayerIndex, LayerIndexable, Layout, UILayer, UIState,
     },
+    window::{event::WindowEvent, FrameCapture, FrameRecording},
     KludgineError, KludgineResult,
 };
 use async_handle::Handle;
@@ -206,6 +207,34 @@ impl Context {
         self.ui_state.set_needs_redraw().await;
     }
 
+    /// Requests that the next frame rendered by the window be captured.
+    pub async fn capture_next_frame(&self) -> FrameCapture {
+        let (sender, receiver) = async_channel::bounded(1);
+        self.ui_state
+            .send_window_event(WindowEvent::CaptureFrame(sender))
+            .await;
+        FrameCapture { receiver }
+    }
+
+    /// Saves every frame the window renders until [`Context::stop_recording_frames`]
+    /// is called. Returns an error if the recording's directory can't be
+    /// created.
+    pub async fn start_recording_frames(&self, recording: FrameRecording) -> KludgineResult<()> {
+        recording.create_directory()?;
+        self.ui_state
+            .send_window_event(WindowEvent::SetFrameRecording(Some(recording)))
+            .await;
+        Ok(())
+    }
+
+    /// Stops saving the frames the window renders. Frames that have already
+    /// been rendered are still saved.
+    pub async fn stop_recording_frames(&self) {
+        self.ui_state
+            .send_window_event(WindowEvent::SetFrameRecording(None))
+            .await;
+    }
+
     pub async fn estimate_next_frame(&self, duration: Duration) {
         self.ui_state.estimate_next_frame(duration).await;
     }
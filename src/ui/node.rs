@@ -1,10 +1,9 @@
 use crate::{
     math::{Point, Scaled, Size, Surround, Vector},
-    runtime::Runtime,
     style::StyleSheet,
     ui::{
-        AbsoluteBounds, Callback, ContentOffset, Context, InteractiveComponent, Layout,
-        LayoutSolver, StyledContext,
+        spawn_delivery, AbsoluteBounds, Callback, ContentOffset, Context, InteractiveComponent,
+        Layout, LayoutSolver, StyledContext,
     },
     window::{
         event::{EventStatus, MouseButton, MouseScrollDelta, TouchPhase},
@@ -202,15 +201,14 @@ impl<T: InteractiveComponent + 'static> AnyNode for NodeData<T> {
         let message = message.downcast_ref::<T::Message>().unwrap().clone();
         let component_handle = self.component::<T>().await.unwrap();
         let mut context = context.clone();
-        Runtime::spawn(async move {
+        spawn_delivery(async move {
             let mut component = component_handle.write().await;
             component
                 .receive_message(&mut context, message)
                 .await
                 .filter_invalid_component_references()
                 .unwrap();
-        })
-        .detach();
+        });
     }
 
     async fn initialize(&self, context: &mut Context) -> KludgineResult<()> {
@@ -371,10 +369,9 @@ impl<T: InteractiveComponent + 'static> CallbackSender for NodeData<T> {
     async fn send_callback(&self, output: Box<dyn Any + Send + Sync>) -> bool {
         let output = output.downcast_ref::<T::Event>().unwrap().clone();
         if let Some(callback) = self.callback.clone() {
-            Runtime::spawn(async move {
+            spawn_delivery(async move {
                 callback.invoke(output).await;
-            })
-            .detach();
+            });
             true
         } else {
             false
@@ -2,7 +2,7 @@ use crate::{
     math::{Scaled, Size},
     runtime::Runtime,
     style::theme::{Minimal, SystemTheme, Theme},
-    ui::InteractiveComponent,
+    ui::{Context, InteractiveComponent},
     Handle, KludgineError, KludgineResult,
 };
 use async_trait::async_trait;
@@ -12,12 +12,17 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 use winit::window::{WindowBuilder as WinitWindowBuilder, WindowId};
 
+mod capture;
 pub mod event;
 pub(crate) mod frame;
 mod renderer;
 mod runtime_window;
+mod software_renderer;
 
+pub(crate) use capture::FrameCapturer;
+pub use capture::{FrameCapture, FrameRecording};
 pub(crate) use runtime_window::RuntimeWindow;
+pub use software_renderer::SoftwareRenderer;
 
 pub use winit::window::Icon;
 
@@ -48,6 +53,29 @@ pub trait Window: InteractiveComponent + Send + Sync + 'static {
     fn theme(&self) -> Theme {
         Minimal::default().theme()
     }
+
+    /// Requests that the next frame rendered by this window be captured. See
+    /// [`Context::capture_next_frame`].
+    async fn capture_next_frame(&self, context: &Context) -> FrameCapture {
+        context.capture_next_frame().await
+    }
+
+    /// Saves every frame this window renders until
+    /// [`Window::stop_recording_frames`] is called. Returns an error if the
+    /// recording's directory can't be created.
+    async fn start_recording_frames(
+        &self,
+        context: &Context,
+        recording: FrameRecording,
+    ) -> KludgineResult<()> {
+        context.start_recording_frames(recording).await
+    }
+
+    /// Stops saving the frames this window renders. See
+    /// [`Context::stop_recording_frames`].
+    async fn stop_recording_frames(&self, context: &Context) {
+        context.stop_recording_frames().await
+    }
 }
 
 pub trait WindowCreator: Window {
@@ -0,0 +1,250 @@
+use crate::{runtime::Runtime, window::frame::Frame, KludgineError, KludgineResult};
+use image::RgbaImage;
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// Configures a window to save every frame it renders as a numbered PNG.
+#[derive(Debug, Clone)]
+pub struct FrameRecording {
+    /// The directory the frames are written to, as `frame-000001.png`,
+    /// `frame-000002.png`, and so on.
+    pub directory: PathBuf,
+    /// When set, each recorded frame advances the scene's clock by this amount
+    /// instead of following the system clock, which makes recordings of
+    /// animations deterministic.
+    pub frame_interval: Option<Duration>,
+}
+
+impl FrameRecording {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+            frame_interval: None,
+        }
+    }
+
+    pub fn with_frame_interval(mut self, frame_interval: Duration) -> Self {
+        self.frame_interval = Some(frame_interval);
+        self
+    }
+
+    /// Creates the directory the frames are written to.
+    pub(crate) fn create_directory(&self) -> KludgineResult<()> {
+        std::fs::create_dir_all(&self.directory).map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
+
+/// A pending capture of the next frame rendered by a window.
+#[derive(Debug)]
+pub struct FrameCapture {
+    pub(crate) receiver: async_channel::Receiver<KludgineResult<RgbaImage>>,
+}
+
+impl FrameCapture {
+    /// Waits for the frame to be rendered. The window renders frames after
+    /// updating its components, so this must not be awaited from within
+    /// `Component::update` or `Component::render`. Returns an error if the
+    /// window closed or the frame couldn't be read back from the GPU.
+    pub async fn image(self) -> KludgineResult<RgbaImage> {
+        self.receiver.recv().await.map_err(|_| {
+            KludgineError::InternalWindowMessageSendError(
+                "the frame could not be captured".to_owned(),
+            )
+        })?
+    }
+}
+
+/// Captures rendered frames for a window. The window's renderer reads each
+/// captured frame back from the GPU once it has been drawn.
+#[derive(Default, Debug)]
+pub(crate) struct FrameCapturer {
+    pending: Vec<async_channel::Sender<KludgineResult<RgbaImage>>>,
+    recording: Option<FrameRecording>,
+    recorded_frames: usize,
+    last_frame_time: Option<Instant>,
+}
+
+impl FrameCapturer {
+    pub fn capture_next_frame(&mut self, sender: async_channel::Sender<KludgineResult<RgbaImage>>) {
+        self.pending.push(sender);
+    }
+
+    /// Starts or stops recording. If the recording's directory can't be
+    /// created, an error is returned and no frames are recorded.
+    pub fn set_recording(&mut self, recording: Option<FrameRecording>) -> KludgineResult<()> {
+        self.recording = None;
+        self.recorded_frames = 0;
+        if let Some(recording) = &recording {
+            recording.create_directory()?;
+        }
+        self.recording = recording;
+        Ok(())
+    }
+
+    /// Returns the time the next frame should be started at.
+    pub fn next_frame_time(&mut self) -> Instant {
+        let frame_interval = self.recording.as_ref().and_then(|r| r.frame_interval);
+        let now = match (frame_interval, self.last_frame_time) {
+            (Some(frame_interval), Some(last_frame_time)) => last_frame_time + frame_interval,
+            _ => Instant::now(),
+        };
+        self.last_frame_time = Some(now);
+        now
+    }
+
+    /// Asks the renderer to read `frame` back after drawing it if a capture is
+    /// pending or frames are being recorded. Recorded frames are saved in the
+    /// background, and errors saving them are traced when the `tracing`
+    /// feature is enabled.
+    pub fn capture(&mut self, frame: &mut Frame) {
+        if self.pending.is_empty() && self.recording.is_none() {
+            return;
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+        frame.capture = Some(sender);
+
+        let recording_path = if let Some(recording) = &self.recording {
+            self.recorded_frames += 1;
+            Some(
+                recording
+                    .directory
+                    .join(format!("frame-{:06}.png", self.recorded_frames)),
+            )
+        } else {
+            None
+        };
+        let pending = std::mem::take(&mut self.pending);
+        Runtime::spawn(async move {
+            // If the renderer drops the sender, the pending captures are
+            // dropped and fail too.
+            match receiver.recv().await {
+                Ok(Ok(image)) => {
+                    if let Some(path) = recording_path {
+                        if let Err(_err) = image.save(&path) {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("error saving recorded frame {:?}: {}", path, _err);
+                        }
+                    }
+
+                    for sender in pending {
+                        // The requester may no longer be waiting for the image.
+                        let _ = sender.try_send(Ok(image.clone()));
+                    }
+                }
+                Ok(Err(err)) => {
+                    let message = err.to_string();
+                    for sender in pending {
+                        let _ =
+                            sender.try_send(Err(KludgineError::FrameCaptureError(message.clone())));
+                    }
+                }
+                Err(_) => {}
+            }
+        })
+        .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// Stands in for the renderer by answering the frame's capture request
+    /// with a solid image.
+    fn render(frame: &mut Frame, color: Rgba<u8>) -> RgbaImage {
+        let image = RgbaImage::from_pixel(4, 4, color);
+        if let Some(sender) = frame.capture.take() {
+            sender.try_send(Ok(image.clone())).unwrap();
+        }
+        image
+    }
+
+    #[async_test]
+    async fn capture_and_record_test() -> KludgineResult<()> {
+        Runtime::initialize_headless();
+        let directory =
+            std::env::temp_dir().join(format!("kludgine-capture-{}", std::process::id()));
+        let mut capturer = FrameCapturer::default();
+        capturer.set_recording(Some(
+            FrameRecording::new(&directory).with_frame_interval(Duration::from_millis(10)),
+        ))?;
+
+        let mut frame_times = Vec::new();
+        let mut captures = Vec::new();
+        let mut rendered = Vec::new();
+        for color in &[Rgba([0, 0, 255, 255]), Rgba([255, 0, 0, 255])] {
+            let (sender, receiver) = async_channel::bounded(1);
+            capturer.capture_next_frame(sender);
+            captures.push(FrameCapture { receiver });
+
+            frame_times.push(capturer.next_frame_time());
+            let mut frame = Frame::default();
+            capturer.capture(&mut frame);
+            rendered.push(render(&mut frame, *color));
+        }
+
+        assert_eq!(frame_times[1] - frame_times[0], Duration::from_millis(10));
+
+        for (capture, rendered) in captures.into_iter().zip(rendered.iter()) {
+            assert_eq!(&capture.image().await?, rendered);
+        }
+
+        // Recorded frames are saved before pending captures are answered.
+        let recorded = image::open(directory.join("frame-000002.png"))?.to_rgba8();
+        assert_eq!(recorded, rendered[1]);
+        assert!(!directory.join("frame-000003.png").exists());
+
+        std::fs::remove_dir_all(&directory).map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+
+    #[async_test]
+    async fn capture_errors_test() -> KludgineResult<()> {
+        Runtime::initialize_headless();
+        let mut capturer = FrameCapturer::default();
+
+        // A frame that couldn't be read back fails the capture with the
+        // renderer's error instead of stopping the window.
+        let (sender, receiver) = async_channel::bounded(1);
+        capturer.capture_next_frame(sender);
+        let mut frame = Frame::default();
+        capturer.capture(&mut frame);
+        frame
+            .capture
+            .take()
+            .unwrap()
+            .try_send(Err(KludgineError::OutOfBounds))
+            .unwrap();
+        assert!(matches!(
+            FrameCapture { receiver }.image().await,
+            Err(KludgineError::FrameCaptureError(_))
+        ));
+
+        // A dropped request fails the capture too.
+        let (sender, receiver) = async_channel::bounded(1);
+        capturer.capture_next_frame(sender);
+        let mut frame = Frame::default();
+        capturer.capture(&mut frame);
+        frame.capture = None;
+        assert!(FrameCapture { receiver }.image().await.is_err());
+
+        // A recording directory that can't be created is reported.
+        let file =
+            std::env::temp_dir().join(format!("kludgine-capture-{}.file", std::process::id()));
+        std::fs::write(&file, b"").map_err(anyhow::Error::from)?;
+        assert!(capturer
+            .set_recording(Some(FrameRecording::new(file.join("frames"))))
+            .is_err());
+        let mut frame = Frame::default();
+        capturer.capture(&mut frame);
+        assert!(frame.capture.is_none());
+
+        std::fs::remove_file(&file).map_err(anyhow::Error::from)?;
+        Ok(())
+    }
+}
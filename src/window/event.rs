@@ -1,7 +1,10 @@
 use crate::{
     math::{Point, Scaled, ScreenScale, Size},
     style::theme::SystemTheme,
+    window::FrameRecording,
+    KludgineResult,
 };
+use image::RgbaImage;
 pub use winit::event::{
     DeviceId, ElementState, MouseButton, MouseScrollDelta, ScanCode, TouchPhase, VirtualKeyCode,
 };
@@ -74,4 +77,6 @@ pub(crate) enum WindowEvent {
     ReceiveCharacter(char),
     RedrawRequested,
     SystemThemeChanged(SystemTheme),
+    CaptureFrame(async_channel::Sender<KludgineResult<RgbaImage>>),
+    SetFrameRecording(Option<FrameRecording>),
 }
@@ -4,11 +4,15 @@ use crate::{
     shape, sprite,
     text::{font::LoadedFont, prepared::PreparedSpan},
     texture::Texture,
+    KludgineResult,
 };
+use async_lock::Mutex;
 use easygpu::transform::ScreenSpace;
-use euclid::Rect;
+use euclid::{Rect, Transform2D};
+use image::RgbaImage;
 use std::{
     collections::{HashMap, HashSet},
+    sync::Arc,
     time::Instant,
 };
 #[derive(Default, Debug)]
@@ -18,8 +22,28 @@ pub(crate) struct Frame {
     pub size: Size<f32, ScreenSpace>,
     pub commands: Vec<FrameCommand>,
     pub(crate) textures: HashMap<u64, Texture>,
+    /// The ids of the prepared shapes that have been loaded
+    pub(crate) meshes: HashSet<u64>,
     pub(crate) fonts: HashMap<u64, LoadedFont>,
     pub(crate) pending_font_updates: Vec<FontUpdate>,
+    /// Offscreen targets to render before this frame, in the order they were
+    /// realized
+    pub(crate) offscreen_frames: Vec<OffscreenFrame>,
+    /// The texture ids of dropped offscreen targets, released once this frame
+    /// has been drawn
+    pub(crate) released_offscreen_textures: Vec<u64>,
+    /// Receives this frame's pixels once it has been drawn, when a capture was
+    /// requested
+    pub(crate) capture: Option<async_channel::Sender<KludgineResult<RgbaImage>>>,
+}
+
+/// The contents of an [`OffscreenTarget`](crate::scene::OffscreenTarget),
+/// rendered into the texture with the id `texture_id`. The frame is shared
+/// with the target, which updates it each time it's realized.
+#[derive(Debug)]
+pub(crate) struct OffscreenFrame {
+    pub texture_id: u64,
+    pub frame: Arc<Mutex<Frame>>,
 }
 
 #[derive(Debug)]
@@ -83,13 +107,23 @@ impl Frame {
     )]
     pub async fn update(&mut self, scene: &Target) {
         self.started_at = Some(scene.now().await);
-        self.commands.clear();
+        // Loads and unloads from an update that was never rendered are kept,
+        // since the loaded textures and meshes are tracked from them
+        self.commands.retain(FrameCommand::changes_resources);
+        for offscreen in scene.take_offscreen_frames().await {
+            self.offscreen_frames
+                .retain(|queued| queued.texture_id != offscreen.texture_id);
+            self.offscreen_frames.push(offscreen);
+        }
+        self.released_offscreen_textures
+            .extend(scene.take_released_offscreen_textures().await);
 
         self.cache_glyphs(scene).await;
 
         self.size = scene.internal_size().await.cast_unit();
 
         let mut referenced_texture_ids = HashSet::new();
+        let mut referenced_mesh_ids = HashSet::new();
 
         let mut current_texture_id: Option<u64> = None;
         let mut current_batch: Option<FrameBatch> = None;
@@ -102,6 +136,12 @@ impl Frame {
                 } => {
                     let sprite = sprite_handle.data.clone();
                     let texture = &sprite.source.texture;
+                    // Batches are drawn with the contents their texture has
+                    // when they are committed, so loading a newer revision
+                    // of a texture starts a new batch
+                    if self.is_newer_revision(texture) {
+                        current_batch = self.commit_batch(current_batch);
+                    }
 
                     if current_texture_id.is_none()
                         || current_texture_id.as_ref().unwrap() != &texture.id
@@ -118,18 +158,11 @@ impl Frame {
                         self.commit_batch(current_batch);
                         current_texture_id = Some(texture.id);
                         referenced_texture_ids.insert(texture.id);
-
-                        // Load the texture if needed
-                        if !self.textures.contains_key(&texture.id) {
-                            self.textures
-                                .insert(texture.id, sprite.source.texture.clone());
-                            self.commands
-                                .push(FrameCommand::LoadTexture(sprite.source.texture.clone()));
-                        }
+                        self.load_texture(texture);
 
                         current_batch = Some(FrameBatch::Sprite(sprite::Batch::new(
                             texture.id,
-                            texture.size(),
+                            self.textures[&texture.id].size(),
                             *clip,
                         )));
                     }
@@ -145,16 +178,49 @@ impl Frame {
                     });
                 }
                 Element::Shape(shape) => {
-                    if current_batch.is_some() && !current_batch.as_ref().unwrap().is_shape() {
-                        current_batch = self.commit_batch(current_batch);
-                    }
+                    for layer in shape.paint_layers() {
+                        if let Some(pattern) = layer.pattern() {
+                            // Patterns are drawn with the sprite pipeline, in a
+                            // batch of their own to keep the drawing order.
+                            let texture = &pattern.source.texture;
+                            self.commit_batch(current_batch);
+                            referenced_texture_ids.insert(texture.id);
+                            self.load_texture(texture);
 
-                    if current_batch.is_none() {
-                        current_batch = Some(FrameBatch::Shape(shape::Batch::default()));
-                    }
+                            let mut batch = sprite::Batch::new(
+                                texture.id,
+                                self.textures[&texture.id].size(),
+                                None,
+                            );
+                            batch.shapes.push(layer.clone());
+                            current_batch = self.commit_batch(Some(FrameBatch::Sprite(batch)));
+                            current_texture_id = None;
+                            continue;
+                        }
+
+                        if current_batch.is_some() && !current_batch.as_ref().unwrap().is_shape() {
+                            current_batch = self.commit_batch(current_batch);
+                        }
 
-                    let current_batch = current_batch.as_mut().unwrap().shape_batch_mut().unwrap();
-                    current_batch.add(shape.clone());
+                        if current_batch.is_none() {
+                            current_batch = Some(FrameBatch::Shape(shape::Batch::default()));
+                        }
+
+                        let current_batch =
+                            current_batch.as_mut().unwrap().shape_batch_mut().unwrap();
+                        current_batch.add(layer);
+                    }
+                }
+                Element::PreparedShape { shape, transform } => {
+                    current_batch = self.commit_batch(current_batch);
+                    referenced_mesh_ids.insert(shape.id);
+                    if self.meshes.insert(shape.id) {
+                        self.commands.push(FrameCommand::LoadMesh(shape.clone()));
+                    }
+                    self.commands.push(FrameCommand::DrawMesh {
+                        id: shape.id,
+                        transform: *transform,
+                    });
                 }
             }
         }
@@ -171,9 +237,37 @@ impl Frame {
             self.textures.remove(&id);
         }
 
+        let dead_mesh_ids = self
+            .meshes
+            .iter()
+            .filter(|id| !referenced_mesh_ids.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in dead_mesh_ids {
+            self.meshes.remove(&id);
+            self.commands.push(FrameCommand::UnloadMesh(id));
+        }
+
         self.updated_at = Some(Instant::now());
     }
 
+    /// Loads `texture` if it hasn't been loaded, or if it's a newer revision
+    /// of a loaded texture
+    fn load_texture(&mut self, texture: &Texture) {
+        if !self.textures.contains_key(&texture.id) || self.is_newer_revision(texture) {
+            self.textures.insert(texture.id, texture.clone());
+            self.commands
+                .push(FrameCommand::LoadTexture(texture.clone()));
+        }
+    }
+
+    fn is_newer_revision(&self, texture: &Texture) -> bool {
+        matches!(
+            self.textures.get(&texture.id),
+            Some(loaded) if loaded.revision < texture.revision
+        )
+    }
+
     fn commit_batch(&mut self, batch: Option<FrameBatch>) -> Option<FrameBatch> {
         if let Some(batch) = batch {
             match batch {
@@ -243,8 +337,24 @@ pub(crate) enum FrameCommand {
     LoadTexture(Texture),
     DrawBatch(sprite::Batch),
     DrawShapes(shape::Batch),
+    LoadMesh(shape::PreparedShape),
+    DrawMesh {
+        id: u64,
+        transform: Transform2D<f32, Raw, Raw>,
+    },
+    UnloadMesh(u64),
     DrawText {
         text: PreparedSpan,
         clip: Option<Rect<u32, Raw>>,
     },
 }
+
+impl FrameCommand {
+    /// Returns true if this command loads or unloads a resource.
+    fn changes_resources(&self) -> bool {
+        matches!(
+            self,
+            Self::LoadTexture(_) | Self::LoadMesh(_) | Self::UnloadMesh(_)
+        )
+    }
+}
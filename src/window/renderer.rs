@@ -1,8 +1,9 @@
 use crate::{
     math::{Box2D, Point, Size, Unknown},
     runtime::Runtime,
+    shape::PreparedShape,
     sprite,
-    window::frame::{FontUpdate, Frame, FrameCommand},
+    window::frame::{FontUpdate, Frame, FrameCommand, OffscreenFrame},
     KludgineResult,
 };
 use async_lock::Mutex;
@@ -12,6 +13,8 @@ use easygpu::{
     wgpu::{FilterMode, COPY_BYTES_PER_ROW_ALIGNMENT},
 };
 use easygpu_lyon::LyonPipeline;
+use futures::future::BoxFuture;
+use image::RgbaImage;
 use std::{collections::HashMap, sync::Arc};
 
 pub(crate) struct FrameSynchronizer {
@@ -64,12 +67,72 @@ pub(crate) struct FrameRenderer {
 #[derive(Default)]
 struct GpuState {
     textures: HashMap<u64, BindingGroup>,
+    /// The revision of each texture that has been uploaded
+    texture_revisions: HashMap<u64, u64>,
+    meshes: HashMap<u64, LoadedMesh>,
+    /// The framebuffers offscreen targets were rendered into
+    framebuffers: HashMap<u64, Framebuffer>,
+    /// The frames of the offscreen targets that have been rendered, whose
+    /// meshes are unloaded once the targets are dropped
+    offscreen_frames: HashMap<u64, Arc<Mutex<Frame>>>,
+    /// The framebuffer the last captured frame was rendered into
+    capture_framebuffer: Option<Framebuffer>,
+}
+
+/// A mesh shared by every frame that has loaded it
+struct LoadedMesh {
+    shape: easygpu_lyon::Shape,
+    /// The number of frames that have loaded the mesh and not unloaded it
+    frames: usize,
+}
+
+impl GpuState {
+    fn load_mesh(&mut self, renderer: &Renderer, shape: &PreparedShape) {
+        self.meshes
+            .entry(shape.id)
+            .or_insert_with(|| LoadedMesh {
+                shape: shape.mesh.prepare(renderer),
+                frames: 0,
+            })
+            .frames += 1;
+    }
+
+    fn unload_mesh(&mut self, id: u64) {
+        if let Some(mesh) = self.meshes.get_mut(&id) {
+            mesh.frames -= 1;
+            if mesh.frames == 0 {
+                self.meshes.remove(&id);
+            }
+        }
+    }
+
+    /// Releases everything loaded for the offscreen targets that were dropped
+    /// before `engine_frame` was drawn.
+    async fn release_offscreen_textures(&mut self, engine_frame: &mut Frame) {
+        for texture_id in std::mem::take(&mut engine_frame.released_offscreen_textures) {
+            self.textures.remove(&texture_id);
+            self.texture_revisions.remove(&texture_id);
+            self.framebuffers.remove(&texture_id);
+            if let Some(frame) = self.offscreen_frames.remove(&texture_id) {
+                let frame = frame.lock().await;
+                for &id in frame.meshes.iter() {
+                    self.unload_mesh(id);
+                }
+            }
+        }
+    }
 }
 
 enum RenderCommand {
     SpriteBuffer(u64, sprite::BatchBuffers),
     FontBuffer(u64, sprite::BatchBuffers),
     Shapes(easygpu_lyon::Shape),
+    Mesh {
+        id: u64,
+        binding: BindingGroup,
+        // Kept alive until the mesh is drawn
+        _uniforms: UniformBuffer,
+    },
 }
 
 impl FrameRenderer {
@@ -129,6 +192,10 @@ impl FrameRenderer {
             return Ok(());
         }
 
+        for offscreen in std::mem::take(&mut engine_frame.offscreen_frames) {
+            self.render_offscreen(offscreen).await?;
+        }
+
         if self.swap_chain.size != frame_size {
             self.swap_chain = self.renderer.swap_chain(frame_size, PresentMode::Vsync);
         }
@@ -138,28 +205,226 @@ impl FrameRenderer {
             Err(wgpu::SwapChainError::Outdated) => return Ok(()), // Ignore outdated, we'll draw next time.
             Err(err) => panic!("Unrecoverable error on swap chain {:?}", err),
         };
-        let mut frame = self.renderer.frame();
-
-        let ortho = ScreenTransformation::ortho(
-            0.,
-            output.size.width as f32,
-            output.size.height as f32,
-            0.,
-            -1.,
-            1.,
+
+        let mut gpu_state = self
+            .gpu_state
+            .try_lock()
+            .expect("There should be no contention");
+        let capture = match engine_frame.capture.take() {
+            Some(capture) => capture,
+            None => {
+                Self::draw_frame(
+                    &mut self.renderer,
+                    &self.sprite_pipeline,
+                    &self.shape_pipeline,
+                    &mut gpu_state,
+                    engine_frame,
+                    &output,
+                    output.size,
+                )?;
+                gpu_state.release_offscreen_textures(engine_frame).await;
+                return Ok(());
+            }
+        };
+
+        // The swap chain can't be read from, so captured frames are drawn into
+        // a framebuffer that is read back and then copied to the window.
+        let framebuffer = match gpu_state.capture_framebuffer.take() {
+            Some(framebuffer) if framebuffer.texture.size == output.size => framebuffer,
+            _ => self.renderer.framebuffer(output.size),
+        };
+        Self::draw_frame(
+            &mut self.renderer,
+            &self.sprite_pipeline,
+            &self.shape_pipeline,
+            &mut gpu_state,
+            engine_frame,
+            &framebuffer,
+            output.size,
+        )?;
+        // The capture may no longer be waiting for the image.
+        let _ = capture.try_send(Self::read_framebuffer(&mut self.renderer, &framebuffer).await);
+        Self::draw_framebuffer(
+            &mut self.renderer,
+            &self.sprite_pipeline,
+            &framebuffer,
+            &output,
         );
-        self.renderer
-            .update_pipeline(&self.shape_pipeline, ortho, &mut frame);
+        gpu_state.capture_framebuffer = Some(framebuffer);
+        gpu_state.release_offscreen_textures(engine_frame).await;
 
-        self.renderer
-            .update_pipeline(&self.sprite_pipeline, ortho, &mut frame);
+        Ok(())
+    }
 
+    /// Copies the pixels of `framebuffer` back from the GPU.
+    async fn read_framebuffer(
+        renderer: &mut Renderer,
+        framebuffer: &Framebuffer,
+    ) -> KludgineResult<RgbaImage> {
+        let size = framebuffer.texture.size;
+        let bytes_per_row = size_for_aligned_copy(size.width as usize * 4);
+        let buffer = renderer.device.wgpu.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: (bytes_per_row * size.height as usize) as u64,
+            usage: wgpu::BufferUsage::MAP_READ | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
         {
-            let mut render_commands = Vec::new();
+            let mut encoder = renderer.device.create_command_encoder();
+            encoder.copy_texture_to_buffer(
+                wgpu::TextureCopyView {
+                    texture: &framebuffer.texture.wgpu,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                },
+                wgpu::BufferCopyView {
+                    buffer: &buffer,
+                    layout: wgpu::TextureDataLayout {
+                        offset: 0,
+                        bytes_per_row: bytes_per_row as u32,
+                        rows_per_image: size.height,
+                    },
+                },
+                framebuffer.texture.extent,
+            );
+            renderer.device.submit(vec![encoder.finish()]);
+        }
+
+        let slice = buffer.slice(..);
+        let mapping = slice.map_async(wgpu::MapMode::Read);
+        renderer.device.wgpu.poll(wgpu::Maintain::Wait);
+        mapping.await.map_err(anyhow::Error::from)?;
+
+        let data = slice.get_mapped_range();
+        let mut image = RgbaImage::new(size.width, size.height);
+        for (y, row) in data.chunks(bytes_per_row).enumerate() {
+            for x in 0..size.width {
+                // Framebuffers share the swap chain's BGRA format
+                let offset = x as usize * 4;
+                image.put_pixel(
+                    x,
+                    y as u32,
+                    image::Rgba([
+                        row[offset + 2],
+                        row[offset + 1],
+                        row[offset],
+                        row[offset + 3],
+                    ]),
+                );
+            }
+        }
+
+        Ok(image)
+    }
+
+    /// Draws `framebuffer` over the entirety of `target`.
+    fn draw_framebuffer<T: RenderTarget>(
+        renderer: &mut Renderer,
+        sprite_pipeline: &sprite::Pipeline,
+        framebuffer: &Framebuffer,
+        target: &T,
+    ) {
+        let size = framebuffer.texture.size;
+        let mut frame = renderer.frame();
+        let ortho =
+            ScreenTransformation::ortho(0., size.width as f32, size.height as f32, 0., -1., 1.);
+        renderer.update_pipeline(sprite_pipeline, ortho, &mut frame);
+
+        let mut batch = sprite::GpuBatch::new(size.cast_unit(), None);
+        batch.add_box(
+            Box2D::from_size(size.cast_unit()),
+            Box2D::from_size(size.to_f32().cast_unit()),
+            sprite::SpriteRotation::default(),
+            None,
+            sprite::SpriteTint::default(),
+        );
+        let buffer = batch.finish(renderer);
+        let sampler = renderer.sampler(FilterMode::Nearest, FilterMode::Nearest);
+        let binding = sprite_pipeline.binding(renderer, &framebuffer.texture, &sampler);
+        {
+            let mut pass = frame.pass(PassOp::Clear(Rgba::TRANSPARENT), target);
+            pass.set_easy_pipeline(sprite_pipeline);
+            pass.easy_draw(&buffer, &binding);
+        }
+
+        renderer.present(frame);
+    }
+
+    /// Renders an offscreen target into a framebuffer, which is kept and bound
+    /// as the target's texture so that sprites drawing it sample the GPU copy.
+    /// Offscreen targets can realize targets of their own, so the future is
+    /// boxed.
+    fn render_offscreen(&mut self, offscreen: OffscreenFrame) -> BoxFuture<'_, KludgineResult<()>> {
+        Box::pin(async move {
+            let OffscreenFrame { texture_id, frame } = offscreen;
+            let mut engine_frame = frame.lock().await;
+            for nested in std::mem::take(&mut engine_frame.offscreen_frames) {
+                self.render_offscreen(nested).await?;
+            }
+
+            let size = engine_frame.size.cast::<u32>();
+            if size.width == 0 || size.height == 0 {
+                return Ok(());
+            }
+
             let mut gpu_state = self
                 .gpu_state
                 .try_lock()
                 .expect("There should be no contention");
+            // Realizing the same target again renders into the same framebuffer
+            let framebuffer = match gpu_state.framebuffers.remove(&texture_id) {
+                Some(framebuffer) if framebuffer.texture.size == size => framebuffer,
+                _ => self.renderer.framebuffer(size),
+            };
+
+            Self::draw_frame(
+                &mut self.renderer,
+                &self.sprite_pipeline,
+                &self.shape_pipeline,
+                &mut gpu_state,
+                &mut engine_frame,
+                &framebuffer,
+                size,
+            )?;
+
+            let sampler = self
+                .renderer
+                .sampler(FilterMode::Nearest, FilterMode::Nearest);
+            let binding =
+                self.sprite_pipeline
+                    .binding(&self.renderer, &framebuffer.texture, &sampler);
+            gpu_state.textures.insert(texture_id, binding);
+            gpu_state.framebuffers.insert(texture_id, framebuffer);
+            gpu_state
+                .release_offscreen_textures(&mut engine_frame)
+                .await;
+            gpu_state.offscreen_frames.insert(texture_id, frame.clone());
+
+            Ok(())
+        })
+    }
+
+    /// Uploads the resources `engine_frame` needs and draws its commands into
+    /// `target`.
+    fn draw_frame<T: RenderTarget>(
+        renderer: &mut Renderer,
+        sprite_pipeline: &sprite::Pipeline,
+        shape_pipeline: &LyonPipeline,
+        gpu_state: &mut GpuState,
+        engine_frame: &mut Frame,
+        target: &T,
+        size: Size<u32, ScreenSpace>,
+    ) -> KludgineResult<()> {
+        let mut frame = renderer.frame();
+
+        let ortho =
+            ScreenTransformation::ortho(0., size.width as f32, size.height as f32, 0., -1., 1.);
+        renderer.update_pipeline(shape_pipeline, ortho, &mut frame);
+
+        renderer.update_pipeline(sprite_pipeline, ortho, &mut frame);
+
+        {
+            let mut render_commands = Vec::new();
 
             for FontUpdate {
                 font_id,
@@ -169,14 +434,10 @@ impl FrameRenderer {
             {
                 let mut loaded_font = engine_frame.fonts.get_mut(font_id).unwrap();
                 if loaded_font.texture.is_none() {
-                    let texture = self.renderer.texture(Size::new(512, 512)); // TODO font texture should be configurable
-                    let sampler = self
-                        .renderer
-                        .sampler(FilterMode::Linear, FilterMode::Linear);
-
-                    let binding = self
-                        .sprite_pipeline
-                        .binding(&self.renderer, &texture, &sampler);
+                    let texture = renderer.texture(Size::new(512, 512)); // TODO font texture should be configurable
+                    let sampler = renderer.sampler(FilterMode::Linear, FilterMode::Linear);
+
+                    let binding = sprite_pipeline.binding(renderer, &texture, &sampler);
                     loaded_font.binding = Some(binding);
                     loaded_font.texture = Some(texture);
                 }
@@ -197,7 +458,7 @@ impl FrameRenderer {
                 }
 
                 let pixels = Rgba8::align(&pixels);
-                self.renderer.submit(&[Op::Transfer {
+                renderer.submit(&[Op::Transfer {
                     f: loaded_font.texture.as_ref().unwrap(),
                     buf: pixels,
                     rect: Box2D::new(
@@ -213,10 +474,14 @@ impl FrameRenderer {
             for command in std::mem::take(&mut engine_frame.commands) {
                 match command {
                     FrameCommand::LoadTexture(texture) => {
-                        if !gpu_state.textures.contains_key(&texture.id) {
-                            let sampler = self
-                                .renderer
-                                .sampler(FilterMode::Nearest, FilterMode::Nearest);
+                        // Offscreen targets are bound to their framebuffers
+                        // rather than their placeholder textures
+                        if !gpu_state.framebuffers.contains_key(&texture.id)
+                            && gpu_state.texture_revisions.get(&texture.id)
+                                != Some(&texture.revision)
+                        {
+                            let sampler =
+                                renderer.sampler(FilterMode::Nearest, FilterMode::Nearest);
 
                             let (gpu_texture, texels, texture_id) = {
                                 let (w, h) = texture.image.dimensions();
@@ -238,23 +503,21 @@ impl FrameRenderer {
                                 let pixels = Rgba8::align(&pixels);
 
                                 (
-                                    self.renderer.texture(Size::new(w, h).cast::<u32>()),
+                                    renderer.texture(Size::new(w, h).cast::<u32>()),
                                     pixels.to_owned(),
                                     texture.id,
                                 )
                             };
 
-                            self.renderer
-                                .submit(&[Op::Fill(&gpu_texture, texels.as_slice())]);
+                            renderer.submit(&[Op::Fill(&gpu_texture, texels.as_slice())]);
 
                             gpu_state.textures.insert(
                                 texture_id,
-                                self.sprite_pipeline.binding(
-                                    &self.renderer,
-                                    &gpu_texture,
-                                    &sampler,
-                                ),
+                                sprite_pipeline.binding(renderer, &gpu_texture, &sampler),
                             );
+                            gpu_state
+                                .texture_revisions
+                                .insert(texture_id, texture.revision);
                         }
                     }
                     FrameCommand::DrawBatch(batch) => {
@@ -265,17 +528,46 @@ impl FrameRenderer {
                         for sprite_handle in batch.sprites.iter() {
                             gpu_batch.add_sprite(sprite_handle.clone());
                         }
+                        for shape in batch.shapes.iter() {
+                            gpu_batch.add_pattern_shape(shape)?;
+                        }
                         render_commands.push(RenderCommand::SpriteBuffer(
                             batch.loaded_texture_id,
-                            gpu_batch.finish(&self.renderer),
+                            gpu_batch.finish(renderer),
                         ));
                     }
                     FrameCommand::DrawShapes(batch) => {
-                        render_commands.push(RenderCommand::Shapes(batch.finish(&self.renderer)?));
+                        render_commands.push(RenderCommand::Shapes(batch.finish(renderer)?));
                         // let prepared_shape = batch.finish(&self.renderer)?;
                         // pass.set_easy_pipeline(&self.shape_pipeline);
                         // prepared_shape.draw(&mut pass);
                     }
+                    FrameCommand::LoadMesh(shape) => {
+                        gpu_state.load_mesh(renderer, &shape);
+                    }
+                    FrameCommand::DrawMesh { id, transform } => {
+                        // Each mesh is drawn with its own transform, which the
+                        // shape pipeline's shader applies before the projection
+                        let uniforms =
+                            renderer
+                                .device
+                                .create_uniform_buffer(&[easygpu_lyon::Uniforms {
+                                    ortho,
+                                    transform: transform.to_3d().with_source().with_destination(),
+                                }]);
+                        let binding = renderer.device.create_binding_group(
+                            &shape_pipeline.pipeline.layout.sets[0],
+                            &[&uniforms],
+                        );
+                        render_commands.push(RenderCommand::Mesh {
+                            id,
+                            binding,
+                            _uniforms: uniforms,
+                        });
+                    }
+                    FrameCommand::UnloadMesh(id) => {
+                        gpu_state.unload_mesh(id);
+                    }
                     FrameCommand::DrawText { text, clip } => {
                         if let Some(loaded_font) = engine_frame.fonts.get(&text.data.font.id) {
                             if let Some(texture) = loaded_font.texture.as_ref() {
@@ -316,12 +608,13 @@ impl FrameRenderer {
                                         source.cast_unit().cast(),
                                         dest,
                                         sprite::SpriteRotation::default(),
-                                        text.data.color.into(),
+                                        None,
+                                        sprite::SpriteTint::new(text.data.color),
                                     );
                                 }
                                 render_commands.push(RenderCommand::FontBuffer(
                                     loaded_font.font.id,
-                                    batch.finish(&self.renderer),
+                                    batch.finish(renderer),
                                 ));
                             }
 
@@ -338,16 +631,18 @@ impl FrameRenderer {
                     }
                 }
             }
-            let mut pass = frame.pass(PassOp::Clear(Rgba::TRANSPARENT), &output);
+            let mut pass = frame.pass(PassOp::Clear(Rgba::TRANSPARENT), target);
             for command in &render_commands {
                 match command {
                     RenderCommand::SpriteBuffer(texture_id, buffer) => {
-                        pass.set_easy_pipeline(&self.sprite_pipeline);
-                        let binding = gpu_state.textures.get(texture_id).unwrap();
-                        pass.easy_draw(buffer, binding);
+                        // Released offscreen targets' textures draw nothing
+                        if let Some(binding) = gpu_state.textures.get(texture_id) {
+                            pass.set_easy_pipeline(sprite_pipeline);
+                            pass.easy_draw(buffer, binding);
+                        }
                     }
                     RenderCommand::FontBuffer(font_id, buffer) => {
-                        pass.set_easy_pipeline(&self.sprite_pipeline);
+                        pass.set_easy_pipeline(sprite_pipeline);
                         if let Some(binding) = engine_frame
                             .fonts
                             .get(font_id)
@@ -358,14 +653,21 @@ impl FrameRenderer {
                         }
                     }
                     RenderCommand::Shapes(shapes) => {
-                        pass.set_easy_pipeline(&self.shape_pipeline);
+                        pass.set_easy_pipeline(shape_pipeline);
                         shapes.draw(&mut pass);
                     }
+                    RenderCommand::Mesh { id, binding, .. } => {
+                        if let Some(mesh) = gpu_state.meshes.get(id) {
+                            pass.set_easy_pipeline(shape_pipeline);
+                            pass.set_binding(binding, &[]);
+                            mesh.shape.draw(&mut pass);
+                        }
+                    }
                 }
             }
         }
 
-        self.renderer.present(frame);
+        renderer.present(frame);
 
         Ok(())
     }
@@ -6,10 +6,10 @@ use crate::{
     style::theme::SystemTheme,
     ui::{global_arena, UserInterface},
     window::{
-        event::{ElementState, Event, InputEvent, VirtualKeyCode, WindowEvent},
+        event::{Event, InputEvent, VirtualKeyCode, WindowEvent},
         frame::Frame,
         renderer::{FrameRenderer, FrameSynchronizer},
-        CloseResponse, Renderer, Window, WindowMessage, WINDOWS, WINDOW_CHANNELS,
+        CloseResponse, FrameCapturer, Renderer, Window, WindowMessage, WINDOWS, WINDOW_CHANNELS,
     },
     KludgineError, KludgineResult, KludgineResultExt,
 };
@@ -184,6 +184,7 @@ impl RuntimeWindow {
             UserInterface::new(window, scene.clone(), global_arena().clone(), event_sender).await?;
         #[cfg(feature = "bundled-fonts-enabled")]
         scene.register_bundled_fonts().await;
+        let mut frame_capturer = FrameCapturer::default();
         loop {
             while let Some(event) = match Self::next_window_event(&mut event_receiver, &ui)
                 .await
@@ -212,15 +213,7 @@ impl RuntimeWindow {
                     WindowEvent::Input(input) => {
                         if let Event::Keyboard { key, state, .. } = input.event {
                             if let Some(key) = key {
-                                let mut scene = scene.data.write().await;
-                                match state {
-                                    ElementState::Pressed => {
-                                        scene.pressed_keys.insert(key);
-                                    }
-                                    ElementState::Released => {
-                                        scene.pressed_keys.remove(&key);
-                                    }
-                                }
+                                scene.set_key_state(key, state).await;
                             }
                         }
 
@@ -236,6 +229,19 @@ impl RuntimeWindow {
                     WindowEvent::RedrawRequested => {
                         ui.request_redraw().await;
                     }
+                    WindowEvent::CaptureFrame(sender) => {
+                        frame_capturer.capture_next_frame(sender);
+                        ui.request_redraw().await;
+                    }
+                    WindowEvent::SetFrameRecording(recording) => {
+                        // Context::start_recording_frames has already created
+                        // the directory and reported any error creating it.
+                        if let Err(_err) = frame_capturer.set_recording(recording) {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!("error starting frame recording: {}", _err);
+                        }
+                        ui.request_redraw().await;
+                    }
                 }
             }
 
@@ -253,7 +259,7 @@ impl RuntimeWindow {
             }
 
             if scene.size().await.area() > 0.0 {
-                scene.start_frame().await;
+                scene.start_frame_at(frame_capturer.next_frame_time()).await;
 
                 ui.update(&Target::from(scene.clone()), target_fps)
                     .await
@@ -264,6 +270,7 @@ impl RuntimeWindow {
 
                     let mut frame = frame_synchronizer.take().await;
                     frame.update(&Target::from(scene.clone())).await;
+                    frame_capturer.capture(&mut frame);
                     frame_synchronizer.relinquish(frame).await;
                 }
             }
@@ -0,0 +1,834 @@
+use crate::{
+    math::{Box2D, Point, Raw, Size, Unknown},
+    scene::Target,
+    shape::Mesh,
+    sprite::{self, SpriteRotation, SpriteTint},
+    texture::Texture,
+    window::frame::{FontUpdate, Frame, FrameCommand, OffscreenFrame},
+    KludgineResult,
+};
+use easygpu::color::Rgba8;
+use euclid::Transform2D;
+use futures::future::BoxFuture;
+use image::{Rgba, RgbaImage};
+use std::{collections::HashMap, sync::Arc};
+
+/// The size of the glyph cache texture, matching the one created by the GPU renderer
+const GLYPH_CACHE_SIZE: u32 = 512;
+
+/// Renders a [`Scene`](crate::scene::Scene) on the CPU instead of the GPU.
+///
+/// The renderer consumes the same frame commands as a window's GPU renderer,
+/// which allows entire scenes to be rendered in environments without a GPU
+/// or a window, such as automated tests.
+#[derive(Default, Debug)]
+pub struct SoftwareRenderer {
+    frame: Frame,
+    textures: HashMap<u64, Texture>,
+    meshes: HashMap<u64, Arc<Mesh>>,
+    glyph_textures: HashMap<u64, RgbaImage>,
+    /// Renderers for the offscreen targets realized into this renderer's
+    /// frames, which keep the resources each target has loaded
+    offscreen_renderers: HashMap<u64, SoftwareRenderer>,
+    /// Rendered offscreen targets. Their textures can be drawn long after
+    /// they were realized, so they're kept until the targets are dropped.
+    offscreen_textures: HashMap<u64, RgbaImage>,
+}
+
+impl SoftwareRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Renders the elements currently drawn in `scene`. A frame must have been
+    /// started on the scene before calling this method.
+    pub async fn render(&mut self, scene: &Target) -> KludgineResult<RgbaImage> {
+        self.frame.update(scene).await;
+        let mut frame = std::mem::take(&mut self.frame);
+        let result = self.render_frame(&mut frame).await;
+        self.frame = frame;
+        result
+    }
+
+    /// Renders `frame` after the offscreen targets it realized. Offscreen
+    /// targets can realize targets of their own, so the future is boxed.
+    fn render_frame<'a>(
+        &'a mut self,
+        frame: &'a mut Frame,
+    ) -> BoxFuture<'a, KludgineResult<RgbaImage>> {
+        Box::pin(async move {
+            for OffscreenFrame {
+                texture_id,
+                frame: offscreen,
+            } in std::mem::take(&mut frame.offscreen_frames)
+            {
+                // Each offscreen target has its own glyph cache and loads the
+                // textures and meshes it uses, so it's rendered by a renderer
+                // of its own.
+                let mut offscreen = offscreen.lock().await;
+                let image = self
+                    .offscreen_renderers
+                    .entry(texture_id)
+                    .or_default()
+                    .render_frame(&mut offscreen)
+                    .await?;
+                self.offscreen_textures.insert(texture_id, image);
+            }
+
+            let image = self.draw_frame(frame)?;
+
+            for texture_id in std::mem::take(&mut frame.released_offscreen_textures) {
+                self.offscreen_renderers.remove(&texture_id);
+                self.offscreen_textures.remove(&texture_id);
+            }
+
+            Ok(image)
+        })
+    }
+
+    fn draw_frame(&mut self, frame: &mut Frame) -> KludgineResult<RgbaImage> {
+        let size = frame.size.cast::<u32>();
+        let mut rasterizer = Rasterizer::new(size.width, size.height);
+
+        self.apply_font_updates(frame);
+
+        for command in std::mem::take(&mut frame.commands) {
+            match command {
+                FrameCommand::LoadTexture(texture) => {
+                    self.textures.insert(texture.id, texture);
+                }
+                FrameCommand::DrawBatch(batch) => {
+                    let texture = self
+                        .offscreen_textures
+                        .get(&batch.loaded_texture_id)
+                        .or_else(|| {
+                            self.textures
+                                .get(&batch.loaded_texture_id)
+                                .map(|texture| texture.image.as_ref())
+                        });
+                    if let Some(texture) = texture {
+                        let clip = batch.clipping_rect.map(|r| r.to_box2d());
+                        let mut gpu_batch = sprite::GpuBatch::new(batch.size.cast_unit(), clip);
+                        for sprite_handle in batch.sprites.iter() {
+                            gpu_batch.add_sprite(sprite_handle.clone());
+                        }
+                        for shape in batch.shapes.iter() {
+                            gpu_batch.add_pattern_shape(shape)?;
+                        }
+                        rasterizer.draw_sprites(&gpu_batch, texture, Filter::Nearest, clip);
+                    }
+                }
+                FrameCommand::DrawShapes(batch) => {
+                    rasterizer.draw_mesh(&batch.tessellate()?, None);
+                }
+                FrameCommand::LoadMesh(shape) => {
+                    self.meshes.insert(shape.id, shape.mesh);
+                }
+                FrameCommand::DrawMesh { id, transform } => {
+                    if let Some(mesh) = self.meshes.get(&id) {
+                        rasterizer.draw_mesh(mesh, Some(&transform));
+                    }
+                }
+                FrameCommand::UnloadMesh(id) => {
+                    self.meshes.remove(&id);
+                }
+                FrameCommand::DrawText { text, clip } => {
+                    let loaded_font = frame.fonts.get(&text.data.font.id);
+                    let glyph_texture = self.glyph_textures.get(&text.data.font.id);
+                    if let (Some(loaded_font), Some(glyph_texture)) = (loaded_font, glyph_texture) {
+                        let clip = clip.map(|r| r.to_box2d());
+                        let mut batch = sprite::GpuBatch::new(
+                            Size::new(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE),
+                            clip,
+                        );
+                        let cache_size = GLYPH_CACHE_SIZE as f32;
+                        for (uv_rect, screen_rect) in
+                            text.data.glyphs.iter().filter_map(|g| {
+                                loaded_font.cache.rect_for(0, &g.glyph).ok().flatten()
+                            })
+                        {
+                            // Unlike the GPU renderer, the glyph texture isn't flipped when
+                            // it is updated, so the uv coordinates can be used directly.
+                            let source = Box2D::<_, Unknown>::new(
+                                Point::new(uv_rect.min.x * cache_size, uv_rect.min.y * cache_size),
+                                Point::new(uv_rect.max.x * cache_size, uv_rect.max.y * cache_size),
+                            );
+                            let dest = Box2D::new(
+                                text.location
+                                    + euclid::Vector2D::new(
+                                        screen_rect.min.x as f32,
+                                        screen_rect.min.y as f32,
+                                    ),
+                                text.location
+                                    + euclid::Vector2D::new(
+                                        screen_rect.max.x as f32,
+                                        screen_rect.max.y as f32,
+                                    ),
+                            );
+                            batch.add_box(
+                                source.cast(),
+                                dest,
+                                SpriteRotation::default(),
+                                None,
+                                SpriteTint::new(text.data.color),
+                            );
+                        }
+                        rasterizer.draw_sprites(&batch, glyph_texture, Filter::Linear, clip);
+                    }
+                }
+            }
+        }
+
+        // Forget any textures the frame is no longer referencing. The frame will
+        // issue a new LoadTexture command if they are used again.
+        self.textures
+            .retain(|id, _| frame.textures.contains_key(id));
+
+        Ok(rasterizer.image)
+    }
+
+    fn apply_font_updates(&mut self, frame: &mut Frame) {
+        for FontUpdate {
+            font_id,
+            rect,
+            data,
+        } in frame.pending_font_updates.drain(..)
+        {
+            let texture = self.glyph_textures.entry(font_id).or_insert_with(|| {
+                RgbaImage::from_pixel(GLYPH_CACHE_SIZE, GLYPH_CACHE_SIZE, Rgba([255, 255, 255, 0]))
+            });
+            let width = rect.width();
+            for (index, coverage) in data.into_iter().enumerate() {
+                let index = index as u32;
+                let x = rect.min.x + index % width;
+                let y = rect.min.y + index / width;
+                texture.put_pixel(x, y, Rgba([255, 255, 255, coverage]));
+            }
+        }
+
+        self.glyph_textures
+            .retain(|id, _| frame.fonts.contains_key(id));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Filter {
+    Nearest,
+    Linear,
+}
+
+struct Rasterizer {
+    image: RgbaImage,
+}
+
+impl Rasterizer {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0])),
+        }
+    }
+
+    fn draw_sprites(
+        &mut self,
+        batch: &sprite::GpuBatch,
+        texture: &RgbaImage,
+        filter: Filter,
+        clip: Option<Box2D<u32, Raw>>,
+    ) {
+        for triangle in batch.triangles() {
+            let points = [
+                Point::new(triangle[0].position.x, triangle[0].position.y),
+                Point::new(triangle[1].position.x, triangle[1].position.y),
+                Point::new(triangle[2].position.x, triangle[2].position.y),
+            ];
+            self.fill_triangle(points, clip, |weights| {
+                let u = interpolate(weights, |i| triangle[i].uv.x);
+                let v = interpolate(weights, |i| triangle[i].uv.y);
+                let texel = sample(texture, u, v, filter);
+                let color = interpolate_color(weights, |i| triangle[i].color);
+                let flash = interpolate_color(weights, |i| triangle[i].flash);
+
+                // Mirrors sprite.frag: the tint color multiplies the texel, and
+                // the flash color is added scaled by its alpha.
+                [
+                    (texel[0] * color[0] + flash[0] * flash[3]).min(1.),
+                    (texel[1] * color[1] + flash[1] * flash[3]).min(1.),
+                    (texel[2] * color[2] + flash[2] * flash[3]).min(1.),
+                    texel[3] * color[3],
+                ]
+            });
+        }
+    }
+
+    fn draw_mesh(&mut self, mesh: &Mesh, transform: Option<&Transform2D<f32, Raw, Raw>>) {
+        for triangle in mesh.triangles() {
+            let location = |index: usize| match transform {
+                Some(transform) => transform.transform_point(triangle[index].location()),
+                None => triangle[index].location(),
+            };
+            let points = [location(0), location(1), location(2)];
+            self.fill_triangle(points, None, |weights| {
+                interpolate_color(weights, |i| triangle[i].color)
+            });
+        }
+    }
+
+    fn fill_triangle<F: Fn([f32; 3]) -> [f32; 4]>(
+        &mut self,
+        points: [Point<f32, Raw>; 3],
+        clip: Option<Box2D<u32, Raw>>,
+        shader: F,
+    ) {
+        let area = edge(points[0], points[1], points[2]);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let mut min_x = points.iter().map(|p| p.x).fold(f32::MAX, f32::min).floor() as i64;
+        let mut min_y = points.iter().map(|p| p.y).fold(f32::MAX, f32::min).floor() as i64;
+        let mut max_x = points.iter().map(|p| p.x).fold(f32::MIN, f32::max).ceil() as i64;
+        let mut max_y = points.iter().map(|p| p.y).fold(f32::MIN, f32::max).ceil() as i64;
+
+        min_x = min_x.max(0);
+        min_y = min_y.max(0);
+        max_x = max_x.min(self.image.width() as i64);
+        max_y = max_y.min(self.image.height() as i64);
+        if let Some(clip) = clip {
+            min_x = min_x.max(clip.min.x as i64);
+            min_y = min_y.max(clip.min.y as i64);
+            max_x = max_x.min(clip.max.x as i64);
+            max_y = max_y.min(clip.max.y as i64);
+        }
+
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let pixel_center = Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                let mut weights = [0.; 3];
+                let mut inside = true;
+                for (vertex, &(a, b)) in edges.iter().enumerate() {
+                    let weight = edge(points[a], points[b], pixel_center) / area;
+                    // Pixels exactly on an edge shared by two triangles must only be
+                    // drawn once, otherwise translucent geometry blends twice.
+                    if weight < 0. || (weight == 0. && !owns_edge(points[a], points[b], area > 0.))
+                    {
+                        inside = false;
+                        break;
+                    }
+                    weights[vertex] = weight;
+                }
+
+                if inside {
+                    self.blend(x as u32, y as u32, shader(weights));
+                }
+            }
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, source: [f32; 4]) {
+        let destination = self.image.get_pixel_mut(x, y);
+        let source_alpha = source[3].clamp(0., 1.);
+        let destination_alpha = destination[3] as f32 / 255.;
+        let mut blended = [0u8; 4];
+        for channel in 0..3 {
+            let destination_channel = destination[channel] as f32 / 255.;
+            blended[channel] =
+                to_u8(source[channel] * source_alpha + destination_channel * (1. - source_alpha));
+        }
+        blended[3] = to_u8(source_alpha + destination_alpha * (1. - source_alpha));
+        *destination = Rgba(blended);
+    }
+}
+
+fn edge(a: Point<f32, Raw>, b: Point<f32, Raw>, c: Point<f32, Raw>) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Deterministically assigns an edge to exactly one of the two triangles sharing it
+fn owns_edge(a: Point<f32, Raw>, b: Point<f32, Raw>, clockwise: bool) -> bool {
+    let (a, b) = if clockwise { (a, b) } else { (b, a) };
+    if (a.y - b.y).abs() < f32::EPSILON {
+        b.x < a.x
+    } else {
+        b.y < a.y
+    }
+}
+
+fn interpolate<F: Fn(usize) -> f32>(weights: [f32; 3], value: F) -> f32 {
+    weights[0] * value(0) + weights[1] * value(1) + weights[2] * value(2)
+}
+
+fn interpolate_color<F: Fn(usize) -> Rgba8>(weights: [f32; 3], color: F) -> [f32; 4] {
+    let colors = [color(0), color(1), color(2)];
+    [
+        interpolate(weights, |i| colors[i].r as f32 / 255.),
+        interpolate(weights, |i| colors[i].g as f32 / 255.),
+        interpolate(weights, |i| colors[i].b as f32 / 255.),
+        interpolate(weights, |i| colors[i].a as f32 / 255.),
+    ]
+}
+
+fn sample(texture: &RgbaImage, u: f32, v: f32, filter: Filter) -> [f32; 4] {
+    let (width, height) = texture.dimensions();
+    if width == 0 || height == 0 {
+        return [0.; 4];
+    }
+
+    let texel = |x: i64, y: i64| {
+        let pixel = texture.get_pixel(
+            x.max(0).min(width as i64 - 1) as u32,
+            y.max(0).min(height as i64 - 1) as u32,
+        );
+        [
+            pixel[0] as f32 / 255.,
+            pixel[1] as f32 / 255.,
+            pixel[2] as f32 / 255.,
+            pixel[3] as f32 / 255.,
+        ]
+    };
+
+    match filter {
+        Filter::Nearest => texel(
+            (u * width as f32).floor() as i64,
+            (v * height as f32).floor() as i64,
+        ),
+        Filter::Linear => {
+            let x = u * width as f32 - 0.5;
+            let y = v * height as f32 - 0.5;
+            let x0 = x.floor();
+            let y0 = y.floor();
+            let x_amount = x - x0;
+            let y_amount = y - y0;
+            let (x0, y0) = (x0 as i64, y0 as i64);
+
+            let top_left = texel(x0, y0);
+            let top_right = texel(x0 + 1, y0);
+            let bottom_left = texel(x0, y0 + 1);
+            let bottom_right = texel(x0 + 1, y0 + 1);
+            let mut result = [0.; 4];
+            for channel in 0..4 {
+                result[channel] = lerp(
+                    lerp(top_left[channel], top_right[channel], x_amount),
+                    lerp(bottom_left[channel], bottom_right[channel], x_amount),
+                    y_amount,
+                );
+            }
+            result
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, amount: f32) -> f32 {
+    a + (b - a) * amount
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0., 1.) * 255.).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        color::Color,
+        math::{Angle, Length},
+        math::{Rect, Scaled, ScreenScale},
+        scene::Scene,
+        shape::{CornerRadii, Fill, Gradient, Pattern, Shape, Stroke, StrokeDashes},
+        sprite::{SpriteSource, SpriteTransform},
+        style::theme::Minimal,
+    };
+
+    async fn headless_scene() -> Target {
+        let mut scene = Scene::headless(
+            Minimal::default().theme(),
+            Size::new(8., 8.),
+            ScreenScale::new(1.),
+        )
+        .await;
+        scene.start_frame().await;
+        Target::from(scene)
+    }
+
+    #[async_test]
+    async fn shape_fill_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(2., 2.),
+            Size::new(4., 4.),
+        ))
+        .fill(Fill::new(Color::RED))
+        .render_at(Point::default(), &scene)
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.dimensions(), (8, 8));
+        assert_eq!(image.get_pixel(3, 3), &Rgba([255, 0, 0, 255]));
+        assert_eq!(image.get_pixel(1, 1), &Rgba([0, 0, 0, 0]));
+        assert_eq!(image.get_pixel(6, 6), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn prepared_shape_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let red = Rgba([255, 0, 0, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+        let prepared = Shape::rect(Rect::<f32, Scaled>::new(
+            Point::default(),
+            Size::new(2., 2.),
+        ))
+        .fill(Fill::new(Color::RED))
+        .prepare(&scene)
+        .await?;
+        prepared.render_at(Point::new(1., 1.), &scene).await;
+        prepared
+            .render_scaled_at(Point::new(4., 4.), 2., &scene)
+            .await;
+
+        let mut renderer = SoftwareRenderer::new();
+        let image = renderer.render(&scene).await?;
+        assert_eq!(image.get_pixel(1, 1), &red);
+        assert_eq!(image.get_pixel(3, 3), &clear);
+        assert_eq!(image.get_pixel(7, 7), &red);
+
+        // Later frames draw the same mesh without tessellating it again
+        scene.scene.clone().start_frame().await;
+        prepared.render_at(Point::new(6., 0.), &scene).await;
+        let image = renderer.render(&scene).await?;
+        assert_eq!(image.get_pixel(1, 1), &clear);
+        assert_eq!(image.get_pixel(7, 1), &red);
+        assert_eq!(renderer.meshes.len(), 1);
+
+        // Meshes that are no longer drawn are unloaded
+        scene.scene.clone().start_frame().await;
+        renderer.render(&scene).await?;
+        assert!(renderer.meshes.is_empty());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn offscreen_resources_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let mut offscreen = scene.scene.offscreen(Size::new(2., 2.)).await;
+        let prepared = Shape::rect(Rect::<f32, Scaled>::new(
+            Point::default(),
+            Size::new(2., 2.),
+        ))
+        .fill(Fill::new(Color::RED))
+        .prepare(offscreen.target())
+        .await?;
+        prepared
+            .render_at(Point::default(), offscreen.clear().await)
+            .await;
+        let texture = offscreen.realize().await;
+
+        let mut renderer = SoftwareRenderer::new();
+        renderer.render(&scene).await?;
+        assert_eq!(renderer.offscreen_renderers[&texture.id].meshes.len(), 1);
+
+        // The target keeps its frame, so realizing it without the shape
+        // unloads the shape's mesh
+        offscreen.clear().await;
+        offscreen.realize().await;
+        scene.scene.clone().start_frame().await;
+        renderer.render(&scene).await?;
+        assert!(renderer.offscreen_renderers[&texture.id].meshes.is_empty());
+
+        // Dropping the target releases its texture
+        drop(offscreen);
+        scene.scene.clone().start_frame().await;
+        renderer.render(&scene).await?;
+        assert!(renderer.offscreen_renderers.is_empty());
+        assert!(renderer.offscreen_textures.is_empty());
+        Ok(())
+    }
+
+    #[async_test]
+    async fn transformed_shape_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let red = Rgba([255, 0, 0, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+        // Squashing a circle vertically draws an ellipse
+        Shape::circle(Point::<f32, Scaled>::new(2., 2.), Length::new(2.))
+            .fill(Fill::new(Color::RED))
+            .scaled_around(1., 0.5, Point::new(2., 2.))
+            .render_at(Point::default(), &scene)
+            .await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(4., 4.),
+            Size::new(4., 2.),
+        ))
+        .fill(Fill::new(Color::RED))
+        .rotated_around(Angle::degrees(90.), Point::new(6., 5.))
+        .render_at(Point::default(), &scene)
+        .await;
+        // Prepared shapes can be transformed without being tessellated again
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::default(),
+            Size::new(2., 1.),
+        ))
+        .fill(Fill::new(Color::RED))
+        .prepare(&scene)
+        .await?
+        .render_transformed_at(
+            Point::new(1., 5.),
+            &Transform2D::rotation(Angle::degrees(90.)),
+            &scene,
+        )
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.get_pixel(0, 2), &red);
+        assert_eq!(image.get_pixel(2, 0), &clear);
+        assert_eq!(image.get_pixel(5, 3), &red);
+        assert_eq!(image.get_pixel(5, 6), &red);
+        assert_eq!(image.get_pixel(4, 4), &clear);
+        assert_eq!(image.get_pixel(0, 5), &red);
+        assert_eq!(image.get_pixel(0, 6), &red);
+        assert_eq!(image.get_pixel(1, 5), &clear);
+        Ok(())
+    }
+
+    #[async_test]
+    async fn shape_primitives_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let red = Rgba([255, 0, 0, 255]);
+        let clear = Rgba([0, 0, 0, 0]);
+        Shape::rounded_rect(
+            Rect::<f32, Scaled>::new(Point::new(0., 0.), Size::new(4., 4.)),
+            CornerRadii::uniform(Length::new(2.)).with_top_right(Length::new(0.)),
+        )
+        .fill(Fill::new(Color::RED))
+        .render_at(Point::default(), &scene)
+        .await;
+        Shape::pie(
+            Point::<f32, Scaled>::new(6., 2.),
+            Length::new(2.),
+            Angle::zero(),
+            Angle::degrees(90.),
+        )
+        .fill(Fill::new(Color::RED))
+        .render_at(Point::default(), &scene)
+        .await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(0., 6.),
+            Size::new(8., 2.),
+        ))
+        .stroke(Stroke::new(Color::RED).with_dashes(StrokeDashes::dashed(2., 2.)))
+        .render_at(Point::default(), &scene)
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.get_pixel(0, 0), &clear);
+        assert_eq!(image.get_pixel(3, 0), &red);
+        assert_eq!(image.get_pixel(2, 2), &red);
+        assert_eq!(image.get_pixel(0, 3), &clear);
+        // Only the bottom-right quarter of the pie's circle is filled
+        assert_eq!(image.get_pixel(6, 2), &red);
+        assert_eq!(image.get_pixel(5, 1), &clear);
+        assert_eq!(image.get_pixel(6, 1), &clear);
+        // The dashes along the top edge continue around the corner, so the
+        // right edge begins with a dash
+        let dashes = (0..8)
+            .map(|x| image.get_pixel(x, 6)[3] > 0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dashes,
+            vec![true, true, false, false, true, true, false, true]
+        );
+        Ok(())
+    }
+
+    #[async_test]
+    async fn gradient_fill_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(0., 0.),
+            Size::new(8., 1.),
+        ))
+        .fill(Fill::new(
+            Gradient::linear(Point::new(0., 0.), Point::new(1., 0.))
+                .with_stop(0., Color::new(1., 0., 0., 1.))
+                .with_stop(0.5, Color::new(0., 1., 0., 1.))
+                .with_stop(1., Color::new(0., 0., 1., 1.)),
+        ))
+        .render_at(Point::default(), &scene)
+        .await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(0., 2.),
+            Size::new(6., 6.),
+        ))
+        .fill(Fill::new(
+            Gradient::radial(Point::new(0.5, 0.5), 0.5)
+                .with_stop(0., Color::WHITE)
+                .with_stop(1., Color::BLACK),
+        ))
+        .render_at(Point::default(), &scene)
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        // 3/8ths of the way from the first stop to the second
+        assert_eq!(image.get_pixel(1, 0), &Rgba([159, 96, 0, 255]));
+        assert_eq!(image.get_pixel(6, 0), &Rgba([0, 96, 159, 255]));
+        assert_eq!(image.get_pixel(0, 2), &Rgba([0, 0, 0, 255]));
+        // Radial gradients are interpolated between cells, so are approximate
+        let near_center = image.get_pixel(3, 5)[0];
+        assert!((190..200).contains(&near_center), "{}", near_center);
+        Ok(())
+    }
+
+    #[async_test]
+    async fn pattern_fill_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let mut image = RgbaImage::from_pixel(2, 1, red);
+        image.put_pixel(1, 0, blue);
+        let source =
+            SpriteSource::entire_texture(Texture::new(image::DynamicImage::ImageRgba8(image)))
+                .await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(1., 0.),
+            Size::new(5., 1.),
+        ))
+        .fill(Fill::new(Pattern::new(source.clone())))
+        .render_at(Point::default(), &scene)
+        .await;
+        Shape::rect(Rect::<f32, Scaled>::new(
+            Point::new(0., 2.),
+            Size::new(8., 2.),
+        ))
+        .fill(Fill::new(Pattern::new(source).with_scale(2.)))
+        .render_at(Point::default(), &scene)
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        let row = |y: u32, range: std::ops::Range<u32>| {
+            range.map(|x| *image.get_pixel(x, y)).collect::<Vec<_>>()
+        };
+        // Tiles begin at the top-left of the shape
+        assert_eq!(
+            row(0, 0..7),
+            vec![
+                Rgba([0, 0, 0, 0]),
+                red,
+                blue,
+                red,
+                blue,
+                red,
+                Rgba([0, 0, 0, 0])
+            ]
+        );
+        assert_eq!(
+            row(3, 0..8),
+            vec![red, red, blue, blue, red, red, blue, blue]
+        );
+        Ok(())
+    }
+
+    #[async_test]
+    async fn clipped_sprite_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let texture = Texture::new(image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            2,
+            2,
+            Rgba([0, 0, 255, 255]),
+        )));
+        let clipped = scene.clipped_to(Rect::new(Point::new(0, 0), Size::new(4, 8)));
+        SpriteSource::entire_texture(texture)
+            .await
+            .render_within(
+                &clipped,
+                Rect::new(Point::new(0., 0.), Size::new(8., 8.)),
+                SpriteRotation::default(),
+            )
+            .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.get_pixel(3, 3), &Rgba([0, 0, 255, 255]));
+        assert_eq!(image.get_pixel(5, 3), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn tinted_sprite_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let texture = Texture::new(image::DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            1,
+            1,
+            Rgba([255, 128, 0, 255]),
+        )));
+        let source = SpriteSource::entire_texture(texture).await;
+        let draw = |x: f32, tint: SpriteTint| {
+            let source = source.clone();
+            let scene = scene.clone();
+            async move {
+                source
+                    .render_with_tint(
+                        &scene,
+                        Rect::new(Point::new(x, 0.), Size::new(1., 1.)),
+                        SpriteRotation::default(),
+                        tint,
+                    )
+                    .await
+            }
+        };
+        draw(0., SpriteTint::default()).await;
+        draw(1., SpriteTint::new(Color::new(0.5, 1., 1., 1.))).await;
+        draw(2., SpriteTint::flash(Color::new(0., 0., 1., 0.5))).await;
+        draw(3., SpriteTint::default().with_alpha(0.)).await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.get_pixel(0, 0), &Rgba([255, 128, 0, 255]));
+        assert_eq!(image.get_pixel(1, 0), &Rgba([128, 128, 0, 255]));
+        assert_eq!(image.get_pixel(2, 0), &Rgba([255, 128, 128, 255]));
+        assert_eq!(image.get_pixel(3, 0), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+
+    #[async_test]
+    async fn transformed_sprite_test() -> KludgineResult<()> {
+        let scene = headless_scene().await;
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let mut image = RgbaImage::from_pixel(2, 2, red);
+        image.put_pixel(1, 0, blue);
+        image.put_pixel(1, 1, blue);
+        let source =
+            SpriteSource::entire_texture(Texture::new(image::DynamicImage::ImageRgba8(image)))
+                .await;
+        let draw = |origin: Point<f32, Scaled>, transform: SpriteTransform| {
+            let source = source.clone();
+            let scene = scene.clone();
+            async move {
+                source
+                    .render_transformed(
+                        &scene,
+                        Rect::new(origin, Size::new(2., 2.)),
+                        transform,
+                        SpriteTint::default(),
+                    )
+                    .await
+            }
+        };
+        draw(Point::new(0., 0.), SpriteTransform::default().flipped_x()).await;
+        draw(
+            Point::new(0., 4.),
+            SpriteTransform::default()
+                .with_scale(2., 1.)
+                .with_pivot(Point::new(0., 0.)),
+        )
+        .await;
+
+        let image = SoftwareRenderer::new().render(&scene).await?;
+        assert_eq!(image.get_pixel(0, 0), &blue);
+        assert_eq!(image.get_pixel(1, 1), &red);
+        // Scaled away from the pivot at the top-left corner
+        assert_eq!(image.get_pixel(1, 4), &red);
+        assert_eq!(image.get_pixel(2, 5), &blue);
+        assert_eq!(image.get_pixel(3, 5), &blue);
+        assert_eq!(image.get_pixel(4, 4), &Rgba([0, 0, 0, 0]));
+        Ok(())
+    }
+}